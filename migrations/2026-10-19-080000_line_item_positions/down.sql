@@ -0,0 +1,2 @@
+drop index idx_line_item_date_id_and_position;
+alter table line_items drop column position;
@@ -0,0 +1,13 @@
+alter table line_items add column position integer not null default 0;
+
+-- Keep the current (creation) order for existing line items
+update line_items
+set position = (
+    select count(*)
+    from line_items li
+    where li.line_item_date_id = line_items.line_item_date_id
+      and (li.created_at < line_items.created_at
+        or (li.created_at = line_items.created_at and li.id < line_items.id))
+);
+
+create index idx_line_item_date_id_and_position on line_items (line_item_date_id, position);
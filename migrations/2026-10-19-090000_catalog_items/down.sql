@@ -0,0 +1 @@
+drop table catalog_items;
@@ -0,0 +1,14 @@
+create table catalog_items (
+    id text not null primary key,
+    sku text not null,
+    name text not null,
+    description text,
+    unit text not null,
+    unit_price decimal(10,2) not null,
+    active boolean not null default true,
+    created_at text not null,
+    updated_at text not null
+);
+
+create unique index idx_catalog_items_sku on catalog_items (sku);
+create index idx_catalog_items_name on catalog_items (name);
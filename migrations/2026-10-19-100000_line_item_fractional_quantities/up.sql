@@ -0,0 +1,25 @@
+create table line_items_new (
+    id text not null primary key,
+    line_item_date_id text not null,
+    name text not null,
+    description text,
+    quantity decimal(10,3) not null,
+    unit_price decimal(10,2) not null,
+    created_at text not null,
+    updated_at text not null,
+    position integer not null default 0,
+    unit text not null default 'pcs',
+    foreign key(line_item_date_id) references line_item_dates(id)
+);
+
+insert into line_items_new
+    (id, line_item_date_id, name, description, quantity, unit_price, created_at, updated_at, position)
+select
+    id, line_item_date_id, name, description, quantity, unit_price, created_at, updated_at, position
+from line_items;
+
+drop table line_items;
+alter table line_items_new rename to line_items;
+
+create index idx_line_item_date_id on line_items (line_item_date_id);
+create index idx_line_item_date_id_and_position on line_items (line_item_date_id, position);
@@ -0,0 +1,17 @@
+create table users (
+    id text not null primary key,
+    username text not null,
+    name text not null,
+    role text not null,
+    created_at text not null,
+    updated_at text not null
+);
+
+create unique index idx_users_username on users (username);
+
+insert into users
+    (id, username, name, role, created_at, updated_at)
+values
+    ('01JABVYDQAK5W2Y443JSBWE9KH','accountant','Alex Accountant','accountant',strftime('%Y-%m-%dT%H:%M:%fZ'),strftime('%Y-%m-%dT%H:%M:%fZ')),
+    ('01JAB02NNR8WW3R95Y7KRVZ37K','manager','Morgan Manager','manager',strftime('%Y-%m-%dT%H:%M:%fZ'),strftime('%Y-%m-%dT%H:%M:%fZ')),
+    ('01JABVKEAE6KC7S8J9XXS61JRD','sales','Sam Sales','sales',strftime('%Y-%m-%dT%H:%M:%fZ'),strftime('%Y-%m-%dT%H:%M:%fZ'));
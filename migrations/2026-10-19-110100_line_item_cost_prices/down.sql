@@ -0,0 +1 @@
+alter table line_items drop column cost_price;
@@ -0,0 +1 @@
+alter table line_items add column cost_price decimal(10,2) not null default 0;
@@ -0,0 +1,3 @@
+drop trigger audit_entries_no_delete;
+drop trigger audit_entries_no_update;
+drop table audit_entries;
@@ -0,0 +1,24 @@
+create table audit_entries (
+    id text not null primary key,
+    quote_id text not null,
+    actor_id text not null,
+    actor_name text not null,
+    entity text not null,
+    entity_id text not null,
+    action text not null,
+    before_values text,
+    after_values text,
+    created_at text not null
+);
+
+create index idx_audit_entries_quote_id_and_created_at on audit_entries (quote_id, created_at);
+
+create trigger audit_entries_no_update before update on audit_entries
+begin
+    select raise(abort, 'audit entries are append-only');
+end;
+
+create trigger audit_entries_no_delete before delete on audit_entries
+begin
+    select raise(abort, 'audit entries are append-only');
+end;
@@ -0,0 +1,2 @@
+drop table comment_mentions;
+drop table comments;
@@ -0,0 +1,22 @@
+create table comments (
+    id text not null primary key,
+    quote_id text not null,
+    line_item_id text,
+    user_id text not null,
+    body text not null,
+    created_at text not null,
+    updated_at text not null,
+    foreign key(quote_id) references quotes(id),
+    foreign key(user_id) references users(id)
+);
+
+create index idx_comments_quote_id_and_created_at on comments (quote_id, created_at);
+
+create table comment_mentions (
+    comment_id text not null,
+    user_id text not null,
+    notified_at text,
+    primary key (comment_id, user_id),
+    foreign key(comment_id) references comments(id),
+    foreign key(user_id) references users(id)
+);
@@ -0,0 +1,14 @@
+create table attachments (
+    id text not null primary key,
+    quote_id text not null,
+    line_item_id text,
+    user_id text not null,
+    file_name text not null,
+    content_type text not null,
+    size integer not null,
+    created_at text not null,
+    foreign key(quote_id) references quotes(id),
+    foreign key(user_id) references users(id)
+);
+
+create index idx_attachments_quote_id on attachments (quote_id);
@@ -0,0 +1,2 @@
+drop table share_link_views;
+drop table share_links;
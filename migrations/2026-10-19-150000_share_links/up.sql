@@ -0,0 +1,23 @@
+create table share_links (
+    id text not null primary key,
+    quote_id text not null,
+    token text not null,
+    user_id text not null,
+    created_at text not null,
+    revoked_at text,
+    foreign key(quote_id) references quotes(id),
+    foreign key(user_id) references users(id)
+);
+
+create unique index idx_share_links_token on share_links (token);
+create index idx_share_links_quote_id on share_links (quote_id);
+
+create table share_link_views (
+    id text not null primary key,
+    share_link_id text not null,
+    user_agent text,
+    viewed_at text not null,
+    foreign key(share_link_id) references share_links(id)
+);
+
+create index idx_share_link_views_share_link_id on share_link_views (share_link_id);
@@ -0,0 +1,2 @@
+drop table acceptances;
+alter table quotes drop column status;
@@ -0,0 +1,19 @@
+alter table quotes add column status text not null default 'draft';
+
+create table acceptances (
+    id text not null primary key,
+    quote_id text not null,
+    share_link_id text not null,
+    decision text not null,
+    signer_name text not null,
+    signature text,
+    comment text,
+    snapshot text not null,
+    ip_address text,
+    created_at text not null,
+    foreign key(quote_id) references quotes(id),
+    foreign key(share_link_id) references share_links(id)
+);
+
+-- A quote is accepted or declined once
+create unique index idx_acceptances_quote_id on acceptances (quote_id);
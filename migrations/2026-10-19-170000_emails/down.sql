@@ -0,0 +1 @@
+drop table emails;
@@ -0,0 +1,15 @@
+create table emails (
+    id text not null primary key,
+    quote_id text not null,
+    user_id text not null,
+    recipient text not null,
+    subject text not null,
+    body text not null,
+    status text not null,
+    error text,
+    created_at text not null,
+    foreign key(quote_id) references quotes(id),
+    foreign key(user_id) references users(id)
+);
+
+create index idx_emails_quote_id_and_created_at on emails (quote_id, created_at);
@@ -0,0 +1,29 @@
+create table webhook_subscriptions (
+    id text not null primary key,
+    url text not null,
+    secret text not null,
+    -- Comma separated event types, e.g. `quote.created,quote.accepted`
+    events text not null,
+    active boolean not null default true,
+    created_at text not null,
+    updated_at text not null
+);
+
+-- Outbox of events to deliver, written in the same transaction as the change
+create table webhook_deliveries (
+    id text not null primary key,
+    subscription_id text not null,
+    event text not null,
+    payload text not null,
+    status text not null,
+    attempts integer not null default 0,
+    next_attempt_at text not null,
+    response_status integer,
+    last_error text,
+    created_at text not null,
+    delivered_at text,
+    foreign key(subscription_id) references webhook_subscriptions(id)
+);
+
+create index idx_webhook_deliveries_status_and_next_attempt_at on webhook_deliveries (status, next_attempt_at);
+create index idx_webhook_deliveries_created_at on webhook_deliveries (created_at);
@@ -0,0 +1,3 @@
+drop table template_line_items;
+drop table template_dates;
+drop table templates;
@@ -0,0 +1,31 @@
+create table templates (
+    id text not null primary key,
+    name text not null,
+    created_at text not null,
+    updated_at text not null
+);
+
+-- Days are relative to the start date chosen when a quote is created from the template, day 1 being the start date
+create table template_dates (
+    id text not null primary key,
+    template_id text not null,
+    day integer not null,
+    foreign key(template_id) references templates(id)
+);
+
+create unique index idx_template_dates_template_id_and_day on template_dates (template_id, day);
+
+create table template_line_items (
+    id text not null primary key,
+    template_date_id text not null,
+    name text not null,
+    description text,
+    quantity decimal(10,3) not null,
+    unit text not null,
+    unit_price decimal(10,2) not null,
+    cost_price decimal(10,2) not null,
+    position integer not null,
+    foreign key(template_date_id) references template_dates(id)
+);
+
+create index idx_template_line_items_template_date_id on template_line_items (template_date_id, position);
@@ -0,0 +1,13 @@
+drop index idx_template_dates_template_id_day_and_start_time;
+create unique index idx_template_dates_template_id_and_day on template_dates (template_id, day);
+
+alter table template_dates drop column timezone;
+alter table template_dates drop column end_time;
+alter table template_dates drop column start_time;
+
+drop index idx_quote_id_date_and_start_time;
+create unique index idx_quote_id_and_date on line_item_dates (quote_id, "date");
+
+alter table line_item_dates drop column timezone;
+alter table line_item_dates drop column end_time;
+alter table line_item_dates drop column start_time;
@@ -0,0 +1,15 @@
+-- Optional time slot of a date, in the timezone given or the local time of the venue
+alter table line_item_dates add column start_time text;
+alter table line_item_dates add column end_time text;
+alter table line_item_dates add column timezone text;
+
+-- Several time slots can share a day, a day without a time slot appearing only once
+drop index idx_quote_id_and_date;
+create unique index idx_quote_id_date_and_start_time on line_item_dates (quote_id, "date", coalesce(start_time, ''));
+
+alter table template_dates add column start_time text;
+alter table template_dates add column end_time text;
+alter table template_dates add column timezone text;
+
+drop index idx_template_dates_template_id_and_day;
+create unique index idx_template_dates_template_id_day_and_start_time on template_dates (template_id, day, coalesce(start_time, ''));
@@ -0,0 +1,6 @@
+drop index idx_line_items_resource_id;
+
+alter table template_line_items drop column resource_id;
+alter table line_items drop column resource_id;
+
+drop table resources;
@@ -0,0 +1,14 @@
+create table resources (
+    id text not null primary key,
+    name text not null,
+    -- Quantity available on any single day, shared by all quotes
+    capacity decimal(10,3) not null,
+    created_at text not null,
+    updated_at text not null
+);
+
+-- The quantity of a line item is booked from its resource, if any
+alter table line_items add column resource_id text;
+alter table template_line_items add column resource_id text;
+
+create index idx_line_items_resource_id on line_items (resource_id);
@@ -0,0 +1,2 @@
+-- The free text units are not kept, there is nothing to restore
+select 1;
@@ -0,0 +1,10 @@
+-- Catalog items predate the list of units, their free text units are mapped onto it
+update catalog_items
+set unit = case
+    when lower(trim(unit)) in ('pcs', 'pc', 'piece', 'pieces', 'each', 'ea', 'unit', 'units') then 'pcs'
+    when lower(trim(unit)) in ('hours', 'hour', 'hrs', 'hr', 'h') then 'hours'
+    when lower(trim(unit)) in ('days', 'day', 'd') then 'days'
+    when lower(trim(unit)) in ('m²', 'm2', 'sqm', 'sq m') then 'm²'
+    when lower(trim(unit)) in ('kg', 'kgs', 'kilo', 'kilos', 'kilogram', 'kilograms') then 'kg'
+    else 'pcs'
+end;
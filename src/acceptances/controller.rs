@@ -0,0 +1,28 @@
+use crate::{
+    Db, Result,
+    acceptances::{self, model::AcceptancePresenter, view::*},
+    rocket_ext::{HtmxRequest, HtmxResponder},
+};
+use rocket::fairing::AdHoc;
+
+pub(crate) fn stage() -> AdHoc {
+    AdHoc::on_ignite("Acceptance Stage", |rocket| async {
+        rocket.mount("/acceptances", routes![quote])
+    })
+}
+
+#[get("/quote/<quote_id>")]
+async fn quote(db: Db, htmx: HtmxRequest, quote_id: String) -> Result<HtmxResponder> {
+    let acceptance = db
+        .run(move |conn| {
+            let record =
+                acceptances::query::for_quote(conn, &quote_id)?.map(AcceptancePresenter::from);
+            Result::Ok(record)
+        })
+        .await?;
+
+    let template = Acceptance {
+        acceptance: &acceptance,
+    };
+    Ok(HtmxResponder::Ok(htmx.render("Acceptance", template)))
+}
@@ -0,0 +1,5 @@
+pub(crate) mod controller;
+pub(crate) mod query;
+// Required because structs generated by markup.rs macros are public
+pub mod model;
+pub mod view;
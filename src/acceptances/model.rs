@@ -0,0 +1,116 @@
+use crate::{
+    forms::{validate_decision, validate_signature},
+    quotes::model::STATUS_ACCEPTED,
+    schema::acceptances,
+    share_links::model::ShareLink,
+    time::timestamp,
+};
+use currency_rs::Currency;
+use diesel::prelude::*;
+use rocket::serde::json::{self, Value};
+use std::net::IpAddr;
+use time::OffsetDateTime;
+use ulid::Ulid;
+
+pub(crate) const SIGNATURE_PREFIX: &str = "data:image/png;base64,";
+pub(crate) const SIGNATURE_MAX_LENGTH: usize = 200_000;
+
+#[derive(Debug, Insertable, Queryable, Selectable)]
+#[diesel(table_name = acceptances)]
+pub(crate) struct Acceptance {
+    pub(crate) id: String,
+    pub(crate) quote_id: String,
+    pub(crate) share_link_id: String,
+    pub(crate) decision: String,
+    pub(crate) signer_name: String,
+    pub(crate) signature: Option<String>,
+    pub(crate) comment: Option<String>,
+    // The quote, its dates and line items as JSON at the time of the decision
+    pub(crate) snapshot: String,
+    pub(crate) ip_address: Option<String>,
+    pub(crate) created_at: OffsetDateTime,
+}
+
+impl Acceptance {
+    pub(crate) fn new(
+        share_link: &ShareLink,
+        form: &AcceptanceForm,
+        snapshot: String,
+        ip_address: Option<IpAddr>,
+    ) -> Acceptance {
+        Acceptance {
+            id: Ulid::new().to_string(),
+            quote_id: share_link.quote_id.clone(),
+            share_link_id: share_link.id.clone(),
+            decision: form.decision.clone(),
+            signer_name: form.signer_name.trim().to_string(),
+            signature: form
+                .signature
+                .clone()
+                .filter(|signature| !signature.is_empty()),
+            comment: form
+                .comment
+                .as_ref()
+                .map(|comment| comment.trim().to_string())
+                .filter(|comment| !comment.is_empty()),
+            snapshot,
+            ip_address: ip_address.map(|ip_address| ip_address.to_string()),
+            created_at: OffsetDateTime::now_utc(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, FromForm)]
+pub struct AcceptanceForm {
+    #[field(validate = validate_decision())]
+    pub(crate) decision: String,
+    #[field(validate = len(1..).or_else(msg!("Please type your name")))]
+    pub(crate) signer_name: String,
+    #[field(validate = validate_signature())]
+    pub(crate) signature: Option<String>,
+    pub(crate) comment: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct AcceptancePresenter {
+    pub accepted: bool,
+    pub signer_name: String,
+    pub signature: Option<String>,
+    pub comment: Option<String>,
+    pub total: String,
+    pub ip_address: String,
+    pub created_at: String,
+}
+
+impl AcceptancePresenter {
+    pub fn decision_name(&self) -> &str {
+        if self.accepted {
+            "Accepted"
+        } else {
+            "Declined"
+        }
+    }
+}
+
+impl From<Acceptance> for AcceptancePresenter {
+    fn from(value: Acceptance) -> Self {
+        // The total the customer agreed to, even if prices change later on
+        let total = json::from_str::<Value>(&value.snapshot)
+            .ok()
+            .and_then(|snapshot| {
+                let total = snapshot["quote"]["total"].as_str()?;
+                Currency::new_string(total, None).ok()
+            })
+            .map(|total| total.format())
+            .unwrap_or_default();
+        AcceptancePresenter {
+            accepted: value.decision == STATUS_ACCEPTED,
+            signer_name: value.signer_name,
+            signature: value.signature,
+            comment: value.comment,
+            total,
+            ip_address: value.ip_address.unwrap_or(String::from("unknown")),
+            created_at: timestamp(value.created_at),
+        }
+    }
+}
@@ -0,0 +1,123 @@
+use crate::{
+    Result,
+    acceptances::model::{Acceptance, AcceptanceForm},
+    audit_entries::{
+        self,
+        model::{ACTION_UPDATE, AuditEntry},
+    },
+    line_item_dates::{self, model::LineItemDatePresenter},
+    line_items::{self, model::LineItemPresenter},
+    quotes::{
+        self,
+        model::{Quote, QuotePresenter, STATUS_ACCEPTED},
+    },
+    schema::{acceptances, quotes as quotes_table},
+    share_links::model::ShareLink,
+    webhooks::{
+        self,
+        model::{QUOTE_ACCEPTED, QUOTE_REJECTED},
+    },
+};
+use diesel::prelude::*;
+use itertools::Itertools;
+use rocket::serde::json::{self, json};
+use std::net::IpAddr;
+
+pub(crate) fn for_quote<S: AsRef<str>>(
+    connection: &mut SqliteConnection,
+    quote_id: S,
+) -> Result<Option<Acceptance>> {
+    let record = acceptances::table
+        .filter(acceptances::quote_id.eq(quote_id.as_ref()))
+        .first(connection)
+        .optional()?;
+    Ok(record)
+}
+
+// Records the decision of the customer with what they saw, which locks the quote
+pub(crate) fn insert(
+    connection: &mut SqliteConnection,
+    share_link: &ShareLink,
+    form: &AcceptanceForm,
+    ip_address: Option<IpAddr>,
+) -> Result<Acceptance> {
+    connection.transaction::<_, crate::error::AppError, _>(|tx| {
+        quotes::query::ensure_editable(tx, &share_link.quote_id)?;
+
+        let snapshot = snapshot(tx, &share_link.quote_id)?;
+        let record = Acceptance::new(share_link, form, snapshot, ip_address);
+
+        diesel::dsl::insert_into(acceptances::table)
+            .values(&record)
+            .execute(tx)?;
+
+        let before: Quote = quotes_table::table.find(&record.quote_id).get_result(tx)?;
+        diesel::dsl::update(quotes_table::table)
+            .set(quotes_table::status.eq(&record.decision))
+            .filter(quotes_table::id.eq(&record.quote_id))
+            .execute(tx)?;
+        let after: Quote = quotes_table::table.find(&record.quote_id).get_result(tx)?;
+
+        // The customer has no user, the share link they came through stands in for them
+        let entry = AuditEntry::with_actor(
+            &record.share_link_id,
+            &record.signer_name,
+            ACTION_UPDATE,
+            &record.quote_id,
+            Some(&before),
+            Some(&after),
+        )?;
+        audit_entries::query::insert(tx, &entry)?;
+
+        let event = if record.decision == STATUS_ACCEPTED {
+            QUOTE_ACCEPTED
+        } else {
+            QUOTE_REJECTED
+        };
+        webhooks::query::enqueue(
+            tx,
+            event,
+            &record.quote_id,
+            &json!({
+                "signer_name": record.signer_name,
+                "comment": record.comment,
+                "ip_address": record.ip_address,
+                "snapshot": json::from_str::<json::Value>(&record.snapshot)?,
+            }),
+        )?;
+
+        Ok(record)
+    })
+}
+
+pub(crate) fn delete_all_for_quote<S: AsRef<str>>(
+    tx: &mut SqliteConnection,
+    quote_id: S,
+) -> Result {
+    _ = diesel::dsl::delete(acceptances::table)
+        .filter(acceptances::quote_id.eq(quote_id.as_ref()))
+        .execute(tx)?;
+    Ok(())
+}
+
+// Same shape as the JSON of the quote page
+fn snapshot(tx: &mut SqliteConnection, quote_id: &str) -> Result<String> {
+    let quote: QuotePresenter = quotes::query::read(tx, quote_id)?.into();
+    let line_item_dates = line_item_dates::query::all(tx, quote_id)?
+        .into_iter()
+        .map(|record| record.into())
+        .collect::<Vec<LineItemDatePresenter>>();
+    let line_items = line_items::query::all_for_quote(tx, quote_id)?
+        .into_iter()
+        .map(|record| record.into())
+        .collect::<Vec<LineItemPresenter>>()
+        .into_iter()
+        .into_group_map_by(|line_item| line_item.line_item_date_id.clone());
+
+    let snapshot = json::to_string(&json!({
+        "quote": quote,
+        "line_item_dates": line_item_dates,
+        "line_items": line_items,
+    }))?;
+    Ok(snapshot)
+}
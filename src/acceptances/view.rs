@@ -0,0 +1,117 @@
+use crate::{
+    acceptances::model::AcceptancePresenter,
+    quotes::model::{STATUS_ACCEPTED, STATUS_REJECTED},
+};
+
+markup::define! {
+    // Placeholder loading the answer of the customer to a quote
+    AcceptanceLoader<'a>(quote_id: &'a str) {
+        div[id = "quote_acceptance",
+            "hx-get" = {format!("/acceptances/quote/{quote_id}")},
+            "hx-trigger" = "load",
+            "hx-swap" = "outerHTML"] {}
+    }
+
+    Acceptance<'a>(acceptance: &'a Option<AcceptancePresenter>) {
+        div[id = "quote_acceptance", class = "mb-8"] {
+            @if let Some(acceptance) = acceptance {
+                div[class = "bg-white rounded-md p-4 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]"] {
+                    @Decision { acceptance }
+                    p[class = "text-[0.75rem] opacity-70 mt-2"] {
+                        "From " @acceptance.ip_address ". The quote can no longer be changed."
+                    }
+                }
+            }
+        }
+    }
+
+    Decision<'a>(acceptance: &'a AcceptancePresenter) {
+        div[class = "flex flex-wrap items-center gap-2"] {
+            span[class = "font-bold"] { @acceptance.decision_name() " by " @acceptance.signer_name }
+            span[class = "text-[0.75rem] opacity-70"] { @acceptance.created_at }
+            @if acceptance.accepted {
+                span[class = "ml-auto font-bold"] { @acceptance.total }
+            }
+        }
+        @if let Some(comment) = &acceptance.comment {
+            p[class = "mt-2 whitespace-pre-wrap"] { @comment }
+        }
+        @if let Some(signature) = &acceptance.signature {
+            img[class = "mt-2 max-h-24", src = signature, alt = "Signature"] {}
+        }
+    }
+
+    // Answer of the customer below the shared quote, or the form to give it
+    Answer<'a>(action: &'a str,
+               acceptance: &'a Option<AcceptancePresenter>,
+               signer_name: &'a str,
+               comment: &'a str,
+               errors: &'a Vec<String>) {
+        @if let Some(acceptance) = acceptance {
+            div[id = "acceptance", class = "bg-white rounded-md mt-8 p-4 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]"] {
+                @Decision { acceptance }
+            }
+        } else {
+            @AcceptanceForm { action, signer_name, comment, errors }
+        }
+    }
+
+    AcceptanceForm<'a>(action: &'a str,
+                       signer_name: &'a str,
+                       comment: &'a str,
+                       errors: &'a Vec<String>) {
+        form[id = "acceptance",
+            action = action,
+            method = "post",
+            class = "flex flex-col gap-3 bg-white rounded-md mt-8 p-4 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]",
+            autocomplete = "off",
+            novalidate,
+            "accept-charset" = "UTF-8"] {
+
+            h2[class = "text-[1.5rem] font-bold"] { "Your answer" }
+
+            @if !errors.is_empty() {
+                div[class = "w-full text-primary bg-primary-bg p-2 rounded-md"] {
+                    @for message in *errors {
+                        p { @message }
+                    }
+                }
+            }
+
+            label["for" = "acceptance_signer_name"] { "Your name" }
+            input[id = "acceptance_signer_name",
+                name = "signer_name",
+                class = "form-input",
+                required,
+                "type" = "text",
+                value = signer_name] {}
+
+            div[class = "signature-pad flex flex-col gap-2"] {
+                label["for" = "acceptance_signature"] { "Signature (optional)" }
+                canvas[id = "acceptance_signature",
+                    class = "w-full h-40 border-2 border-dashed border-[hsl(0,6%,93%)] rounded-md touch-none",
+                    width = "600",
+                    height = "160"] {}
+                input[name = "signature", "type" = "hidden", value = ""] {}
+                button[class = "button button-light self-start", "type" = "button"] { "Clear" }
+            }
+
+            label["for" = "acceptance_comment"] { "Comment (optional)" }
+            textarea[id = "acceptance_comment",
+                name = "comment",
+                class = "form-input",
+                rows = "3"] { @comment }
+
+            div[class = "flex gap-2 justify-end"] {
+                button[class = "button button-light",
+                    name = "decision",
+                    value = STATUS_REJECTED,
+                    "type" = "submit"] { "Decline" }
+                button[class = "button button-prime",
+                    name = "decision",
+                    value = STATUS_ACCEPTED,
+                    "type" = "submit"] { "Accept quote" }
+            }
+        }
+    }
+}
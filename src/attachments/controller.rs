@@ -0,0 +1,195 @@
+use crate::{
+    Db, Result,
+    attachments::{
+        self,
+        model::{Attachment, AttachmentPresenter, DeleteForm, NewAttachmentForm},
+        view::*,
+    },
+    config,
+    line_items::{self, model::LineItemPresenter},
+    rocket_ext::{HtmxRequest, HtmxResponder},
+    users::model::CurrentUser,
+};
+use rocket::{
+    fairing::AdHoc,
+    form::{Contextual, Error, Form},
+    fs::NamedFile,
+    http::{ContentType, Header},
+    response::Redirect,
+    tokio::fs,
+};
+use std::io::ErrorKind;
+
+pub(crate) fn stage() -> AdHoc {
+    AdHoc::on_ignite("Attachment Stage", |rocket| async {
+        rocket.mount("/attachments", routes![quote, download, create, delete])
+    })
+}
+
+#[derive(Responder)]
+struct Download {
+    file: NamedFile,
+    content_type: ContentType,
+    disposition: Header<'static>,
+}
+
+#[get("/quote/<quote_id>")]
+async fn quote(db: Db, htmx: HtmxRequest, quote_id: String) -> Result<HtmxResponder> {
+    let content = render(&db, &htmx, quote_id, Vec::new()).await?;
+    Ok(HtmxResponder::Ok(content))
+}
+
+#[get("/<id>")]
+async fn download(db: Db, id: String) -> Result<Option<Download>> {
+    let record = db
+        .run(move |conn| {
+            let record = attachments::query::read(conn, &id)?;
+            Result::Ok(record)
+        })
+        .await?;
+
+    let Ok(file) = NamedFile::open(record.path()).await else {
+        return Ok(None);
+    };
+
+    // Only printable ASCII survives in the header, the rest of the name is replaced
+    let file_name = record
+        .file_name
+        .chars()
+        .map(|c| {
+            if (c.is_ascii_graphic() && c != '"') || c == ' ' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect::<String>();
+    Ok(Some(Download {
+        file,
+        content_type: ContentType::parse_flexible(&record.content_type)
+            .unwrap_or(ContentType::Binary),
+        disposition: Header::new(
+            "Content-Disposition",
+            format!("attachment; filename=\"{file_name}\""),
+        ),
+    }))
+}
+
+#[post("/create", data = "<form>")]
+async fn create(
+    db: Db,
+    user: CurrentUser,
+    htmx: HtmxRequest,
+    mut form: Form<Contextual<'_, NewAttachmentForm<'_>>>,
+) -> Result<HtmxResponder> {
+    let quote_id = form
+        .context
+        .field_value("quote_id")
+        .unwrap_or("")
+        .to_string();
+
+    let line_item = form
+        .value
+        .as_ref()
+        .and_then(|value| Some((value.quote_id.clone(), value.line_item_id.clone()?)))
+        .filter(|(_, line_item_id)| !line_item_id.is_empty());
+    if let Some((quote_id, line_item_id)) = line_item {
+        let in_quote = db
+            .run(move |conn| attachments::query::line_item_in_quote(conn, &line_item_id, &quote_id))
+            .await?;
+        if !in_quote {
+            form.context.push_error(
+                Error::validation("Please choose an item of this quote").with_name("line_item_id"),
+            );
+            form.value = None;
+        }
+    }
+
+    let errors = form
+        .context
+        .errors()
+        .map(|error| error.to_string())
+        .collect::<Vec<String>>();
+
+    if let Some(attachment_form) = form.value.as_mut() {
+        let record = Attachment::new(&user.0.id, attachment_form)?;
+        let id = record.id.clone();
+        fs::create_dir_all(&config::get().attachments_directory).await?;
+        attachment_form.file.move_copy_to(record.path()).await?;
+
+        // The file is only kept when its record is stored
+        let inserted = db
+            .run(move |conn| attachments::query::insert(conn, &record))
+            .await;
+        if let Err(error) = inserted {
+            remove_file(&id).await?;
+            return Err(error);
+        }
+    }
+
+    if !htmx.0 && errors.is_empty() {
+        let uri = format!("/quotes/show/{}", quote_id);
+        return Ok(HtmxResponder::Redirect(Redirect::to(uri)));
+    }
+
+    let content = render(&db, &htmx, quote_id, errors).await?;
+    Ok(HtmxResponder::Ok(content))
+}
+
+#[post("/delete", data = "<form>")]
+async fn delete(db: Db, htmx: HtmxRequest, form: Form<DeleteForm>) -> Result<HtmxResponder> {
+    let record = db
+        .run(move |conn| {
+            let record = attachments::query::delete(conn, &form.id)?;
+            Result::Ok(record)
+        })
+        .await?;
+
+    remove_file(&record.id).await?;
+
+    if !htmx.0 {
+        let uri = format!("/quotes/show/{}", record.quote_id);
+        return Ok(HtmxResponder::Redirect(Redirect::to(uri)));
+    }
+
+    let content = render(&db, &htmx, record.quote_id, Vec::new()).await?;
+    Ok(HtmxResponder::Ok(content))
+}
+
+// Removes a stored file, one that is already gone is fine
+async fn remove_file(id: &str) -> Result {
+    match fs::remove_file(attachments::model::path(id)).await {
+        Err(error) if error.kind() != ErrorKind::NotFound => Err(error.into()),
+        _ => Ok(()),
+    }
+}
+
+async fn render(
+    db: &Db,
+    htmx: &HtmxRequest,
+    quote_id: String,
+    errors: Vec<String>,
+) -> Result<String> {
+    let id = quote_id.clone();
+    let (attachments, line_items) = db
+        .run(move |conn| {
+            let attachments = attachments::query::all_for_quote(conn, &id)?
+                .into_iter()
+                .map(|record| record.into())
+                .collect::<Vec<AttachmentPresenter>>();
+            let line_items = line_items::query::all_for_quote(conn, &id)?
+                .into_iter()
+                .map(|record| record.into())
+                .collect::<Vec<LineItemPresenter>>();
+            Result::Ok((attachments, line_items))
+        })
+        .await?;
+
+    let template = Attachments {
+        quote_id: &quote_id,
+        attachments: &attachments,
+        line_items: &line_items,
+        errors: &errors,
+    };
+    Ok(htmx.render("Attachments", template))
+}
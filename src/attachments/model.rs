@@ -0,0 +1,143 @@
+use crate::{Result, config, forms::validate_attachment, schema::attachments, time::timestamp};
+use diesel::prelude::*;
+use diesel::sql_types::*;
+use rocket::fs::TempFile;
+use std::path::{Path, PathBuf};
+use time::OffsetDateTime;
+use ulid::Ulid;
+
+// Drawings, photos, offers and the usual office documents
+const ALLOWED_CONTENT_TYPES: [&str; 12] = [
+    "application/pdf",
+    "image/jpeg",
+    "image/png",
+    "image/gif",
+    "image/webp",
+    "image/vnd.dxf",
+    "text/plain",
+    "text/csv",
+    "application/msword",
+    "application/vnd.openxmlformats-officedocument.wordprocessingml.document",
+    "application/vnd.ms-excel",
+    "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet",
+];
+
+// Name of the uploaded file as sent by the browser, without any directories
+pub(crate) fn file_name(file: &TempFile<'_>) -> Option<String> {
+    let raw_name = file.raw_name()?.dangerous_unsafe_unsanitized_raw().as_str();
+    let file_name = Path::new(raw_name).file_name()?.to_str()?;
+    Some(file_name.to_string())
+}
+
+// Type guessed from the file name, or `None` when it isn't one we accept
+pub(crate) fn content_type(file_name: &str) -> Option<&'static str> {
+    mime_guess::from_path(file_name)
+        .iter_raw()
+        .find(|content_type| ALLOWED_CONTENT_TYPES.contains(content_type))
+}
+
+#[derive(Debug, Insertable, Queryable)]
+#[diesel(table_name = attachments)]
+pub(crate) struct Attachment {
+    pub(crate) id: String,
+    pub(crate) quote_id: String,
+    pub(crate) line_item_id: Option<String>,
+    pub(crate) user_id: String,
+    pub(crate) file_name: String,
+    pub(crate) content_type: String,
+    pub(crate) size: i32,
+    pub(crate) created_at: OffsetDateTime,
+}
+
+impl Attachment {
+    pub(crate) fn new(user_id: &str, form: &NewAttachmentForm<'_>) -> Result<Attachment> {
+        let file_name = file_name(&form.file).unwrap_or_default();
+        let content_type = content_type(&file_name).unwrap_or("application/octet-stream");
+        Ok(Attachment {
+            id: Ulid::new().to_string(),
+            quote_id: form.quote_id.clone(),
+            line_item_id: form
+                .line_item_id
+                .clone()
+                .filter(|line_item_id| !line_item_id.is_empty()),
+            user_id: user_id.to_string(),
+            file_name,
+            content_type: content_type.to_string(),
+            size: i32::try_from(form.file.len())?,
+            created_at: OffsetDateTime::now_utc(),
+        })
+    }
+
+    // Files are stored under their id, the name given by the user is only kept in the database
+    pub(crate) fn path(&self) -> PathBuf {
+        path(&self.id)
+    }
+}
+
+pub(crate) fn path(id: &str) -> PathBuf {
+    config::get().attachments_directory.join(id)
+}
+
+#[derive(Debug, QueryableByName)]
+pub(crate) struct AttachmentWithDetails {
+    #[diesel(sql_type = Text)]
+    pub(crate) id: String,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub(crate) line_item_name: Option<String>,
+    #[diesel(sql_type = Text)]
+    pub(crate) file_name: String,
+    #[diesel(sql_type = Integer)]
+    pub(crate) size: i32,
+    #[diesel(sql_type = Text)]
+    pub(crate) author_name: String,
+    #[diesel(sql_type = TimestamptzSqlite)]
+    pub(crate) created_at: OffsetDateTime,
+}
+
+#[derive(Debug, FromForm)]
+pub struct NewAttachmentForm<'r> {
+    #[field(validate = len(1..))]
+    pub(crate) quote_id: String,
+    pub(crate) line_item_id: Option<String>,
+    #[field(validate = validate_attachment())]
+    pub(crate) file: TempFile<'r>,
+}
+
+#[derive(Debug, FromForm)]
+pub(crate) struct DeleteForm {
+    pub(crate) id: String,
+}
+
+#[derive(Debug)]
+pub struct AttachmentPresenter {
+    pub id: String,
+    pub line_item_name: Option<String>,
+    pub file_name: String,
+    pub size: String,
+    pub author_name: String,
+    pub created_at: String,
+}
+
+impl From<AttachmentWithDetails> for AttachmentPresenter {
+    fn from(value: AttachmentWithDetails) -> Self {
+        AttachmentPresenter {
+            id: value.id,
+            line_item_name: value.line_item_name,
+            file_name: value.file_name,
+            size: human_size(value.size),
+            author_name: value.author_name,
+            created_at: timestamp(value.created_at),
+        }
+    }
+}
+
+fn human_size(bytes: i32) -> String {
+    let bytes = f64::from(bytes);
+    if bytes < 1024f64 {
+        format!("{bytes} B")
+    } else if bytes < 1024f64 * 1024f64 {
+        format!("{:.1} KB", bytes / 1024f64)
+    } else {
+        format!("{:.1} MB", bytes / 1024f64 / 1024f64)
+    }
+}
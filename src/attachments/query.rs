@@ -0,0 +1,90 @@
+use crate::{
+    Result,
+    attachments::model::{Attachment, AttachmentWithDetails},
+    schema::{attachments, line_item_dates, line_items},
+};
+use diesel::prelude::*;
+
+pub(crate) fn all_for_quote<S: AsRef<str>>(
+    connection: &mut SqliteConnection,
+    quote_id: S,
+) -> Result<Vec<AttachmentWithDetails>> {
+    // language=SQL
+    let sql = r#"
+      select
+        a.id,
+        li.name as line_item_name,
+        a.file_name,
+        a.size,
+        u.name as author_name,
+        a.created_at
+      from attachments a
+        inner join users u on a.user_id = u.id
+        left join line_items li on a.line_item_id = li.id
+      where a.quote_id = ?
+      order by a.created_at, a.id
+    "#;
+    let records = diesel::dsl::sql_query(sql)
+        .bind::<diesel::sql_types::Text, _>(quote_id.as_ref())
+        .load(connection)?;
+    Ok(records)
+}
+
+pub(crate) fn read<S: AsRef<str>>(connection: &mut SqliteConnection, id: S) -> Result<Attachment> {
+    let record = attachments::table
+        .filter(attachments::id.eq(id.as_ref()))
+        .get_result(connection)?;
+    Ok(record)
+}
+
+// Whether the line item is on one of the dates of the quote
+pub(crate) fn line_item_in_quote(
+    connection: &mut SqliteConnection,
+    line_item_id: &str,
+    quote_id: &str,
+) -> Result<bool> {
+    let count: i64 = line_items::table
+        .inner_join(line_item_dates::table)
+        .filter(line_items::id.eq(line_item_id))
+        .filter(line_item_dates::quote_id.eq(quote_id))
+        .count()
+        .get_result(connection)?;
+    Ok(count > 0)
+}
+
+pub(crate) fn insert(connection: &mut SqliteConnection, record: &Attachment) -> Result {
+    diesel::dsl::insert_into(attachments::table)
+        .values(record)
+        .execute(connection)?;
+    Ok(())
+}
+
+pub(crate) fn delete<S: AsRef<str>>(
+    connection: &mut SqliteConnection,
+    id: S,
+) -> Result<Attachment> {
+    let record = read(connection, &id)?;
+
+    _ = diesel::dsl::delete(attachments::table)
+        .filter(attachments::id.eq(id.as_ref()))
+        .execute(connection)?;
+
+    Ok(record)
+}
+
+// Returns the ids of the deleted attachments so their files can be removed once committed
+pub(crate) fn delete_all_for_quote<S: AsRef<str>>(
+    tx: &mut SqliteConnection,
+    quote_id: S,
+) -> Result<Vec<String>> {
+    let ids = attachments::table
+        .select(attachments::id)
+        .filter(attachments::quote_id.eq(quote_id.as_ref()))
+        .get_results(tx)?;
+
+    _ = diesel::dsl::delete(attachments::table)
+        .filter(attachments::quote_id.eq(quote_id.as_ref()))
+        .execute(tx)?;
+
+    Ok(ids)
+}
@@ -0,0 +1,86 @@
+use crate::{
+    attachments::model::AttachmentPresenter,
+    line_items::{model::LineItemPresenter, view::LineItemOptions},
+};
+
+markup::define! {
+    // Placeholder loading the attachments of a quote
+    AttachmentsLoader<'a>(quote_id: &'a str) {
+        div[id = "quote_attachments",
+            "hx-get" = {format!("/attachments/quote/{quote_id}")},
+            "hx-trigger" = "load",
+            "hx-swap" = "outerHTML"] {}
+    }
+
+    Attachments<'a>(quote_id: &'a str,
+                    attachments: &'a Vec<AttachmentPresenter>,
+                    line_items: &'a Vec<LineItemPresenter>,
+                    errors: &'a Vec<String>) {
+        div[id = "quote_attachments", class = "mt-12"] {
+            h2[class = "text-[1.5rem] font-bold mb-2"] { "Attachments" }
+            div[class = "bg-white rounded-md p-4 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]"] {
+                @if !attachments.is_empty() {
+                    ul[class = "flex flex-col gap-2 mb-4"] {
+                        @for attachment in *attachments {
+                            li[class = "flex flex-wrap items-center gap-2 text-[0.875rem]"] {
+                                a[class = "font-bold underline",
+                                    href = {format!("/attachments/{}", attachment.id)}] { @attachment.file_name }
+                                @if let Some(line_item_name) = &attachment.line_item_name {
+                                    span { "on " @line_item_name }
+                                }
+                                span[class = "text-[0.75rem] opacity-70"] {
+                                    @attachment.size " · " @attachment.author_name " · " @attachment.created_at
+                                }
+                                form[class = "ml-auto",
+                                    action = "/attachments/delete",
+                                    method = "post",
+                                    "hx-post" = "/attachments/delete",
+                                    "hx-target" = "#quote_attachments",
+                                    "hx-swap" = "outerHTML"] {
+                                    input[name = "id", "type" = "hidden", value = &attachment.id] {}
+                                    button[class = "button button-light", "type" = "submit"] { "Delete" }
+                                }
+                            }
+                        }
+                    }
+                }
+
+                form[id = "attachment_new",
+                    action = "/attachments/create",
+                    method = "post",
+                    enctype = "multipart/form-data",
+                    "hx-post" = "/attachments/create",
+                    "hx-encoding" = "multipart/form-data",
+                    "hx-target" = "#quote_attachments",
+                    "hx-swap" = "outerHTML",
+                    class = "flex flex-wrap items-center gap-2",
+                    "accept-charset" = "UTF-8"] {
+
+                    @if !errors.is_empty() {
+                        div[class = "w-full text-primary bg-primary-bg p-2 rounded-md"] {
+                            @for message in *errors {
+                                p { @message }
+                            }
+                        }
+                    }
+
+                    input[name = "quote_id", "type" = "hidden", value = quote_id] {}
+                    label[class = "visually-hidden", "for" = "attachment_file"] { "File" }
+                    input[id = "attachment_file",
+                        name = "file",
+                        class = "form-input flex-1",
+                        required,
+                        "type" = "file"] {}
+                    label[class = "visually-hidden", "for" = "attachment_line_item_id"] { "About" }
+                    select[id = "attachment_line_item_id", name = "line_item_id", class = "form-input"] {
+                        @LineItemOptions { line_items, selected: "" }
+                    }
+                    input[name = "commit",
+                        "type" = "submit",
+                        value = "Upload",
+                        class = "button button-secondary"] {}
+                }
+            }
+        }
+    }
+}
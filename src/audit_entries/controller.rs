@@ -0,0 +1,37 @@
+use crate::{
+    Db, Result,
+    audit_entries::{self, model::AuditEntryPresenter, view::*},
+    rocket_ext::HtmxRequest,
+    users::model::CurrentUser,
+};
+use rocket::{fairing::AdHoc, response::content::RawHtml};
+
+pub(crate) fn stage() -> AdHoc {
+    AdHoc::on_ignite("AuditEntry Stage", |rocket| async {
+        rocket.mount("/audit_entries", routes![quote])
+    })
+}
+
+#[get("/quote/<quote_id>")]
+async fn quote(
+    db: Db,
+    user: CurrentUser,
+    htmx: HtmxRequest,
+    quote_id: String,
+) -> Result<RawHtml<String>> {
+    let can_view_margins = user.0.can_view_margins();
+    let audit_entries = db
+        .run(move |conn| {
+            let records = audit_entries::query::all_for_quote(conn, &quote_id)?
+                .into_iter()
+                .map(|record| AuditEntryPresenter::new(record, can_view_margins))
+                .collect::<Vec<AuditEntryPresenter>>();
+            Result::Ok(records)
+        })
+        .await?;
+
+    let template = Activity {
+        audit_entries: &audit_entries,
+    };
+    Ok(RawHtml(htmx.render("Activity", template)))
+}
@@ -0,0 +1,188 @@
+use crate::{schema::audit_entries, time::timestamp, users::model::User};
+use diesel::prelude::*;
+use rocket::serde::{
+    Serialize,
+    json::{self, Value},
+};
+use time::OffsetDateTime;
+use ulid::Ulid;
+
+pub(crate) const ACTION_CREATE: &str = "create";
+pub(crate) const ACTION_UPDATE: &str = "update";
+pub(crate) const ACTION_DELETE: &str = "delete";
+pub(crate) const ACTION_REORDER: &str = "reorder";
+
+const MARGIN_FIELDS: [&str; 1] = ["cost_price"];
+
+// Records whose changes are kept in the audit log, snapshotted as JSON
+pub(crate) trait Audited: Serialize {
+    const ENTITY: &'static str;
+
+    fn entity_id(&self) -> &str;
+}
+
+#[derive(Debug, Insertable, Queryable)]
+#[diesel(table_name = audit_entries)]
+pub(crate) struct AuditEntry {
+    pub(crate) id: String,
+    pub(crate) quote_id: String,
+    pub(crate) actor_id: String,
+    pub(crate) actor_name: String,
+    pub(crate) entity: String,
+    pub(crate) entity_id: String,
+    pub(crate) action: String,
+    pub(crate) before_values: Option<String>,
+    pub(crate) after_values: Option<String>,
+    pub(crate) created_at: OffsetDateTime,
+}
+
+impl AuditEntry {
+    pub(crate) fn new<T: Audited>(
+        actor: &User,
+        action: &str,
+        quote_id: &str,
+        before: Option<&T>,
+        after: Option<&T>,
+    ) -> crate::Result<AuditEntry> {
+        AuditEntry::with_actor(&actor.id, &actor.name, action, quote_id, before, after)
+    }
+
+    // For changes made by someone other than a user, like a customer deciding on a shared quote
+    pub(crate) fn with_actor<T: Audited>(
+        actor_id: &str,
+        actor_name: &str,
+        action: &str,
+        quote_id: &str,
+        before: Option<&T>,
+        after: Option<&T>,
+    ) -> crate::Result<AuditEntry> {
+        let entity_id = after
+            .or(before)
+            .map(|record| record.entity_id().to_string())
+            .unwrap_or_default();
+        Ok(AuditEntry {
+            id: Ulid::new().to_string(),
+            quote_id: quote_id.to_string(),
+            actor_id: actor_id.to_string(),
+            actor_name: actor_name.to_string(),
+            entity: T::ENTITY.to_string(),
+            entity_id,
+            action: action.to_string(),
+            before_values: before.map(json::to_string).transpose()?,
+            after_values: after.map(json::to_string).transpose()?,
+            created_at: OffsetDateTime::now_utc(),
+        })
+    }
+}
+
+#[derive(Debug)]
+pub struct ChangePresenter {
+    pub field: String,
+    pub before: Option<String>,
+    pub after: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct AuditEntryPresenter {
+    pub actor_name: String,
+    pub summary: String,
+    pub changes: Vec<ChangePresenter>,
+    pub created_at: String,
+}
+
+impl AuditEntryPresenter {
+    // Snapshots hold whole records, so internal prices are dropped for users who can't see margins
+    pub fn new(value: AuditEntry, can_view_margins: bool) -> Self {
+        let before = values(&value.before_values);
+        let after = values(&value.after_values);
+
+        let label = ["name", "date"]
+            .iter()
+            .find_map(|key| after.get(*key).or(before.get(*key)))
+            .map(text)
+            .unwrap_or(value.entity_id);
+        let action = match value.action.as_str() {
+            ACTION_CREATE => "created",
+            ACTION_UPDATE => "updated",
+            ACTION_DELETE => "deleted",
+            ACTION_REORDER => "reordered the items of",
+            action => action,
+        };
+        let summary = format!("{action} {} \"{label}\"", value.entity.replace('_', " "));
+
+        // Identifiers are left out, they mean nothing to the reader
+        let mut fields = [&before, &after]
+            .into_iter()
+            .filter_map(|values| values.as_object())
+            .flat_map(|values| values.keys().cloned())
+            .collect::<Vec<String>>();
+        fields.sort();
+        fields.dedup();
+        let changes = fields
+            .into_iter()
+            .filter(|field| field != "id" && !field.ends_with("_id"))
+            .filter(|field| can_view_margins || !MARGIN_FIELDS.contains(&field.as_str()))
+            .filter(|field| before.get(field) != after.get(field))
+            .map(|field| ChangePresenter {
+                before: before.get(&field).map(text),
+                after: after.get(&field).map(text),
+                field: field.replace('_', " "),
+            })
+            .collect();
+
+        AuditEntryPresenter {
+            actor_name: value.actor_name,
+            summary,
+            changes,
+            created_at: timestamp(value.created_at),
+        }
+    }
+}
+
+fn values(snapshot: &Option<String>) -> Value {
+    snapshot
+        .as_deref()
+        .and_then(|snapshot| json::from_str(snapshot).ok())
+        .unwrap_or_default()
+}
+
+fn text(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        Value::Array(values) => values.iter().map(text).collect::<Vec<String>>().join(", "),
+        value => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AuditEntry, AuditEntryPresenter};
+    use time::OffsetDateTime;
+
+    #[test]
+    fn cost_price_only_for_margins() {
+        let entry = || AuditEntry {
+            id: String::from("01HE2X4FKPDTVHHB6C2HZD5Z53"),
+            quote_id: String::from("01HE2X4FKPDTVHHB6C2HZD5Z53"),
+            actor_id: String::from("01HE2X51WKBYSJ7ZPETRB9STCQ"),
+            actor_name: String::from("Sales"),
+            entity: String::from("line_item"),
+            entity_id: String::from("01HE2X51WKBYSJ7ZPETRB9STCQ"),
+            action: String::from("update"),
+            before_values: Some(String::from(r#"{"name":"Room","cost_price":"10.00"}"#)),
+            after_values: Some(String::from(r#"{"name":"Suite","cost_price":"20.00"}"#)),
+            created_at: OffsetDateTime::UNIX_EPOCH,
+        };
+        let fields = |can_view_margins| {
+            AuditEntryPresenter::new(entry(), can_view_margins)
+                .changes
+                .into_iter()
+                .map(|change| change.field)
+                .collect::<Vec<String>>()
+        };
+
+        assert_eq!(vec!["cost price", "name"], fields(true));
+        assert_eq!(vec!["name"], fields(false));
+    }
+}
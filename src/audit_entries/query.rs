@@ -0,0 +1,21 @@
+use crate::{Result, audit_entries::model::AuditEntry, schema::audit_entries};
+use diesel::prelude::*;
+
+pub(crate) fn all_for_quote<S: AsRef<str>>(
+    connection: &mut SqliteConnection,
+    quote_id: S,
+) -> Result<Vec<AuditEntry>> {
+    let records = audit_entries::table
+        .filter(audit_entries::quote_id.eq(&quote_id.as_ref()))
+        .order_by((audit_entries::created_at.desc(), audit_entries::id.desc()))
+        .get_results(connection)?;
+    Ok(records)
+}
+
+// Entries are append-only, the table refuses updates and deletes
+pub(crate) fn insert(connection: &mut SqliteConnection, record: &AuditEntry) -> Result {
+    diesel::dsl::insert_into(audit_entries::table)
+        .values(record)
+        .execute(connection)?;
+    Ok(())
+}
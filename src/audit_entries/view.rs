@@ -0,0 +1,46 @@
+use crate::audit_entries::model::AuditEntryPresenter;
+
+markup::define! {
+    // Placeholder loading the timeline, swapped out of band to refresh it after each change
+    ActivityLoader<'a>(quote_id: &'a str, oob: bool) {
+        div[id = "quote_activity",
+            "hx-swap-oob" = oob.then_some("true"),
+            "hx-get" = {format!("/audit_entries/quote/{quote_id}")},
+            "hx-trigger" = "load",
+            "hx-swap" = "outerHTML"] {}
+    }
+
+    Activity<'a>(audit_entries: &'a Vec<AuditEntryPresenter>) {
+        div[id = "quote_activity", class = "mt-12"] {
+            h2[class = "text-[1.5rem] font-bold mb-2"] { "Activity" }
+            div[class = "bg-white rounded-md p-4 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]"] {
+                @if audit_entries.is_empty() {
+                    p[class = "text-[0.875rem]"] { "No changes recorded yet." }
+                }
+                ol[class = "flex flex-col gap-3 max-h-[24rem] overflow-y-auto"] {
+                    @for audit_entry in *audit_entries {
+                        li[class = "border-l-2 border-[hsl(0,6%,93%)] pl-3"] {
+                            div[class = "text-[0.875rem]"] {
+                                span[class = "font-bold"] { @audit_entry.actor_name }
+                                " " @audit_entry.summary
+                                span[class = "ml-2 text-[0.75rem] opacity-70"] { @audit_entry.created_at }
+                            }
+                            @if !audit_entry.changes.is_empty() {
+                                ul[class = "text-[0.75rem] mt-1"] {
+                                    @for change in &audit_entry.changes {
+                                        li {
+                                            span[class = "font-bold"] { @change.field ": " }
+                                            @change.before.as_deref().unwrap_or("—")
+                                            " → "
+                                            @change.after.as_deref().unwrap_or("—")
+                                        }
+                                    }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use crate::{
+    Db, Result,
+    calendar::{self, model::Calendar, view::*},
+    config,
+    layout::Layout,
+    quotes,
+};
+use hmac::{Hmac, Mac};
+use rocket::{
+    fairing::AdHoc,
+    http::{ContentType, Header},
+    response::content::RawHtml,
+};
+use sha2::Sha256;
+use time::OffsetDateTime;
+
+pub(crate) fn stage() -> AdHoc {
+    AdHoc::on_ignite("Calendar Stage", |rocket| async {
+        rocket
+            .mount("/calendar", routes![index, feed])
+            .mount("/quotes", routes![quote])
+    })
+}
+
+#[derive(Responder)]
+struct Ics {
+    content: String,
+    content_type: ContentType,
+    disposition: Header<'static>,
+}
+
+impl Ics {
+    fn new(content: String, file_name: &str) -> Self {
+        Ics {
+            content,
+            content_type: ContentType::new("text", "calendar").with_params(("charset", "utf-8")),
+            disposition: Header::new(
+                "Content-Disposition",
+                format!("inline; filename=\"{file_name}\""),
+            ),
+        }
+    }
+}
+
+#[get("/?<view>&<date>")]
+async fn index(db: Db, view: Option<&str>, date: Option<&str>) -> Result<RawHtml<String>> {
+    let calendar = Calendar::new(view, date, OffsetDateTime::now_utc().date());
+    let (first, last) = calendar.range();
+    let entries = db
+        .run(move |conn| calendar::query::all_between(conn, first, last))
+        .await?;
+    let days = calendar.days(entries);
+
+    let feed_url = config::get().calendar_feed_token.as_ref().map(|token| {
+        format!(
+            "{}/calendar/feed.ics?token={token}",
+            config::get().public_url.trim_end_matches('/')
+        )
+    });
+
+    let template = Layout {
+        head: markup::new! {
+            title { "Calendar" }
+        },
+        body: Index {
+            calendar: &calendar,
+            days: &days,
+            feed_url: feed_url.as_deref(),
+        },
+    };
+    Ok(RawHtml(template.to_string()))
+}
+
+// Subscription feed of every quote, only known to those given the token
+#[get("/feed.ics?<token>")]
+async fn feed(db: Db, token: Option<&str>) -> Result<Option<Ics>> {
+    let expected = config::get().calendar_feed_token.as_deref();
+    match (token, expected) {
+        (Some(token), Some(expected)) if token_matches(token, expected) => {}
+        _ => return Ok(None),
+    }
+
+    let events = db
+        .run(move |conn| calendar::query::events(conn, None))
+        .await?;
+    let content = calendar::model::ics("Quotes", &config::get().public_url, &events);
+    Ok(Some(Ics::new(content, "quotes.ics")))
+}
+
+// Compares the HMAC digests of both tokens in constant time, so that the time taken tells nothing
+// of how much of the token given was right
+fn token_matches(token: &str, expected: &str) -> bool {
+    let digest = |value: &str| {
+        let mut mac = Hmac::<Sha256>::new_from_slice(expected.as_bytes())
+            .expect("HMAC accepts keys of any size");
+        mac.update(value.as_bytes());
+        mac
+    };
+    digest(expected)
+        .verify_slice(&digest(token).finalize().into_bytes())
+        .is_ok()
+}
+
+// Ranked after the `/quotes/<action>/<id>` routes it would otherwise collide with
+#[get("/<id>/calendar.ics", rank = 2)]
+async fn quote(db: Db, id: String) -> Result<Ics> {
+    let (quote, events) = db
+        .run(move |conn| {
+            let quote = quotes::query::read(conn, &id)?;
+            let events = calendar::query::events(conn, Some(&id))?;
+            Result::Ok((quote, events))
+        })
+        .await?;
+
+    let content = calendar::model::ics(&quote.name, &config::get().public_url, &events);
+    Ok(Ics::new(content, &format!("quote-{}.ics", quote.id)))
+}
@@ -0,0 +1,408 @@
+use crate::{
+    line_item_dates::model::time_range,
+    quotes::model::{STATUS_DRAFT, STATUS_REJECTED},
+    time::{DATE_FORMAT, long_form, short_form},
+};
+use currency_rs::Currency;
+use diesel::prelude::*;
+use diesel::sql_types::*;
+use time::{
+    Date, Duration, Month, OffsetDateTime, PrimitiveDateTime, Time, UtcOffset,
+    macros::format_description,
+};
+use time_tz::{PrimitiveDateTimeExt, timezones};
+
+pub(crate) const VIEW_MONTH: &str = "month";
+pub(crate) const VIEW_WEEK: &str = "week";
+
+// A date of a quote, as shown on the calendar
+#[derive(Debug, QueryableByName)]
+pub struct CalendarEntry {
+    #[diesel(sql_type = Text)]
+    pub id: String,
+    #[diesel(sql_type = Text)]
+    pub quote_id: String,
+    #[diesel(sql_type = Text)]
+    pub quote_name: String,
+    #[diesel(sql_type = Text)]
+    pub quote_status: String,
+    #[diesel(sql_type = diesel::sql_types::Date)]
+    pub date: Date,
+    #[diesel(sql_type = Nullable<diesel::sql_types::Time>)]
+    pub start_time: Option<Time>,
+    #[diesel(sql_type = Nullable<diesel::sql_types::Time>)]
+    pub end_time: Option<Time>,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub timezone: Option<String>,
+    #[diesel(sql_type = currency_rs::diesel2::sqlite::sql_types::Currency)]
+    pub subtotal: Currency,
+}
+
+impl CalendarEntry {
+    pub fn href(&self) -> String {
+        format!("/quotes/show/{}#line_item_date_{}", self.quote_id, self.id)
+    }
+
+    pub fn time_range(&self) -> Option<String> {
+        time_range(self.start_time, self.end_time, &self.timezone)
+    }
+
+    // Quotes not accepted yet are not a commitment
+    pub fn is_tentative(&self) -> bool {
+        self.quote_status == STATUS_DRAFT
+    }
+}
+
+#[derive(Debug)]
+pub struct CalendarDay {
+    pub date: Date,
+    pub in_period: bool,
+    pub entries: Vec<CalendarEntry>,
+}
+
+// Monday of the week of a date
+pub(crate) fn week_start(date: Date) -> Date {
+    date - Duration::days(i64::from(date.weekday().number_days_from_monday()))
+}
+
+fn month_start(date: Date) -> Date {
+    date.replace_day(1).unwrap()
+}
+
+fn next_month_start(date: Date) -> Date {
+    let year = match date.month() {
+        Month::December => date.year() + 1,
+        _ => date.year(),
+    };
+    Date::from_calendar_date(year, date.month().next(), 1).unwrap()
+}
+
+// The month or week around a date, shown as whole weeks from Monday to Sunday
+#[derive(Debug)]
+pub struct Calendar {
+    pub view: &'static str,
+    pub date: Date,
+}
+
+impl Calendar {
+    pub(crate) fn new(view: Option<&str>, date: Option<&str>, today: Date) -> Self {
+        let view = match view {
+            Some(VIEW_WEEK) => VIEW_WEEK,
+            _ => VIEW_MONTH,
+        };
+        let date = date
+            .and_then(|date| Date::parse(date, DATE_FORMAT).ok())
+            .unwrap_or(today);
+        Calendar { view, date }
+    }
+
+    pub fn is_week(&self) -> bool {
+        self.view == VIEW_WEEK
+    }
+
+    // First and last day of the period itself
+    pub fn period(&self) -> (Date, Date) {
+        if self.is_week() {
+            let first = week_start(self.date);
+            (first, first + Duration::days(6))
+        } else {
+            let first = month_start(self.date);
+            (first, next_month_start(self.date) - Duration::days(1))
+        }
+    }
+
+    // First and last day shown
+    pub fn range(&self) -> (Date, Date) {
+        let (first, last) = self.period();
+        (week_start(first), week_start(last) + Duration::days(6))
+    }
+
+    pub fn title(&self) -> String {
+        let (first, _) = self.period();
+        if self.is_week() {
+            format!("Week of {}", long_form(first))
+        } else {
+            format!("{} {}", first.month(), first.year())
+        }
+    }
+
+    pub fn previous_href(&self) -> String {
+        let (first, _) = self.period();
+        let date = if self.is_week() {
+            first - Duration::days(7)
+        } else {
+            month_start(first - Duration::days(1))
+        };
+        href(self.view, date)
+    }
+
+    pub fn next_href(&self) -> String {
+        let (_, last) = self.period();
+        href(self.view, last + Duration::days(1))
+    }
+
+    pub fn view_href(&self, view: &str) -> String {
+        href(view, self.date)
+    }
+
+    pub(crate) fn days(&self, entries: Vec<CalendarEntry>) -> Vec<CalendarDay> {
+        let (first, last) = self.range();
+        let (period_first, period_last) = self.period();
+        let mut entries = entries.into_iter().peekable();
+
+        let mut days = Vec::new();
+        let mut date = first;
+        while date <= last {
+            let mut day = CalendarDay {
+                date,
+                in_period: period_first <= date && date <= period_last,
+                entries: Vec::new(),
+            };
+            while let Some(entry) = entries.next_if(|entry| entry.date <= date) {
+                if entry.date == date {
+                    day.entries.push(entry);
+                }
+            }
+            days.push(day);
+            date += Duration::days(1);
+        }
+        days
+    }
+}
+
+pub(crate) fn href(view: &str, date: Date) -> String {
+    format!("/calendar?view={view}&date={}", short_form(date))
+}
+
+// A date of a quote exported to calendar apps
+#[derive(Debug, QueryableByName)]
+pub struct CalendarEvent {
+    #[diesel(sql_type = Text)]
+    pub id: String,
+    #[diesel(sql_type = Text)]
+    pub quote_id: String,
+    #[diesel(sql_type = Text)]
+    pub quote_name: String,
+    #[diesel(sql_type = Text)]
+    pub quote_status: String,
+    #[diesel(sql_type = diesel::sql_types::Date)]
+    pub date: Date,
+    #[diesel(sql_type = Nullable<diesel::sql_types::Time>)]
+    pub start_time: Option<Time>,
+    #[diesel(sql_type = Nullable<diesel::sql_types::Time>)]
+    pub end_time: Option<Time>,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub timezone: Option<String>,
+    #[diesel(sql_type = TimestamptzSqlite)]
+    pub updated_at: OffsetDateTime,
+}
+
+impl CalendarEvent {
+    // Lines of the VEVENT, the UID coming from the date so that an export imported again or a
+    // refreshed feed updates the event instead of adding another one
+    fn lines(&self, host: &str, public_url: &str, line_items: &[String]) -> Vec<String> {
+        let status = match self.quote_status.as_str() {
+            STATUS_DRAFT => "TENTATIVE",
+            STATUS_REJECTED => "CANCELLED",
+            _ => "CONFIRMED",
+        };
+        let stamp = ics_timestamp(self.updated_at);
+
+        let mut lines = vec![
+            String::from("BEGIN:VEVENT"),
+            format!("UID:{}@{host}", self.id),
+            format!("DTSTAMP:{stamp}"),
+            format!("LAST-MODIFIED:{stamp}"),
+        ];
+        match self.start_time {
+            Some(start_time) => {
+                lines.push(format!("DTSTART{}", self.ics_time(start_time)));
+                if let Some(end_time) = self.end_time {
+                    lines.push(format!("DTEND{}", self.ics_time(end_time)));
+                }
+            }
+            None => {
+                lines.push(format!("DTSTART;VALUE=DATE:{}", ics_date(self.date)));
+                lines.push(format!(
+                    "DTEND;VALUE=DATE:{}",
+                    ics_date(self.date + Duration::days(1))
+                ));
+            }
+        }
+        lines.push(format!("SUMMARY:{}", ics_escape(&self.quote_name)));
+        if !line_items.is_empty() {
+            lines.push(format!(
+                "DESCRIPTION:{}",
+                ics_escape(&line_items.join("\n"))
+            ));
+        }
+        lines.push(format!("URL:{public_url}/quotes/show/{}", self.quote_id));
+        lines.push(format!("STATUS:{status}"));
+        lines.push(String::from("END:VEVENT"));
+        lines
+    }
+
+    // UTC time converted from the timezone given, so that no VTIMEZONE is needed, or floating
+    // time when there is none, its name is unknown or the local time falls in a DST gap
+    fn ics_time(&self, time: Time) -> String {
+        let date_time = PrimitiveDateTime::new(self.date, time);
+        match self
+            .timezone
+            .as_deref()
+            .and_then(timezones::get_by_name)
+            .and_then(|timezone| date_time.assume_timezone(timezone).take_first())
+        {
+            Some(moment) => format!(":{}", ics_timestamp(moment)),
+            None => format!(
+                ":{}",
+                date_time
+                    .format(format_description!(
+                        "[year][month][day]T[hour][minute][second]"
+                    ))
+                    .unwrap()
+            ),
+        }
+    }
+}
+
+fn ics_date(date: Date) -> String {
+    date.format(format_description!("[year][month][day]"))
+        .unwrap()
+}
+
+fn ics_timestamp(moment: OffsetDateTime) -> String {
+    moment
+        .to_offset(UtcOffset::UTC)
+        .format(format_description!(
+            "[year][month][day]T[hour][minute][second]Z"
+        ))
+        .unwrap()
+}
+
+// Text values escape backslashes, separators and new lines, carriage returns being dropped
+fn ics_escape(text: &str) -> String {
+    text.replace('\r', "")
+        .replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+// Content lines are at most 75 octets, longer ones continue on lines starting with a space
+fn ics_fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len() + line.len() / 74 * 3);
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > 75 {
+            folded.push_str("\r\n ");
+            length = 1;
+        }
+        folded.push(c);
+        length += c.len_utf8();
+    }
+    folded
+}
+
+// An iCalendar file of events, each with its line items as the description
+pub(crate) fn ics(name: &str, public_url: &str, events: &[(CalendarEvent, Vec<String>)]) -> String {
+    let public_url = public_url.trim_end_matches('/');
+    let host = public_url
+        .split("://")
+        .last()
+        .and_then(|rest| rest.split(['/', ':']).next())
+        .unwrap_or("localhost");
+
+    let mut lines = vec![
+        String::from("BEGIN:VCALENDAR"),
+        String::from("VERSION:2.0"),
+        String::from("PRODID:-//ABC Corp//Quotes//EN"),
+        String::from("CALSCALE:GREGORIAN"),
+        format!("X-WR-CALNAME:{}", ics_escape(name)),
+    ];
+    for (event, line_items) in events {
+        lines.extend(event.lines(host, public_url, line_items));
+    }
+    lines.push(String::from("END:VCALENDAR"));
+
+    lines
+        .iter()
+        .map(|line| ics_fold(line) + "\r\n")
+        .collect::<String>()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Calendar, CalendarEvent, VIEW_MONTH, VIEW_WEEK, ics, ics_fold};
+    use time::{Date, Month, OffsetDateTime, Time};
+
+    #[test]
+    fn ranges() {
+        let today = Date::from_calendar_date(2024, Month::February, 14).unwrap();
+        let calendar = Calendar::new(None, Some("2024-02-29"), today);
+        assert_eq!(VIEW_MONTH, calendar.view);
+        assert_eq!(
+            (
+                Date::from_calendar_date(2024, Month::February, 1).unwrap(),
+                Date::from_calendar_date(2024, Month::February, 29).unwrap(),
+            ),
+            calendar.period()
+        );
+        assert_eq!(
+            (
+                Date::from_calendar_date(2024, Month::January, 29).unwrap(),
+                Date::from_calendar_date(2024, Month::March, 3).unwrap(),
+            ),
+            calendar.range()
+        );
+        assert_eq!(
+            "/calendar?view=month&date=2024-01-01",
+            calendar.previous_href()
+        );
+        assert_eq!("/calendar?view=month&date=2024-03-01", calendar.next_href());
+
+        let calendar = Calendar::new(Some(VIEW_WEEK), Some("not a date"), today);
+        assert_eq!(
+            (
+                Date::from_calendar_date(2024, Month::February, 12).unwrap(),
+                Date::from_calendar_date(2024, Month::February, 18).unwrap(),
+            ),
+            calendar.range()
+        );
+        assert_eq!("Week of February 12, 2024", calendar.title());
+    }
+
+    #[test]
+    fn ics_events() {
+        let event = CalendarEvent {
+            id: String::from("01HQ5Z8X9K3M4N5P6Q7R8S9T0V"),
+            quote_id: String::from("01HQ5Z7A1B2C3D4E5F6G7H8J9K"),
+            quote_name: String::from("Gala dinner; Smith, Jones\r\n"),
+            quote_status: String::from("draft"),
+            date: Date::from_calendar_date(2024, Month::February, 29).unwrap(),
+            start_time: Some(Time::from_hms(18, 30, 0).unwrap()),
+            end_time: Some(Time::from_hms(23, 0, 0).unwrap()),
+            timezone: Some(String::from("Europe/Paris")),
+            updated_at: OffsetDateTime::UNIX_EPOCH,
+        };
+        let line_items = vec![String::from("80 pcs Chairs"), String::from("1 h Setup")];
+        let text = ics(
+            "Quotes",
+            "https://quotes.example.com/",
+            &[(event, line_items)],
+        );
+
+        assert!(text.starts_with("BEGIN:VCALENDAR\r\n"));
+        assert!(text.ends_with("END:VCALENDAR\r\n"));
+        assert!(text.contains("\r\nUID:01HQ5Z8X9K3M4N5P6Q7R8S9T0V@quotes.example.com\r\n"));
+        assert!(text.contains("\r\nDTSTAMP:19700101T000000Z\r\n"));
+        assert!(text.contains("\r\nDTSTART:20240229T173000Z\r\n"));
+        assert!(text.contains("\r\nDTEND:20240229T220000Z\r\n"));
+        assert!(text.contains("\r\nSUMMARY:Gala dinner\\; Smith\\, Jones\\n\r\n"));
+        assert!(text.contains("\r\nDESCRIPTION:80 pcs Chairs\\n1 h Setup\r\n"));
+        assert!(text.contains("\r\nSTATUS:TENTATIVE\r\n"));
+
+        let folded = ics_fold(&"é".repeat(50));
+        assert_eq!(Some(74), folded.split("\r\n").next().map(str::len));
+        assert!(folded.split("\r\n").all(|line| line.len() <= 75));
+    }
+}
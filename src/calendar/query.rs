@@ -0,0 +1,106 @@
+use crate::{
+    Result,
+    calendar::model::{CalendarEntry, CalendarEvent},
+    line_items::model::LineItem,
+    quantity::format_quantity,
+    quotes::model::STATUS_REJECTED,
+    schema::{line_item_dates, line_items, quotes},
+};
+use diesel::prelude::*;
+use std::collections::HashMap;
+use time::Date;
+
+// Dates of every quote but the declined ones from `first` to `last`, in chronological order
+pub(crate) fn all_between(
+    connection: &mut SqliteConnection,
+    first: Date,
+    last: Date,
+) -> Result<Vec<CalendarEntry>> {
+    // language=SQL
+    let sql = r#"
+      select
+        lid.id,
+        lid.quote_id,
+        q.name as quote_name,
+        q.status as quote_status,
+        lid."date",
+        lid.start_time,
+        lid.end_time,
+        lid.timezone,
+        (select
+            coalesce(sum(li.quantity * li.unit_price), 0)
+          from line_items li
+          where li.line_item_date_id = lid.id) as subtotal
+      from line_item_dates lid
+        inner join quotes q on lid.quote_id = q.id
+      where lid."date" between ? and ?
+        and q.status != ?
+      order by lid."date", lid.start_time, q.name
+    "#;
+    let records = diesel::dsl::sql_query(sql)
+        .bind::<diesel::sql_types::Date, _>(first)
+        .bind::<diesel::sql_types::Date, _>(last)
+        .bind::<diesel::sql_types::Text, _>(STATUS_REJECTED)
+        .load(connection)?;
+    Ok(records)
+}
+
+// Dates of a quote, or of every quote but the declined ones, with their line items as text
+pub(crate) fn events(
+    connection: &mut SqliteConnection,
+    quote_id: Option<&str>,
+) -> Result<Vec<(CalendarEvent, Vec<String>)>> {
+    // language=SQL
+    let sql = r#"
+      select
+        lid.id,
+        lid.quote_id,
+        q.name as quote_name,
+        q.status as quote_status,
+        lid."date",
+        lid.start_time,
+        lid.end_time,
+        lid.timezone,
+        lid.updated_at
+      from line_item_dates lid
+        inner join quotes q on lid.quote_id = q.id
+      where lid.quote_id = ?
+        or (? is null and q.status != ?)
+      order by lid."date", lid.start_time, q.name
+    "#;
+    let records: Vec<CalendarEvent> = diesel::dsl::sql_query(sql)
+        .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(quote_id)
+        .bind::<diesel::sql_types::Nullable<diesel::sql_types::Text>, _>(quote_id)
+        .bind::<diesel::sql_types::Text, _>(STATUS_REJECTED)
+        .load(connection)?;
+
+    let query = line_items::table
+        .inner_join(line_item_dates::table.inner_join(quotes::table))
+        .select(line_items::all_columns)
+        .order_by((line_items::position, line_items::created_at))
+        .into_boxed();
+    let query = match quote_id {
+        Some(quote_id) => query.filter(line_item_dates::quote_id.eq(quote_id)),
+        None => query.filter(quotes::status.ne(STATUS_REJECTED)),
+    };
+    let mut descriptions: HashMap<String, Vec<String>> = HashMap::new();
+    for line_item in query.load::<LineItem>(connection)? {
+        descriptions
+            .entry(line_item.line_item_date_id)
+            .or_default()
+            .push(format!(
+                "{} {} {}",
+                format_quantity(line_item.quantity),
+                line_item.unit,
+                line_item.name
+            ));
+    }
+
+    Ok(records
+        .into_iter()
+        .map(|record| {
+            let items = descriptions.remove(&record.id).unwrap_or_default();
+            (record, items)
+        })
+        .collect())
+}
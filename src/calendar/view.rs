@@ -0,0 +1,55 @@
+use crate::calendar::model::{Calendar, CalendarDay, VIEW_MONTH, VIEW_WEEK};
+
+markup::define! {
+    Index<'a>(calendar: &'a Calendar, days: &'a Vec<CalendarDay>, feed_url: Option<&'a str>) {
+        main[id = "container", class = "w-full px-4 py-0 mx-auto my-0 max-w-[80rem]"] {
+            div[id = "header", class = "flex flex-wrap gap-3 justify-between items-center mt-4 mb-8"] {
+                h1[class = "text-header text-[2rem]/[1.1] box-border m-0 p-0 font-bold"] { @calendar.title() }
+                div[class = "flex flex-wrap gap-2"] {
+                    a[class = "button button-light", href = calendar.previous_href()] { "← Previous" }
+                    a[class = "button button-light", href = {format!("/calendar?view={}", calendar.view)}] { "Today" }
+                    a[class = "button button-light", href = calendar.next_href()] { "Next →" }
+                    @if calendar.is_week() {
+                        a[class = "button button-light", href = calendar.view_href(VIEW_MONTH)] { "Month" }
+                    } else {
+                        a[class = "button button-light", href = calendar.view_href(VIEW_WEEK)] { "Week" }
+                    }
+                }
+            }
+
+            div[id = "calendar", class = "grid grid-cols-7 gap-px bg-[hsl(0,6%,93%)] rounded-md overflow-hidden shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]"] {
+                @for weekday in ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"] {
+                    div[class = "bg-light p-2 font-bold text-[0.875rem] tracking-[1px] uppercase"] { @weekday }
+                }
+                @for day in *days {
+                    @let height = if calendar.is_week() { "min-h-[20rem]" } else { "min-h-[7rem]" };
+                    @let background = if day.in_period { "bg-white" } else { "bg-background text-muted" };
+                    div[class = format!("flex flex-col gap-1 p-2 {height} {background}")] {
+                        div[class = "font-bold text-[0.875rem]"] { @day.date.day() }
+                        @for entry in &day.entries {
+                            a[href = entry.href(),
+                                class = {if entry.is_tentative() {
+                                    "block px-2 py-1 rounded-md border border-dashed border-[hsl(0,6%,80%)] text-[0.75rem]"
+                                } else {
+                                    "block px-2 py-1 rounded-md bg-light text-[0.75rem]"
+                                }}] {
+                                div[class = "font-bold truncate"] { @entry.quote_name }
+                                @if let Some(time_range) = entry.time_range() {
+                                    div { @time_range }
+                                }
+                                div { @entry.subtotal.format() }
+                            }
+                        }
+                    }
+                }
+            }
+            p[class = "mt-2 text-[0.875rem] text-muted"] { "Dates of quotes not accepted yet have a dashed border." }
+            @if let Some(feed_url) = feed_url {
+                p[class = "mt-2 text-[0.875rem] text-muted"] {
+                    "Subscribe to every quote from a calendar app with "
+                    code[class = "break-all"] { @feed_url }
+                }
+            }
+        }
+    }
+}
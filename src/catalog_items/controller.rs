@@ -0,0 +1,229 @@
+use crate::{
+    Db, Result,
+    catalog_items::{
+        self,
+        model::{CatalogItemPresenter, DeleteForm, EditCatalogItemForm, NewCatalogItemForm},
+        view::*,
+    },
+    layout::{Flash, Layout},
+    rocket_ext::{HtmxRequest, HtmxResponder},
+};
+use rocket::{
+    fairing::AdHoc,
+    form::{Contextual, Error, Form},
+    http::Header,
+    response::{Redirect, content::RawHtml},
+};
+
+pub(crate) fn stage() -> AdHoc {
+    AdHoc::on_ignite("CatalogItem Stage", |rocket| async {
+        rocket.mount(
+            "/catalog_items",
+            routes![
+                index,
+                catalog_item,
+                search,
+                new,
+                create,
+                edit,
+                update,
+                delete
+            ],
+        )
+    })
+}
+
+#[get("/")]
+async fn index(db: Db) -> Result<RawHtml<String>> {
+    let catalog_items = db
+        .run(move |conn| {
+            let records = catalog_items::query::all(conn)?
+                .into_iter()
+                .map(|record| record.into())
+                .collect::<Vec<CatalogItemPresenter>>();
+            Result::Ok(records)
+        })
+        .await?;
+
+    let template = Layout {
+        head: markup::new! {
+            title { "Catalog" }
+        },
+        body: Index { catalog_items },
+    };
+
+    Ok(RawHtml(template.to_string()))
+}
+
+#[get("/<id>")]
+async fn catalog_item(db: Db, htmx: HtmxRequest, id: String) -> Result<RawHtml<String>> {
+    let record = db
+        .run(move |conn| {
+            let record = catalog_items::query::read(conn, &id)?;
+            Result::Ok(record)
+        })
+        .await?;
+
+    let template = CatalogItem {
+        catalog_item: &record.into(),
+    };
+    Ok(RawHtml(htmx.render("Catalog item", template)))
+}
+
+#[get("/search?<name>")]
+async fn search(db: Db, name: String) -> Result<RawHtml<String>> {
+    if name.trim().is_empty() {
+        return Ok(RawHtml(String::new()));
+    }
+
+    let catalog_items = db
+        .run(move |conn| {
+            let records = catalog_items::query::search(conn, &name)?
+                .into_iter()
+                .map(|record| record.into())
+                .collect::<Vec<CatalogItemPresenter>>();
+            Result::Ok(records)
+        })
+        .await?;
+
+    let template = Suggestions {
+        catalog_items: &catalog_items,
+    };
+    Ok(RawHtml(template.to_string()))
+}
+
+#[get("/new")]
+async fn new(htmx: HtmxRequest) -> RawHtml<String> {
+    RawHtml(htmx.render("New catalog item", NewForm {}))
+}
+
+#[post("/create", data = "<form>")]
+async fn create(
+    db: Db,
+    htmx: HtmxRequest,
+    mut form: Form<Contextual<'_, NewCatalogItemForm>>,
+) -> Result<HtmxResponder> {
+    let catalog_item = match form.value.clone() {
+        Some(catalog_item_form) => {
+            let record = db
+                .run(move |conn| catalog_items::query::insert(conn, &catalog_item_form))
+                .await?;
+            if record.is_none() {
+                reject_sku(&mut *form);
+            }
+            record
+        }
+        None => None,
+    };
+
+    match catalog_item {
+        Some(catalog_item) => {
+            if !htmx.0 {
+                return Ok(HtmxResponder::Redirect(Redirect::to("/catalog_items")));
+            }
+
+            let content = Create {
+                catalog_item: &catalog_item.into(),
+                message: "Catalog item was successfully created.",
+            }
+            .to_string();
+
+            Ok(HtmxResponder::Ok(content))
+        }
+        None => {
+            let template = NewFormWithErrors { form: &form };
+            if !htmx.0 {
+                return Ok(HtmxResponder::Ok(htmx.render("New catalog item", template)));
+            }
+
+            let content = template.to_string();
+            Ok(HtmxResponder::Retarget {
+                content,
+                retarget: Header::new("HX-Retarget", "#catalog_item_new".to_string()),
+                reswap: Header::new("HX-Reswap", "outerhtml".to_string()),
+            })
+        }
+    }
+}
+
+#[get("/edit/<id>")]
+async fn edit(db: Db, htmx: HtmxRequest, id: String) -> Result<RawHtml<String>> {
+    let record = db
+        .run(move |conn| {
+            let record = catalog_items::query::read(conn, &id)?;
+            Result::Ok(record)
+        })
+        .await?;
+
+    let template = EditForm {
+        catalog_item: &record.into(),
+    };
+    Ok(RawHtml(htmx.render("Edit catalog item", template)))
+}
+
+#[post("/update", data = "<form>")]
+async fn update(
+    db: Db,
+    htmx: HtmxRequest,
+    mut form: Form<Contextual<'_, EditCatalogItemForm>>,
+) -> Result<HtmxResponder> {
+    let catalog_item = match form.value.clone() {
+        Some(catalog_item_form) => {
+            let record = db
+                .run(move |conn| catalog_items::query::update(conn, &catalog_item_form))
+                .await?;
+            if record.is_none() {
+                reject_sku(&mut *form);
+            }
+            record
+        }
+        None => None,
+    };
+
+    match catalog_item {
+        Some(catalog_item) => {
+            if !htmx.0 {
+                return Ok(HtmxResponder::Redirect(Redirect::to("/catalog_items")));
+            }
+
+            let content = Update {
+                catalog_item: &catalog_item.into(),
+                message: "Catalog item was successfully updated.",
+            }
+            .to_string();
+            Ok(HtmxResponder::Ok(content))
+        }
+        None => {
+            let template = EditFormWithErrors { form: &form };
+            Ok(HtmxResponder::Ok(
+                htmx.render("Edit catalog item", template),
+            ))
+        }
+    }
+}
+
+#[post("/delete", data = "<form>")]
+async fn delete(db: Db, htmx: HtmxRequest, form: Form<DeleteForm>) -> Result<HtmxResponder> {
+    db.run(move |conn| {
+        catalog_items::query::delete(conn, &form.id)?;
+        Result::Ok(())
+    })
+    .await?;
+
+    if !htmx.0 {
+        return Ok(HtmxResponder::Redirect(Redirect::to("/catalog_items")));
+    }
+
+    let content = Flash {
+        message: "Catalog item was successfully destroyed.",
+    }
+    .to_string();
+    Ok(HtmxResponder::Ok(content))
+}
+
+// Turns a form with a SKU already in use into an invalid one, rather than failing on the unique index
+fn reject_sku<T>(form: &mut Contextual<'_, T>) {
+    form.context
+        .push_error(Error::validation("This SKU is already used").with_name("sku"));
+    form.value = None;
+}
@@ -0,0 +1,136 @@
+use crate::{
+    forms::{validate_amount, validate_unit},
+    schema::catalog_items,
+};
+use currency_rs::Currency;
+use diesel::prelude::*;
+use time::OffsetDateTime;
+use ulid::Ulid;
+
+#[derive(Debug, Insertable, Queryable, Selectable)]
+#[diesel(table_name = catalog_items)]
+pub(crate) struct CatalogItem {
+    pub(crate) id: String,
+    pub(crate) sku: String,
+    pub(crate) name: String,
+    pub(crate) description: Option<String>,
+    pub(crate) unit: String,
+    pub(crate) unit_price: Currency,
+    pub(crate) active: bool,
+    pub(crate) created_at: OffsetDateTime,
+    pub(crate) updated_at: OffsetDateTime,
+}
+
+impl From<&NewCatalogItemForm> for CatalogItem {
+    fn from(value: &NewCatalogItemForm) -> Self {
+        CatalogItem {
+            id: Ulid::new().to_string(),
+            sku: value.sku.clone(),
+            name: value.name.clone(),
+            description: optional_description(&value.description),
+            unit: value.unit.clone(),
+            unit_price: Currency::new_string(value.unit_price.as_str(), None)
+                .unwrap_or(Currency::new_float(0f64, None)),
+            active: value.active,
+            created_at: OffsetDateTime::now_utc(),
+            updated_at: OffsetDateTime::now_utc(),
+        }
+    }
+}
+
+impl From<&EditCatalogItemForm> for CatalogItem {
+    fn from(value: &EditCatalogItemForm) -> Self {
+        CatalogItem {
+            id: value.id.clone(),
+            sku: value.sku.clone(),
+            name: value.name.clone(),
+            description: optional_description(&value.description),
+            unit: value.unit.clone(),
+            unit_price: Currency::new_string(value.unit_price.as_str(), None)
+                .unwrap_or(Currency::new_float(0f64, None)),
+            active: value.active,
+            created_at: OffsetDateTime::now_utc(),
+            updated_at: OffsetDateTime::now_utc(),
+        }
+    }
+}
+
+fn optional_description(description: &Option<String>) -> Option<String> {
+    description
+        .as_ref()
+        .filter(|description| !description.is_empty())
+        .cloned()
+}
+
+#[derive(Clone, Debug, FromForm)]
+pub struct NewCatalogItemForm {
+    #[field(validate = len(1..).or_else(msg!("Please enter a SKU")))]
+    pub(crate) sku: String,
+    #[field(validate = len(1..).or_else(msg!("Please enter a name")))]
+    pub(crate) name: String,
+    pub(crate) description: Option<String>,
+    #[field(validate = validate_unit())]
+    pub(crate) unit: String,
+    #[field(validate = validate_amount())]
+    pub(crate) unit_price: String,
+    pub(crate) active: bool,
+}
+
+#[derive(Clone, Debug, FromForm)]
+pub struct EditCatalogItemForm {
+    #[field(validate = len(1..))]
+    pub(crate) id: String,
+    #[field(validate = len(1..).or_else(msg!("Please enter a SKU")))]
+    pub(crate) sku: String,
+    #[field(validate = len(1..).or_else(msg!("Please enter a name")))]
+    pub(crate) name: String,
+    pub(crate) description: Option<String>,
+    #[field(validate = validate_unit())]
+    pub(crate) unit: String,
+    #[field(validate = validate_amount())]
+    pub(crate) unit_price: String,
+    pub(crate) active: bool,
+}
+
+#[derive(Clone, Debug)]
+pub struct CatalogItemPresenter {
+    pub id: Option<String>,
+    pub sku: String,
+    pub name: String,
+    pub description: String,
+    pub unit: String,
+    pub unit_price: Currency,
+    pub active: bool,
+}
+
+impl CatalogItemPresenter {
+    pub fn id(&self) -> String {
+        match &self.id {
+            Some(id) => id.clone(),
+            None => String::from("new"),
+        }
+    }
+
+    pub fn dom_id(&self) -> String {
+        format!("catalog_item_{}", self.id())
+    }
+}
+
+impl From<CatalogItem> for CatalogItemPresenter {
+    fn from(value: CatalogItem) -> Self {
+        CatalogItemPresenter {
+            id: Some(value.id),
+            sku: value.sku,
+            name: value.name,
+            description: value.description.unwrap_or(String::from("")),
+            unit: value.unit,
+            unit_price: value.unit_price,
+            active: value.active,
+        }
+    }
+}
+
+#[derive(Clone, Debug, FromForm)]
+pub(crate) struct DeleteForm {
+    pub(crate) id: String,
+}
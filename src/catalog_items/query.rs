@@ -0,0 +1,171 @@
+use crate::{
+    Result,
+    catalog_items::model::{CatalogItem, EditCatalogItemForm, NewCatalogItemForm},
+    schema::catalog_items,
+};
+use diesel::prelude::*;
+use diesel::result::{DatabaseErrorKind, Error::DatabaseError};
+
+const SEARCH_LIMIT: i64 = 10;
+
+pub(crate) fn all(connection: &mut SqliteConnection) -> Result<Vec<CatalogItem>> {
+    let records = catalog_items::table
+        .order_by((catalog_items::name, catalog_items::sku))
+        .get_results(connection)?;
+    Ok(records)
+}
+
+pub(crate) fn search<S: AsRef<str>>(
+    connection: &mut SqliteConnection,
+    text: S,
+) -> Result<Vec<CatalogItem>> {
+    // Wildcards typed by the user are matched literally
+    let text = text
+        .as_ref()
+        .trim()
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_");
+    let pattern = format!("%{text}%");
+    let records = catalog_items::table
+        .filter(catalog_items::active.eq(true))
+        .filter(
+            catalog_items::name
+                .like(&pattern)
+                .escape('\\')
+                .or(catalog_items::sku.like(&pattern).escape('\\')),
+        )
+        .order_by(catalog_items::name)
+        .limit(SEARCH_LIMIT)
+        .get_results(connection)?;
+    Ok(records)
+}
+
+pub(crate) fn read<S: AsRef<str>>(connection: &mut SqliteConnection, id: S) -> Result<CatalogItem> {
+    let record = catalog_items::table
+        .filter(catalog_items::id.eq(&id.as_ref()))
+        .get_result(connection)?;
+    Ok(record)
+}
+
+// Returns `None` when the SKU is already used by another catalog item
+pub(crate) fn insert(
+    connection: &mut SqliteConnection,
+    form: &NewCatalogItemForm,
+) -> Result<Option<CatalogItem>> {
+    let record: CatalogItem = form.into();
+
+    let inserted = diesel::dsl::insert_into(catalog_items::table)
+        .values(&record)
+        .execute(connection);
+    if sku_taken(inserted)? {
+        return Ok(None);
+    }
+
+    Ok(Some(record))
+}
+
+// Returns `None` when the SKU is already used by another catalog item
+pub(crate) fn update(
+    connection: &mut SqliteConnection,
+    form: &EditCatalogItemForm,
+) -> Result<Option<CatalogItem>> {
+    let record: CatalogItem = form.into();
+
+    let updated = diesel::dsl::update(catalog_items::table)
+        .set((
+            catalog_items::sku.eq(&record.sku),
+            catalog_items::name.eq(&record.name),
+            catalog_items::description.eq(&record.description),
+            catalog_items::unit.eq(&record.unit),
+            catalog_items::unit_price.eq(&record.unit_price),
+            catalog_items::active.eq(&record.active),
+            catalog_items::updated_at.eq(&record.updated_at),
+        ))
+        .filter(catalog_items::id.eq(&record.id))
+        .execute(connection);
+    if sku_taken(updated)? {
+        return Ok(None);
+    }
+
+    read(connection, &record.id).map(Some)
+}
+
+// Whether a write failed on the unique index of SKUs, checked by the database so that two
+// requests with the same SKU cannot both get through
+fn sku_taken(outcome: QueryResult<usize>) -> Result<bool> {
+    match outcome {
+        Ok(_) => Ok(false),
+        Err(DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => Ok(true),
+        Err(error) => Err(error.into()),
+    }
+}
+
+pub(crate) fn delete<S: AsRef<str>>(
+    connection: &mut SqliteConnection,
+    id: S,
+) -> Result<CatalogItem> {
+    let record = read(connection, &id)?;
+
+    _ = diesel::dsl::delete(catalog_items::table)
+        .filter(catalog_items::id.eq(&id.as_ref()))
+        .execute(connection)?;
+
+    Ok(record)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::catalog_items::model::NewCatalogItemForm;
+
+    fn form(sku: &str, name: &str) -> NewCatalogItemForm {
+        NewCatalogItemForm {
+            sku: sku.to_string(),
+            name: name.to_string(),
+            description: None,
+            unit: String::from("pcs"),
+            unit_price: String::from("2.50"),
+            active: true,
+        }
+    }
+
+    #[test]
+    fn sku_taken() {
+        let mut connection = crate::test_connection();
+        assert!(
+            super::insert(&mut connection, &form("CH-01", "Chair"))
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            super::insert(&mut connection, &form("CH-01", "Folding chair"))
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn search() {
+        let mut connection = crate::test_connection();
+        super::insert(&mut connection, &form("CH-01", "Chair")).unwrap();
+        super::insert(&mut connection, &form("TB_01", "Table 100%")).unwrap();
+
+        assert_eq!(2, super::search(&mut connection, "01").unwrap().len());
+        let records = super::search(&mut connection, "_").unwrap();
+        assert_eq!(
+            vec!["TB_01"],
+            records
+                .iter()
+                .map(|record| record.sku.as_str())
+                .collect::<Vec<&str>>()
+        );
+        let records = super::search(&mut connection, "%").unwrap();
+        assert_eq!(
+            vec!["Table 100%"],
+            records
+                .iter()
+                .map(|record| record.name.as_str())
+                .collect::<Vec<&str>>()
+        );
+    }
+}
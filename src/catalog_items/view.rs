@@ -0,0 +1,461 @@
+use crate::{
+    catalog_items::model::{CatalogItemPresenter, EditCatalogItemForm, NewCatalogItemForm},
+    forms::css_for_field,
+    layout::Flash,
+    line_items::view::UnitOptions,
+    quantity::DEFAULT_UNIT,
+};
+use rocket::form::{Contextual, Form};
+
+markup::define! {
+    Index(catalog_items: Vec<CatalogItemPresenter>) {
+        main[id = "container", class = "w-full px-4 py-0 mx-auto my-0 max-w-[60rem]"] {
+            div[id = "header", class = "flex flex-wrap gap-3 justify-between mt-4 mb-8"] {
+                h1[class = "text-header text-[2rem]/[1.1] box-border m-0 p-0 font-bold"] {"Catalog"}
+                a[class = "button button-prime",
+                    href = "/catalog_items/new",
+                    "hx-get" = "/catalog_items/new",
+                    "hx-target" = "#catalog_item_new",
+                    "hx-trigger" = "click",
+                    "hx-swap" = "outerHTML"] { "Add catalog item" }
+            }
+
+            div[id = "catalog_item_new"] {}
+
+            div[id = "catalog_items"] {
+                div[id = "catalog_items_empty", class = "p-4 border-2 border-[hsl(0,6%,93%)] border-dashed text-center hidden only:[display:revert]"] {
+                    p[class = "[font-size:1.125rem] text-header mb-6 font-bold"] {
+                        "You don't have any catalog items yet!"
+                    }
+                }
+                @for catalog_item in catalog_items {
+                    @CatalogItem { catalog_item }
+                }
+            }
+        }
+    }
+
+    CatalogItem<'a>(catalog_item: &'a CatalogItemPresenter) {
+        div[id = &catalog_item.dom_id()] {
+            div[class= "flex flex-wrap justify-between items-start gap-3 bg-white rounded-md mb-4 px-4 py-2 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]"] {
+                div[class = "flex-[0_0_8rem] text-muted"] { @catalog_item.sku }
+                div[class = "flex-1 font-bold"] {
+                    @catalog_item.name
+                    @if !catalog_item.active {
+                        span[class = "ml-2 px-2 rounded-full bg-light font-normal text-[0.875rem] text-muted"] { "Inactive" }
+                    }
+                    div[class = "font-normal text-[0.875rem] text-muted"] { @catalog_item.description }
+                }
+                div[class = "flex-[0_0_10rem]"] {
+                    @catalog_item.unit_price.format() " / " @catalog_item.unit
+                }
+                div[class = "flex flex-auto grow-0 shrink-0 self-start gap-2"] {
+                    form[action = "/catalog_items/delete",
+                         method = "post",
+                         "hx-post" = "/catalog_items/delete",
+                         "hx-target" = {format!("#{}", catalog_item.dom_id())},
+                         "hx-swap" = "delete"] {
+                        input[id = "catalog_item_id",
+                            name = "id",
+                            "type" = "hidden",
+                            value = &catalog_item.id()] {}
+                        button[class = "button button-light", "hx-confirm" = "Are you sure?", "type" = "submit"] {"Delete"}
+                    }
+                    a[class = "button button-light",
+                        href = {format!("/catalog_items/edit/{}", catalog_item.id())},
+                        "hx-get" = {format!("/catalog_items/edit/{}", catalog_item.id())},
+                        "hx-target" = {format!("#{}", catalog_item.dom_id())},
+                        "hx-trigger" = "click"] { "Edit" }
+                }
+            }
+        }
+    }
+
+    Suggestions<'a>(catalog_items: &'a Vec<CatalogItemPresenter>) {
+        @if !catalog_items.is_empty() {
+            ul[class = "absolute z-10 w-full mt-1 bg-white rounded-md font-normal shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]"] {
+                @for catalog_item in *catalog_items {
+                    li {
+                        button[class = "block w-full px-2 py-1 text-left hover:bg-light",
+                            "type" = "button",
+                            "data-catalog-item" = &catalog_item.id(),
+                            "data-name" = &catalog_item.name,
+                            "data-description" = &catalog_item.description,
+                            "data-unit" = &catalog_item.unit,
+                            "data-unit-price" = &catalog_item.unit_price.to_string()] {
+                            span[class = "font-bold"] { @catalog_item.name }
+                            " "
+                            span[class = "text-[0.875rem] text-muted"] {
+                                @catalog_item.sku " · " @catalog_item.unit_price.format() " / " @catalog_item.unit
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    EditForm<'a>(catalog_item: &'a CatalogItemPresenter) {
+        div[id = &catalog_item.dom_id()] {
+            form[id = format!("form_{}", catalog_item.id()),
+                action = "/catalog_items/update",
+                method = "post",
+                "hx-post" = "/catalog_items/update",
+                "hx-target" = {format!("#{}", catalog_item.dom_id())},
+                "hx-swap" = "outerHTML",
+                class = "flex flex-wrap justify-between items-start gap-3 bg-white rounded-md mb-4 px-4 py-2 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]",
+                autocomplete = "off",
+                novalidate,
+                "accept-charset" = "UTF-8"] {
+
+                input[id = "catalog_item_id",
+                    name = "id",
+                    "type" = "hidden",
+                    value = &catalog_item.id.clone().unwrap()] {}
+                div[class = "flex-[0_0_8rem]"] {
+                    label[class = "visually-hidden", "for" = "catalog_item_sku"] { "SKU" }
+                    input[id = "catalog_item_sku",
+                        name = "sku",
+                        class = "form-input",
+                        autofocus = "autofocus",
+                        placeholder = "SKU",
+                        "type" = "text",
+                        value = &catalog_item.sku] {}
+                }
+                div[class = "[flex:1]"] {
+                    label[class = "visually-hidden", "for" = "catalog_item_name"] { "Name" }
+                    input[id = "catalog_item_name",
+                        name = "name",
+                        class = "form-input",
+                        placeholder = "Name of the item",
+                        "type" = "text",
+                        value = &catalog_item.name] {}
+                }
+                div[class = "flex-[0_0_6rem]"] {
+                    label[class = "visually-hidden", "for" = "catalog_item_unit"] { "Unit" }
+                    select[id = "catalog_item_unit",
+                        name = "unit",
+                        class = "form-input"] {
+                        @UnitOptions { selected: &catalog_item.unit }
+                    }
+                }
+                div[class = "flex-[0_0_9rem]"] {
+                    label[class = "visually-hidden", "for" = "catalog_item_unit_price"] { "Unit price" }
+                    input[id = "catalog_item_unit_price",
+                        name = "unit_price",
+                        class = "form-input",
+                        placeholder = "$100.00",
+                        "type" = "number",
+                        min = "0.01",
+                        step = "0.01",
+                        value = &catalog_item.unit_price.to_string()] {}
+                }
+                label[class = "flex items-center gap-2 self-center", "for" = "catalog_item_active"] {
+                    input[id = "catalog_item_active",
+                        name = "active",
+                        "type" = "checkbox",
+                        checked = catalog_item.active] {}
+                    "Active"
+                }
+                div[class = "basis-full"] {
+                    label[class = "visually-hidden", "for" = "catalog_item_description"] { "Description" }
+                    textarea[id = "catalog_item_description",
+                        name = "description",
+                        class = "resize-none form-input",
+                        placeholder = "Description (optional)"] { @catalog_item.description }
+                }
+                div[class = "flex gap-2 ml-auto"] {
+                    a[class = "button button-light",
+                        href = {format!("/catalog_items/{}", catalog_item.id())},
+                        "hx-get" = {format!("/catalog_items/{}", catalog_item.id())},
+                        "hx-target" = {format!("#{}", catalog_item.dom_id())},
+                        "hx-trigger" = "click"] { "Cancel" }
+                    input[name = "commit",
+                        "type" = "submit",
+                        value = "Update catalog item",
+                        class = "button button-secondary",
+                        "_" = "on click add { pointer-events: none }"] {}
+                }
+            }
+        }
+    }
+
+    EditFormWithErrors<'a, 'r>(form: &'a Form<Contextual<'r, EditCatalogItemForm>>) {
+        @let context = &form.context;
+        @let id = context.field_value("id").unwrap_or("");
+        @let sku = context.field_value("sku").unwrap_or("");
+        @let name = context.field_value("name").unwrap_or("");
+        @let unit = context.field_value("unit").unwrap_or(DEFAULT_UNIT);
+        @let unit_price = context.field_value("unit_price").unwrap_or("");
+        @let active = context.field_value("active").is_some();
+        @let description = context.field_value("description").unwrap_or("");
+        @let dom_id = format!("catalog_item_{}", id);
+        div[id = &dom_id] {
+            form[id = format!("form_{}", id),
+                action = "/catalog_items/update",
+                method = "post",
+                "hx-post" = "/catalog_items/update",
+                "hx-target" = {format!("#{}", dom_id)},
+                "hx-swap" = "outerHTML",
+                class = "flex flex-wrap justify-between items-start gap-3 bg-white rounded-md mb-4 px-4 py-2 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]",
+                autocomplete = "off",
+                novalidate,
+                "accept-charset" = "UTF-8"] {
+
+                @let messages = context.errors().map(|item| item.to_string()).collect::<Vec<String>>();
+                div[class = "w-full text-primary bg-primary-bg p-2 rounded-md"] {
+                    @for message in messages {
+                        p { @message }
+                    }
+                }
+
+                input[id = "catalog_item_id",
+                    name = "id",
+                    "type" = "hidden",
+                    value = &id] {}
+                div[class = "flex-[0_0_8rem]"] {
+                    label[class = "visually-hidden", "for" = "catalog_item_sku"] { "SKU" }
+                    input[id = "catalog_item_sku",
+                        name = "sku",
+                        class = css_for_field(form, "sku", "form-input", "border-primary"),
+                        autofocus = "autofocus",
+                        placeholder = "SKU",
+                        "type" = "text",
+                        value = &sku] {}
+                }
+                div[class = "[flex:1]"] {
+                    label[class = "visually-hidden", "for" = "catalog_item_name"] { "Name" }
+                    input[id = "catalog_item_name",
+                        name = "name",
+                        class = css_for_field(form, "name", "form-input", "border-primary"),
+                        placeholder = "Name of the item",
+                        "type" = "text",
+                        value = &name] {}
+                }
+                div[class = "flex-[0_0_6rem]"] {
+                    label[class = "visually-hidden", "for" = "catalog_item_unit"] { "Unit" }
+                    select[id = "catalog_item_unit",
+                        name = "unit",
+                        class = css_for_field(form, "unit", "form-input", "border-primary")] {
+                        @UnitOptions { selected: &unit }
+                    }
+                }
+                div[class = "flex-[0_0_9rem]"] {
+                    label[class = "visually-hidden", "for" = "catalog_item_unit_price"] { "Unit price" }
+                    input[id = "catalog_item_unit_price",
+                        name = "unit_price",
+                        class = css_for_field(form, "unit_price", "form-input", "border-primary"),
+                        placeholder = "$100.00",
+                        "type" = "number",
+                        min = "0.01",
+                        step = "0.01",
+                        value = &unit_price] {}
+                }
+                label[class = "flex items-center gap-2 self-center", "for" = "catalog_item_active"] {
+                    input[id = "catalog_item_active",
+                        name = "active",
+                        "type" = "checkbox",
+                        checked = active] {}
+                    "Active"
+                }
+                div[class = "basis-full"] {
+                    label[class = "visually-hidden", "for" = "catalog_item_description"] { "Description" }
+                    textarea[id = "catalog_item_description",
+                        name = "description",
+                        class = css_for_field(form, "description", "resize-none form-input", "border-primary"),
+                        placeholder = "Description (optional)"] { @description }
+                }
+                div[class = "flex gap-2 ml-auto"] {
+                    a[class = "button button-light",
+                        href = {format!("/catalog_items/{}", id)},
+                        "hx-get" = {format!("/catalog_items/{}", id)},
+                        "hx-target" = {format!("#{}", dom_id)},
+                        "hx-trigger" = "click"] { "Cancel" }
+                    input[name = "commit",
+                        "type" = "submit",
+                        value = "Update catalog item",
+                        class = "button button-secondary",
+                        "_" = "on click add { pointer-events: none }"] {}
+                }
+            }
+        }
+    }
+
+    NewForm() {
+        div[id = "catalog_item_new"] {
+            form[id = "form_new",
+                action = "/catalog_items/create",
+                method = "post",
+                "hx-post" = "/catalog_items/create",
+                "hx-target" = "#catalog_items_empty",
+                "hx-swap" = "afterend",
+                class = "flex flex-wrap justify-between items-start gap-3 bg-white rounded-md mb-4 px-4 py-2 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]",
+                autocomplete = "off",
+                novalidate,
+                "accept-charset" = "UTF-8"] {
+
+                div[class = "flex-[0_0_8rem]"] {
+                    label[class = "visually-hidden", "for" = "catalog_item_sku"] { "SKU" }
+                    input[id = "catalog_item_sku",
+                        name = "sku",
+                        class = "form-input",
+                        autofocus = "autofocus",
+                        placeholder = "SKU",
+                        "type" = "text"] {}
+                }
+                div[class = "[flex:1]"] {
+                    label[class = "visually-hidden", "for" = "catalog_item_name"] { "Name" }
+                    input[id = "catalog_item_name",
+                        name = "name",
+                        class = "form-input",
+                        placeholder = "Name of the item",
+                        "type" = "text"] {}
+                }
+                div[class = "flex-[0_0_6rem]"] {
+                    label[class = "visually-hidden", "for" = "catalog_item_unit"] { "Unit" }
+                    select[id = "catalog_item_unit",
+                        name = "unit",
+                        class = "form-input"] {
+                        @UnitOptions { selected: DEFAULT_UNIT }
+                    }
+                }
+                div[class = "flex-[0_0_9rem]"] {
+                    label[class = "visually-hidden", "for" = "catalog_item_unit_price"] { "Unit price" }
+                    input[id = "catalog_item_unit_price",
+                        name = "unit_price",
+                        class = "form-input",
+                        placeholder = "$100.00",
+                        "type" = "number",
+                        min = "0.01",
+                        step = "0.01"] {}
+                }
+                label[class = "flex items-center gap-2 self-center", "for" = "catalog_item_active"] {
+                    input[id = "catalog_item_active",
+                        name = "active",
+                        "type" = "checkbox",
+                        checked = true] {}
+                    "Active"
+                }
+                div[class = "basis-full"] {
+                    label[class = "visually-hidden", "for" = "catalog_item_description"] { "Description" }
+                    textarea[id = "catalog_item_description",
+                        name = "description",
+                        class = "resize-none form-input",
+                        placeholder = "Description (optional)"] {}
+                }
+                div[class = "flex gap-2 ml-auto"] {
+                    a[class = "button button-light",
+                        "_" = "on click remove #form_new"] { "Cancel" }
+                    input[name = "commit",
+                        "type" = "submit",
+                        value = "Create catalog item",
+                        class = "button button-secondary",
+                        "_" = "on click add { pointer-events: none }"] {}
+                }
+            }
+        }
+    }
+
+    NewFormWithErrors<'a, 'r>(form: &'a Form<Contextual<'r, NewCatalogItemForm>>) {
+        @let context = &form.context;
+        @let sku = context.field_value("sku").unwrap_or("");
+        @let name = context.field_value("name").unwrap_or("");
+        @let unit = context.field_value("unit").unwrap_or(DEFAULT_UNIT);
+        @let unit_price = context.field_value("unit_price").unwrap_or("");
+        @let active = context.field_value("active").is_some();
+        @let description = context.field_value("description").unwrap_or("");
+        div[id = "catalog_item_new"] {
+            form[id = "form_new",
+                action = "/catalog_items/create",
+                method = "post",
+                "hx-post" = "/catalog_items/create",
+                "hx-target" = "#catalog_items_empty",
+                "hx-swap" = "afterend",
+                class = "flex flex-wrap justify-between items-start gap-3 bg-white rounded-md mb-4 px-4 py-2 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]",
+                autocomplete = "off",
+                novalidate,
+                "accept-charset" = "UTF-8"] {
+
+                @let messages = context.errors().map(|item| item.to_string()).collect::<Vec<String>>();
+                div[class = "w-full text-primary bg-primary-bg p-2 rounded-md"] {
+                    @for message in messages {
+                        p { @message }
+                    }
+                }
+
+                div[class = "flex-[0_0_8rem]"] {
+                    label[class = "visually-hidden", "for" = "catalog_item_sku"] { "SKU" }
+                    input[id = "catalog_item_sku",
+                        name = "sku",
+                        class = css_for_field(form, "sku", "form-input", "border-primary"),
+                        autofocus = "autofocus",
+                        placeholder = "SKU",
+                        "type" = "text",
+                        value = &sku] {}
+                }
+                div[class = "[flex:1]"] {
+                    label[class = "visually-hidden", "for" = "catalog_item_name"] { "Name" }
+                    input[id = "catalog_item_name",
+                        name = "name",
+                        class = css_for_field(form, "name", "form-input", "border-primary"),
+                        placeholder = "Name of the item",
+                        "type" = "text",
+                        value = &name] {}
+                }
+                div[class = "flex-[0_0_6rem]"] {
+                    label[class = "visually-hidden", "for" = "catalog_item_unit"] { "Unit" }
+                    select[id = "catalog_item_unit",
+                        name = "unit",
+                        class = css_for_field(form, "unit", "form-input", "border-primary")] {
+                        @UnitOptions { selected: &unit }
+                    }
+                }
+                div[class = "flex-[0_0_9rem]"] {
+                    label[class = "visually-hidden", "for" = "catalog_item_unit_price"] { "Unit price" }
+                    input[id = "catalog_item_unit_price",
+                        name = "unit_price",
+                        class = css_for_field(form, "unit_price", "form-input", "border-primary"),
+                        placeholder = "$100.00",
+                        "type" = "number",
+                        min = "0.01",
+                        step = "0.01",
+                        value = &unit_price] {}
+                }
+                label[class = "flex items-center gap-2 self-center", "for" = "catalog_item_active"] {
+                    input[id = "catalog_item_active",
+                        name = "active",
+                        "type" = "checkbox",
+                        checked = active] {}
+                    "Active"
+                }
+                div[class = "basis-full"] {
+                    label[class = "visually-hidden", "for" = "catalog_item_description"] { "Description" }
+                    textarea[id = "catalog_item_description",
+                        name = "description",
+                        class = css_for_field(form, "description", "resize-none form-input", "border-primary"),
+                        placeholder = "Description (optional)"] { @description }
+                }
+                div[class = "flex gap-2 ml-auto"] {
+                    a[class = "button button-light",
+                        "_" = "on click remove #form_new"] { "Cancel" }
+                    input[name = "commit",
+                        "type" = "submit",
+                        value = "Create catalog item",
+                        class = "button button-secondary",
+                        "_" = "on click add { pointer-events: none }"] {}
+                }
+            }
+        }
+    }
+
+    Create<'a>(catalog_item: &'a CatalogItemPresenter, message: &'a str) {
+        @CatalogItem{ catalog_item }
+        div[id = "catalog_item_new", "hx-swap-oob"="innerHTML"]{}
+        @Flash{ message }
+    }
+
+    Update<'a>(catalog_item: &'a CatalogItemPresenter, message: &'a str) {
+        @CatalogItem{ catalog_item }
+        @Flash{ message }
+    }
+}
@@ -0,0 +1,235 @@
+use crate::{
+    Db, Result,
+    comments::{
+        self,
+        model::{CommentPresenter, DeleteForm, EditCommentForm, NewCommentForm},
+        view::*,
+    },
+    layout::Flash,
+    line_items::{self, model::LineItemPresenter},
+    rocket_ext::{HtmxRequest, HtmxResponder},
+    users::model::CurrentUser,
+};
+use rocket::{
+    fairing::AdHoc,
+    form::{Contextual, Form},
+    http::Status,
+    response::Redirect,
+};
+
+pub(crate) fn stage() -> AdHoc {
+    AdHoc::on_ignite("Comment Stage", |rocket| async {
+        rocket.mount(
+            "/comments",
+            routes![quote, comment, create, edit, update, delete],
+        )
+    })
+}
+
+#[get("/quote/<quote_id>")]
+async fn quote(
+    db: Db,
+    user: CurrentUser,
+    htmx: HtmxRequest,
+    quote_id: String,
+) -> Result<HtmxResponder> {
+    let id = quote_id.clone();
+    let (comments, line_items) = db
+        .run(move |conn| {
+            let comments = comments::query::all_for_quote(conn, &id)?
+                .into_iter()
+                .map(|record| record.into())
+                .collect::<Vec<CommentPresenter>>();
+            let line_items = line_items::query::all_for_quote(conn, &id)?
+                .into_iter()
+                .map(|record| record.into())
+                .collect::<Vec<LineItemPresenter>>();
+            Result::Ok((comments, line_items))
+        })
+        .await?;
+
+    let template = Thread {
+        quote_id: &quote_id,
+        comments: &comments,
+        line_items: &line_items,
+        user_id: &user.0.id,
+    };
+    Ok(HtmxResponder::Ok(htmx.render("Comments", template)))
+}
+
+#[get("/<id>")]
+async fn comment(
+    db: Db,
+    user: CurrentUser,
+    htmx: HtmxRequest,
+    id: String,
+) -> Result<HtmxResponder> {
+    let record = db
+        .run(move |conn| {
+            let record = comments::query::read(conn, &id)?;
+            Result::Ok(record)
+        })
+        .await?;
+
+    let template = Comment {
+        comment: &record.into(),
+        user_id: &user.0.id,
+    };
+    Ok(HtmxResponder::Ok(htmx.render("Comment", template)))
+}
+
+#[post("/create", data = "<form>")]
+async fn create(
+    db: Db,
+    user: CurrentUser,
+    htmx: HtmxRequest,
+    form: Form<Contextual<'_, NewCommentForm>>,
+) -> Result<HtmxResponder> {
+    let quote_id = form
+        .context
+        .field_value("quote_id")
+        .unwrap_or("")
+        .to_string();
+    let comment_form = form.value.clone();
+    let user_id = user.0.id.clone();
+    let (comment, line_items) = db
+        .run(move |conn| {
+            let comment = match comment_form {
+                Some(ref comment_form) => {
+                    Some(comments::query::insert(conn, &user_id, comment_form)?)
+                }
+                None => None,
+            };
+            let line_items = line_items::query::all_for_quote(conn, &quote_id)?
+                .into_iter()
+                .map(|record| record.into())
+                .collect::<Vec<LineItemPresenter>>();
+            Result::Ok((comment, line_items))
+        })
+        .await?;
+
+    match comment {
+        Some(comment) => {
+            let comment: CommentPresenter = comment.into();
+            if !htmx.0 {
+                let uri = format!("/quotes/show/{}", comment.quote_id);
+                return Ok(HtmxResponder::Redirect(Redirect::to(uri)));
+            }
+
+            let content = Create {
+                comment: &comment,
+                line_items: &line_items,
+                user_id: &user.0.id,
+                message: "Comment was successfully created.",
+            }
+            .to_string();
+            Ok(HtmxResponder::Ok(content))
+        }
+        None => {
+            let template = NewFormWithErrors {
+                form: &form,
+                line_items: &line_items,
+            };
+            Ok(HtmxResponder::Ok(htmx.render("New comment", template)))
+        }
+    }
+}
+
+#[get("/edit/<id>")]
+async fn edit(db: Db, user: CurrentUser, htmx: HtmxRequest, id: String) -> Result<HtmxResponder> {
+    let record = db
+        .run(move |conn| {
+            let record = comments::query::read(conn, &id)?;
+            Result::Ok(record)
+        })
+        .await?;
+
+    if record.user_id != user.0.id {
+        return Ok(HtmxResponder::Empty(Status::Forbidden));
+    }
+
+    let template = EditForm {
+        comment: &record.into(),
+    };
+    Ok(HtmxResponder::Ok(htmx.render("Edit comment", template)))
+}
+
+#[post("/update", data = "<form>")]
+async fn update(
+    db: Db,
+    user: CurrentUser,
+    htmx: HtmxRequest,
+    form: Form<Contextual<'_, EditCommentForm>>,
+) -> Result<HtmxResponder> {
+    match form.value {
+        Some(ref comment_form) => {
+            let comment_form = comment_form.clone();
+            let user_id = user.0.id.clone();
+            let comment = db
+                .run(move |conn| {
+                    let record = comments::query::read(conn, &comment_form.id)?;
+                    if record.user_id != user_id {
+                        return Result::Ok(None);
+                    }
+                    let record = comments::query::update(conn, &user_id, &comment_form)?;
+                    Result::Ok(Some(record))
+                })
+                .await?;
+
+            let Some(comment) = comment else {
+                return Ok(HtmxResponder::Empty(Status::Forbidden));
+            };
+
+            let comment: CommentPresenter = comment.into();
+            if !htmx.0 {
+                let uri = format!("/quotes/show/{}", comment.quote_id);
+                return Ok(HtmxResponder::Redirect(Redirect::to(uri)));
+            }
+
+            let template = Comment {
+                comment: &comment,
+                user_id: &user.0.id,
+            };
+            Ok(HtmxResponder::Ok(template.to_string()))
+        }
+        None => {
+            let template = EditFormWithErrors { form: &form };
+            Ok(HtmxResponder::Ok(htmx.render("Edit comment", template)))
+        }
+    }
+}
+
+#[post("/delete", data = "<form>")]
+async fn delete(
+    db: Db,
+    user: CurrentUser,
+    htmx: HtmxRequest,
+    form: Form<DeleteForm>,
+) -> Result<HtmxResponder> {
+    let user_id = user.0.id.clone();
+    let comment = db
+        .run(move |conn| {
+            let record = comments::query::read(conn, &form.id)?;
+            if record.user_id != user_id {
+                return Result::Ok(None);
+            }
+            let record = comments::query::delete(conn, &user_id, &form.id)?;
+            Result::Ok(Some(record))
+        })
+        .await?;
+
+    let Some(comment) = comment else {
+        return Ok(HtmxResponder::Empty(Status::Forbidden));
+    };
+
+    if !htmx.0 {
+        let uri = format!("/quotes/show/{}", comment.quote_id);
+        return Ok(HtmxResponder::Redirect(Redirect::to(uri)));
+    }
+
+    let content = Flash {
+        message: "Comment was successfully destroyed.",
+    }
+    .to_string();
+    Ok(HtmxResponder::Ok(content))
+}
@@ -0,0 +1,137 @@
+use crate::{schema::comments, time::timestamp};
+use diesel::prelude::*;
+use diesel::sql_types::*;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use time::OffsetDateTime;
+use ulid::Ulid;
+
+static MENTION_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"(?:^|[^\w@])@([A-Za-z0-9_.-]+)").unwrap());
+
+#[derive(Debug, Insertable, Queryable)]
+#[diesel(table_name = comments)]
+pub(crate) struct Comment {
+    pub(crate) id: String,
+    pub(crate) quote_id: String,
+    pub(crate) line_item_id: Option<String>,
+    pub(crate) user_id: String,
+    pub(crate) body: String,
+    pub(crate) created_at: OffsetDateTime,
+    pub(crate) updated_at: OffsetDateTime,
+}
+
+impl Comment {
+    pub(crate) fn new(user_id: &str, form: &NewCommentForm) -> Comment {
+        let now = OffsetDateTime::now_utc();
+        Comment {
+            id: Ulid::new().to_string(),
+            quote_id: form.quote_id.clone(),
+            line_item_id: form
+                .line_item_id
+                .clone()
+                .filter(|line_item_id| !line_item_id.is_empty()),
+            user_id: user_id.to_string(),
+            body: form.body.trim().to_string(),
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+// Usernames mentioned with `@username` in a comment body, without duplicates
+pub(crate) fn mentioned_usernames(body: &str) -> Vec<String> {
+    let mut usernames = MENTION_REGEX
+        .captures_iter(body)
+        .map(|captures| captures[1].trim_end_matches('.').to_string())
+        .collect::<Vec<String>>();
+    usernames.sort();
+    usernames.dedup();
+    usernames
+}
+
+#[derive(Debug, QueryableByName)]
+pub(crate) struct CommentWithAuthor {
+    #[diesel(sql_type = Text)]
+    pub(crate) id: String,
+    #[diesel(sql_type = Text)]
+    pub(crate) quote_id: String,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub(crate) line_item_name: Option<String>,
+    #[diesel(sql_type = Text)]
+    pub(crate) user_id: String,
+    #[diesel(sql_type = Text)]
+    pub(crate) author_name: String,
+    #[diesel(sql_type = Text)]
+    pub(crate) body: String,
+    #[diesel(sql_type = TimestamptzSqlite)]
+    pub(crate) created_at: OffsetDateTime,
+    #[diesel(sql_type = TimestamptzSqlite)]
+    pub(crate) updated_at: OffsetDateTime,
+}
+
+#[derive(Clone, Debug, FromForm)]
+pub struct NewCommentForm {
+    #[field(validate = len(1..))]
+    pub(crate) quote_id: String,
+    pub(crate) line_item_id: Option<String>,
+    #[field(validate = len(1..).or_else(msg!("Please enter a comment")))]
+    pub(crate) body: String,
+}
+
+#[derive(Clone, Debug, FromForm)]
+pub struct EditCommentForm {
+    #[field(validate = len(1..))]
+    pub(crate) id: String,
+    #[field(validate = len(1..).or_else(msg!("Please enter a comment")))]
+    pub(crate) body: String,
+}
+
+#[derive(Debug, FromForm)]
+pub(crate) struct DeleteForm {
+    pub(crate) id: String,
+}
+
+#[derive(Debug)]
+pub struct CommentPresenter {
+    pub id: String,
+    pub quote_id: String,
+    pub line_item_name: Option<String>,
+    pub user_id: String,
+    pub author_name: String,
+    pub body: String,
+    pub created_at: String,
+    pub edited: bool,
+}
+
+impl CommentPresenter {
+    pub fn dom_id(&self) -> String {
+        format!("comment_{}", self.id)
+    }
+}
+
+impl From<CommentWithAuthor> for CommentPresenter {
+    fn from(value: CommentWithAuthor) -> Self {
+        CommentPresenter {
+            id: value.id,
+            quote_id: value.quote_id,
+            line_item_name: value.line_item_name,
+            user_id: value.user_id,
+            author_name: value.author_name,
+            body: value.body,
+            created_at: timestamp(value.created_at),
+            edited: value.updated_at > value.created_at,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn mentioned_usernames() {
+        assert_eq!(
+            vec!["manager", "sales"],
+            super::mentioned_usernames("@sales can you check with @manager? Thanks @sales. a@b.c")
+        );
+    }
+}
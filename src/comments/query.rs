@@ -0,0 +1,160 @@
+use crate::{
+    Result,
+    comments::model::{
+        Comment, CommentWithAuthor, EditCommentForm, NewCommentForm, mentioned_usernames,
+    },
+    schema::{comment_mentions, comments},
+    users,
+};
+use diesel::prelude::*;
+use time::OffsetDateTime;
+
+// language=SQL
+const SELECT_WITH_AUTHOR: &str = r#"
+  select
+    c.id,
+    c.quote_id,
+    li.name as line_item_name,
+    c.user_id,
+    u.name as author_name,
+    c.body,
+    c.created_at,
+    c.updated_at
+  from comments c
+    inner join users u on c.user_id = u.id
+    left join line_items li on c.line_item_id = li.id
+"#;
+
+pub(crate) fn all_for_quote<S: AsRef<str>>(
+    connection: &mut SqliteConnection,
+    quote_id: S,
+) -> Result<Vec<CommentWithAuthor>> {
+    let sql = format!("{SELECT_WITH_AUTHOR} where c.quote_id = ? order by c.created_at, c.id");
+    let records = diesel::dsl::sql_query(sql)
+        .bind::<diesel::sql_types::Text, _>(quote_id.as_ref())
+        .load(connection)?;
+    Ok(records)
+}
+
+pub(crate) fn read<S: AsRef<str>>(
+    connection: &mut SqliteConnection,
+    id: S,
+) -> Result<CommentWithAuthor> {
+    let sql = format!("{SELECT_WITH_AUTHOR} where c.id = ?");
+    let record = diesel::dsl::sql_query(sql)
+        .bind::<diesel::sql_types::Text, _>(id.as_ref())
+        .get_result(connection)?;
+    Ok(record)
+}
+
+pub(crate) fn insert(
+    connection: &mut SqliteConnection,
+    user_id: &str,
+    form: &NewCommentForm,
+) -> Result<CommentWithAuthor> {
+    let record = Comment::new(user_id, form);
+
+    connection.transaction::<_, crate::error::AppError, _>(|tx| {
+        diesel::dsl::insert_into(comments::table)
+            .values(&record)
+            .execute(tx)?;
+
+        mention(tx, &record.id, user_id, &record.body)
+    })?;
+
+    read(connection, &record.id)
+}
+
+// Only the author may edit a comment
+pub(crate) fn update(
+    connection: &mut SqliteConnection,
+    user_id: &str,
+    form: &EditCommentForm,
+) -> Result<CommentWithAuthor> {
+    let body = form.body.trim();
+
+    connection.transaction::<_, crate::error::AppError, _>(|tx| {
+        diesel::dsl::update(comments::table)
+            .set((
+                comments::body.eq(body),
+                comments::updated_at.eq(OffsetDateTime::now_utc()),
+            ))
+            .filter(comments::id.eq(&form.id))
+            .filter(comments::user_id.eq(user_id))
+            .execute(tx)?;
+
+        mention(tx, &form.id, user_id, body)
+    })?;
+
+    read(connection, &form.id)
+}
+
+// Only the author may delete a comment
+pub(crate) fn delete<S: AsRef<str>>(
+    connection: &mut SqliteConnection,
+    user_id: &str,
+    id: S,
+) -> Result<CommentWithAuthor> {
+    let record = read(connection, &id)?;
+
+    connection.transaction::<_, crate::error::AppError, _>(|tx| {
+        let deleted = diesel::dsl::delete(comments::table)
+            .filter(comments::id.eq(id.as_ref()))
+            .filter(comments::user_id.eq(user_id))
+            .execute(tx)?;
+
+        if deleted > 0 {
+            _ = diesel::dsl::delete(comment_mentions::table)
+                .filter(comment_mentions::comment_id.eq(id.as_ref()))
+                .execute(tx)?;
+        }
+
+        Ok(())
+    })?;
+
+    Ok(record)
+}
+
+pub(crate) fn delete_all_for_quote<S: AsRef<str>>(
+    tx: &mut SqliteConnection,
+    quote_id: S,
+) -> Result {
+    let quote_comments = comments::table
+        .select(comments::id)
+        .filter(comments::quote_id.eq(quote_id.as_ref()));
+
+    _ = diesel::dsl::delete(comment_mentions::table)
+        .filter(comment_mentions::comment_id.eq_any(quote_comments))
+        .execute(tx)?;
+
+    _ = diesel::dsl::delete(comments::table)
+        .filter(comments::quote_id.eq(quote_id.as_ref()))
+        .execute(tx)?;
+
+    Ok(())
+}
+
+// Keeps the mentions of a comment in line with its body. Mentions already stored keep their
+// notification state, mentions of the author are ignored.
+fn mention(tx: &mut SqliteConnection, comment_id: &str, author_id: &str, body: &str) -> Result {
+    let user_ids = users::query::ids_for_usernames(tx, &mentioned_usernames(body))?
+        .into_iter()
+        .filter(|user_id| user_id != author_id)
+        .collect::<Vec<String>>();
+
+    _ = diesel::dsl::delete(comment_mentions::table)
+        .filter(comment_mentions::comment_id.eq(comment_id))
+        .filter(comment_mentions::user_id.ne_all(&user_ids))
+        .execute(tx)?;
+
+    for user_id in &user_ids {
+        _ = diesel::dsl::insert_or_ignore_into(comment_mentions::table)
+            .values((
+                comment_mentions::comment_id.eq(comment_id),
+                comment_mentions::user_id.eq(user_id),
+            ))
+            .execute(tx)?;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,228 @@
+use crate::{
+    comments::model::{CommentPresenter, EditCommentForm, NewCommentForm},
+    forms::css_for_field,
+    layout::Flash,
+    line_items::{model::LineItemPresenter, view::LineItemOptions},
+};
+use rocket::form::{Contextual, Form};
+
+markup::define! {
+    // Placeholder loading the comments of a quote
+    ThreadLoader<'a>(quote_id: &'a str) {
+        div[id = "quote_comments",
+            "hx-get" = {format!("/comments/quote/{quote_id}")},
+            "hx-trigger" = "load",
+            "hx-swap" = "outerHTML"] {}
+    }
+
+    Thread<'a>(quote_id: &'a str,
+               comments: &'a Vec<CommentPresenter>,
+               line_items: &'a Vec<LineItemPresenter>,
+               user_id: &'a str) {
+        div[id = "quote_comments", class = "mt-12"] {
+            h2[class = "text-[1.5rem] font-bold mb-2"] { "Comments" }
+            div[id = "comments", class = "flex flex-col gap-3 mb-3"] {
+                @for comment in *comments {
+                    @Comment { comment, user_id }
+                }
+            }
+            @NewForm { quote_id, line_items }
+        }
+    }
+
+    Comment<'a>(comment: &'a CommentPresenter, user_id: &'a str) {
+        div[id = &comment.dom_id(), class = "bg-white rounded-md p-4 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]"] {
+            div[class = "flex flex-wrap items-center gap-2 text-[0.875rem]"] {
+                span[class = "font-bold"] { @comment.author_name }
+                @if let Some(line_item_name) = &comment.line_item_name {
+                    span { "on " @line_item_name }
+                }
+                span[class = "text-[0.75rem] opacity-70"] {
+                    @comment.created_at
+                    @if comment.edited { " (edited)" }
+                }
+                @if comment.user_id == *user_id {
+                    div[class = "flex gap-2 ml-auto"] {
+                        a[class = "button button-light",
+                            href = {format!("/comments/edit/{}", comment.id)},
+                            "hx-get" = {format!("/comments/edit/{}", comment.id)},
+                            "hx-target" = {format!("#{}", comment.dom_id())},
+                            "hx-trigger" = "click",
+                            "hx-swap" = "outerHTML"] { "Edit" }
+                        form[action = "/comments/delete",
+                             method = "post",
+                             "hx-post" = "/comments/delete",
+                             "hx-target" = {format!("#{}", comment.dom_id())},
+                             "hx-swap" = "delete"] {
+                            input[name = "id", "type" = "hidden", value = &comment.id] {}
+                            button[class = "button button-light", "type" = "submit"] { "Delete" }
+                        }
+                    }
+                }
+            }
+            p[class = "mt-2 whitespace-pre-line"] { @comment.body }
+        }
+    }
+
+    NewForm<'a>(quote_id: &'a str, line_items: &'a Vec<LineItemPresenter>) {
+        form[id = "comment_new",
+            action = "/comments/create",
+            method = "post",
+            "hx-post" = "/comments/create",
+            "hx-target" = "#comment_new",
+            "hx-swap" = "outerHTML",
+            class = "flex flex-wrap items-start gap-2 bg-white rounded-md p-4 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]",
+            autocomplete = "off",
+            novalidate,
+            "accept-charset" = "UTF-8"] {
+            input[name = "quote_id", "type" = "hidden", value = quote_id] {}
+            @LineItemSelect { line_items, selected: "" }
+            label[class = "visually-hidden", "for" = "comment_body"] { "Comment" }
+            textarea[id = "comment_body",
+                name = "body",
+                class = "basis-full resize-y form-input",
+                rows = "3",
+                placeholder = "Write a comment, mention someone with @username"] {}
+            input[name = "commit",
+                "type" = "submit",
+                value = "Comment",
+                class = "button button-secondary ml-auto",
+                "_" = "on click add { pointer-events: none }"] {}
+        }
+    }
+
+    NewFormWithErrors<'a, 'r>(form: &'a Form<Contextual<'r, NewCommentForm>>,
+                              line_items: &'a Vec<LineItemPresenter>) {
+        @let context = &form.context;
+        @let quote_id = context.field_value("quote_id").unwrap_or("");
+        @let line_item_id = context.field_value("line_item_id").unwrap_or("");
+        @let body = context.field_value("body").unwrap_or("");
+        form[id = "comment_new",
+            action = "/comments/create",
+            method = "post",
+            "hx-post" = "/comments/create",
+            "hx-target" = "#comment_new",
+            "hx-swap" = "outerHTML",
+            class = "flex flex-wrap items-start gap-2 bg-white rounded-md p-4 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]",
+            autocomplete = "off",
+            novalidate,
+            "accept-charset" = "UTF-8"] {
+
+            @let messages = context.errors().map(|item| item.to_string()).collect::<Vec<String>>();
+            div[class = "w-full text-primary bg-primary-bg p-2 rounded-md"] {
+                @for message in messages {
+                    p { @message }
+                }
+            }
+
+            input[name = "quote_id", "type" = "hidden", value = quote_id] {}
+            @LineItemSelect { line_items, selected: line_item_id }
+            label[class = "visually-hidden", "for" = "comment_body"] { "Comment" }
+            textarea[id = "comment_body",
+                name = "body",
+                class = {css_for_field(form, "body", "basis-full resize-y form-input", "border-primary")},
+                rows = "3",
+                autofocus = "autofocus",
+                placeholder = "Write a comment, mention someone with @username"] { @body }
+            input[name = "commit",
+                "type" = "submit",
+                value = "Comment",
+                class = "button button-secondary ml-auto",
+                "_" = "on click add { pointer-events: none }"] {}
+        }
+    }
+
+    LineItemSelect<'a>(line_items: &'a Vec<LineItemPresenter>, selected: &'a str) {
+        label[class = "visually-hidden", "for" = "comment_line_item_id"] { "About" }
+        select[id = "comment_line_item_id", name = "line_item_id", class = "form-input"] {
+            @LineItemOptions { line_items, selected }
+        }
+    }
+
+    EditForm<'a>(comment: &'a CommentPresenter) {
+        form[id = &comment.dom_id(),
+            action = "/comments/update",
+            method = "post",
+            "hx-post" = "/comments/update",
+            "hx-target" = "this",
+            "hx-swap" = "outerHTML",
+            class = "flex flex-wrap items-start gap-2 bg-white rounded-md p-4 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]",
+            autocomplete = "off",
+            novalidate,
+            "accept-charset" = "UTF-8"] {
+            input[name = "id", "type" = "hidden", value = &comment.id] {}
+            label[class = "visually-hidden", "for" = {format!("comment_{}_body", comment.id)}] { "Comment" }
+            textarea[id = {format!("comment_{}_body", comment.id)},
+                name = "body",
+                class = "basis-full resize-y form-input",
+                rows = "3",
+                autofocus = "autofocus"] { @comment.body }
+            a[class = "button button-light ml-auto",
+                href = {format!("/comments/{}", comment.id)},
+                "hx-get" = {format!("/comments/{}", comment.id)},
+                "hx-target" = {format!("#{}", comment.dom_id())},
+                "hx-trigger" = "click",
+                "hx-swap" = "outerHTML"] { "Cancel" }
+            input[name = "commit",
+                "type" = "submit",
+                value = "Update comment",
+                class = "button button-secondary",
+                "_" = "on click add { pointer-events: none }"] {}
+        }
+    }
+
+    EditFormWithErrors<'a, 'r>(form: &'a Form<Contextual<'r, EditCommentForm>>) {
+        @let context = &form.context;
+        @let id = context.field_value("id").unwrap_or("");
+        @let body = context.field_value("body").unwrap_or("");
+        @let dom_id = format!("comment_{}", id);
+        form[id = &dom_id,
+            action = "/comments/update",
+            method = "post",
+            "hx-post" = "/comments/update",
+            "hx-target" = "this",
+            "hx-swap" = "outerHTML",
+            class = "flex flex-wrap items-start gap-2 bg-white rounded-md p-4 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]",
+            autocomplete = "off",
+            novalidate,
+            "accept-charset" = "UTF-8"] {
+
+            @let messages = context.errors().map(|item| item.to_string()).collect::<Vec<String>>();
+            div[class = "w-full text-primary bg-primary-bg p-2 rounded-md"] {
+                @for message in messages {
+                    p { @message }
+                }
+            }
+
+            input[name = "id", "type" = "hidden", value = id] {}
+            label[class = "visually-hidden", "for" = {format!("{}_body", dom_id)}] { "Comment" }
+            textarea[id = {format!("{}_body", dom_id)},
+                name = "body",
+                class = {css_for_field(form, "body", "basis-full resize-y form-input", "border-primary")},
+                rows = "3",
+                autofocus = "autofocus"] { @body }
+            a[class = "button button-light ml-auto",
+                href = {format!("/comments/{}", id)},
+                "hx-get" = {format!("/comments/{}", id)},
+                "hx-target" = {format!("#{}", dom_id)},
+                "hx-trigger" = "click",
+                "hx-swap" = "outerHTML"] { "Cancel" }
+            input[name = "commit",
+                "type" = "submit",
+                value = "Update comment",
+                class = "button button-secondary",
+                "_" = "on click add { pointer-events: none }"] {}
+        }
+    }
+
+    Create<'a>(comment: &'a CommentPresenter,
+               line_items: &'a Vec<LineItemPresenter>,
+               user_id: &'a str,
+               message: &'a str) {
+        @NewForm { quote_id: &comment.quote_id, line_items }
+        div[id = "comments", "hx-swap-oob" = "beforeend"] {
+            @Comment { comment, user_id }
+        }
+        @Flash { message }
+    }
+}
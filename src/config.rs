@@ -0,0 +1,92 @@
+use once_cell::sync::OnceCell;
+use rocket::{
+    data::{ByteUnit, ToByteUnit},
+    fairing::AdHoc,
+    serde::Deserialize,
+};
+use std::path::PathBuf;
+
+static CONFIG: OnceCell<AppConfig> = OnceCell::new();
+
+// Application settings read from `Rocket.toml` (or `ROCKET_*` environment variables) next to
+// Rocket's own configuration.
+#[derive(Debug, Deserialize)]
+#[serde(crate = "rocket::serde", default)]
+pub(crate) struct AppConfig {
+    // Decimals of quantities, at most 3 as stored by the `decimal(10,3)` column
+    pub(crate) quantity_precision: usize,
+    // Percentage of revenue under which the quote margin is flagged
+    pub(crate) margin_warning_threshold: f64,
+    // Directory uploaded attachments are stored in, their metadata lives in the database
+    pub(crate) attachments_directory: PathBuf,
+    // Largest accepted attachment, Rocket's `limits.file` must allow at least as much
+    pub(crate) attachment_max_size: ByteUnit,
+    // Address the application is reached at by customers, used for links in emails
+    pub(crate) public_url: String,
+    // SMTP server quotes are sent through, a local mail catcher such as Mailpit by default
+    pub(crate) smtp_host: String,
+    pub(crate) smtp_port: u16,
+    pub(crate) smtp_username: Option<String>,
+    pub(crate) smtp_password: Option<String>,
+    pub(crate) smtp_starttls: bool,
+    pub(crate) mail_from: String,
+    // Templates of the email sending a quote, see `emails::model::fill` for the placeholders
+    pub(crate) quote_email_subject: String,
+    pub(crate) quote_email_body: String,
+    // Secret of the calendar feed of every quote at `/calendar/feed.ics?token=`, no feed when unset
+    pub(crate) calendar_feed_token: Option<String>,
+    // Lets anyone pick the user they work as from the header, only meant for trying roles out
+    pub(crate) user_switcher: bool,
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        AppConfig {
+            quantity_precision: 2,
+            margin_warning_threshold: 20.0,
+            attachments_directory: PathBuf::from("data/attachments"),
+            attachment_max_size: 10.mebibytes(),
+            public_url: String::from("http://localhost:8000"),
+            smtp_host: String::from("localhost"),
+            smtp_port: 1025,
+            smtp_username: None,
+            smtp_password: None,
+            smtp_starttls: false,
+            mail_from: String::from("ABC Corp <quotes@example.com>"),
+            quote_email_subject: String::from("Your quote {quote_name}"),
+            quote_email_body: String::from(
+                "Hello,\n\nPlease find attached our quote {quote_name} for a total of {total}.\n\n\
+                 You can also view it online and accept it at {share_link}\n\nKind regards,\nABC Corp",
+            ),
+            calendar_feed_token: None,
+            user_switcher: false,
+        }
+    }
+}
+
+pub(crate) fn stage() -> AdHoc {
+    AdHoc::on_ignite("Config Stage", |rocket| async {
+        let config = rocket
+            .figment()
+            .extract::<AppConfig>()
+            .expect("failure reading application configuration");
+        assert!(
+            config.quantity_precision <= 3,
+            "quantity_precision must be between 0 and 3, quantities are stored with 3 decimals"
+        );
+        CONFIG
+            .set(config)
+            .expect("application configuration already set");
+        rocket
+    })
+}
+
+// Only available once the Config Stage has run, tests get the defaults
+pub(crate) fn get() -> &'static AppConfig {
+    if cfg!(test) {
+        return CONFIG.get_or_init(AppConfig::default);
+    }
+    CONFIG
+        .get()
+        .expect("application configuration read before the Config Stage")
+}
@@ -1,5 +1,12 @@
+use currency_rs::Currency;
 use once_cell::sync::Lazy;
 use regex::Regex;
+use rocket::serde::Serializer;
 
 pub(crate) static FORM_CURRENCY_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"^\d+(\.\d{2})?$").unwrap());
+
+// Serializes a `Currency` as its plain decimal amount rather than the formatted display text
+pub(crate) fn serialize<S: Serializer>(value: &Currency, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&value.to_string())
+}
@@ -0,0 +1,178 @@
+use crate::{
+    Db, Result, config,
+    emails::{
+        self,
+        model::{Email, EmailForm, EmailFormPresenter, EmailPresenter, fill},
+        view::*,
+    },
+    layout::Flash,
+    line_item_dates::{self, model::LineItemDatePresenter},
+    line_items::{self, model::LineItemPresenter},
+    quotes::{self, model::QuotePresenter},
+    rocket_ext::{HtmxRequest, HtmxResponder},
+    share_links,
+    users::model::CurrentUser,
+};
+use itertools::Itertools;
+use rocket::{
+    fairing::AdHoc,
+    form::{Contextual, Form},
+    http::Header,
+    response::Redirect,
+};
+
+pub(crate) fn stage() -> AdHoc {
+    AdHoc::on_ignite("Email Stage", |rocket| async {
+        rocket.mount("/emails", routes![quote, new, create])
+    })
+}
+
+#[get("/quote/<quote_id>")]
+async fn quote(db: Db, htmx: HtmxRequest, quote_id: String) -> Result<HtmxResponder> {
+    let content = render(&db, &htmx, &quote_id).await?;
+    Ok(HtmxResponder::Ok(content))
+}
+
+#[get("/new/<quote_id>")]
+fn new(htmx: HtmxRequest, quote_id: &str) -> HtmxResponder {
+    let template = NewForm {
+        form: &EmailFormPresenter::new(quote_id),
+        errors: &Vec::new(),
+    };
+    HtmxResponder::Ok(htmx.render("Send to customer", template))
+}
+
+// Sends the quote with a link to its shared page, the attempt is recorded even when it fails
+#[post("/create", data = "<form>")]
+async fn create(
+    db: Db,
+    user: CurrentUser,
+    htmx: HtmxRequest,
+    form: Form<Contextual<'_, EmailForm>>,
+) -> Result<HtmxResponder> {
+    let Some(email_form) = &form.value else {
+        let values = EmailFormPresenter {
+            quote_id: form
+                .context
+                .field_value("quote_id")
+                .unwrap_or("")
+                .to_string(),
+            recipient: form
+                .context
+                .field_value("recipient")
+                .unwrap_or("")
+                .to_string(),
+            subject: form
+                .context
+                .field_value("subject")
+                .unwrap_or("")
+                .to_string(),
+            body: form.context.field_value("body").unwrap_or("").to_string(),
+        };
+        let errors = form
+            .context
+            .errors()
+            .map(|error| error.to_string())
+            .collect::<Vec<String>>();
+        let template = NewForm {
+            form: &values,
+            errors: &errors,
+        };
+        if !htmx.0 {
+            return Ok(HtmxResponder::Ok(htmx.render("Send to customer", template)));
+        }
+
+        return Ok(HtmxResponder::Retarget {
+            content: template.to_string(),
+            retarget: Header::new("HX-Retarget", "#email_new".to_string()),
+            reswap: Header::new("HX-Reswap", "outerhtml".to_string()),
+        });
+    };
+
+    let user_id = user.0.id.clone();
+    let quote_id = email_form.quote_id.clone();
+    let (share_link, quote, line_item_dates, line_items) = db
+        .run(move |conn| {
+            let share_link = share_links::query::active_or_insert(conn, &user_id, &quote_id)?;
+            let quote = quotes::query::read(conn, &quote_id)?;
+            let line_item_dates = line_item_dates::query::all(conn, &quote_id)?
+                .into_iter()
+                .map(|record| record.into())
+                .collect::<Vec<LineItemDatePresenter>>();
+            let line_items = line_items::query::all_for_quote(conn, &quote_id)?
+                .into_iter()
+                .map(|record| record.into())
+                .collect::<Vec<LineItemPresenter>>()
+                .into_iter()
+                .into_group_map_by(|line_item| line_item.line_item_date_id.clone());
+            Result::Ok((share_link, quote, line_item_dates, line_items))
+        })
+        .await?;
+
+    let quote: QuotePresenter = quote.into();
+    let total = quote.total.format();
+    let link = format!(
+        "{}/shared/{}",
+        config::get().public_url.trim_end_matches('/'),
+        share_link.token
+    );
+    let subject = fill(&email_form.subject, &quote.name, &total, &link);
+    let body = fill(&email_form.body, &quote.name, &total, &link);
+    let attachment = QuoteDocument {
+        quote: &quote,
+        line_item_dates: &line_item_dates,
+        line_items: &line_items,
+    }
+    .to_string();
+
+    let outcome = emails::mailer::send(
+        &email_form.recipient,
+        &subject,
+        &body,
+        &format!("Quote {}.html", quote.name),
+        attachment,
+    )
+    .await;
+    let message = match &outcome {
+        Ok(_) => "Email was successfully sent.",
+        Err(_) => "Email could not be sent.",
+    };
+    let record = Email::new(
+        &user.0.id,
+        &email_form.quote_id,
+        &email_form.recipient,
+        subject,
+        body,
+        &outcome,
+    );
+    db.run(move |conn| emails::query::insert(conn, &record))
+        .await?;
+
+    if !htmx.0 {
+        let uri = format!("/quotes/show/{}", email_form.quote_id);
+        return Ok(HtmxResponder::Redirect(Redirect::to(uri)));
+    }
+
+    let content = render(&db, &htmx, &email_form.quote_id).await?;
+    let flash = Flash { message }.to_string();
+    Ok(HtmxResponder::Ok(format!("{content}{flash}")))
+}
+
+async fn render(db: &Db, htmx: &HtmxRequest, quote_id: &str) -> Result<String> {
+    let id = quote_id.to_string();
+    let emails = db
+        .run(move |conn| {
+            let records = emails::query::all_for_quote(conn, &id)?
+                .into_iter()
+                .map(|record| record.into())
+                .collect::<Vec<EmailPresenter>>();
+            Result::Ok(records)
+        })
+        .await?;
+
+    let template = Emails {
+        quote_id,
+        emails: &emails,
+    };
+    Ok(htmx.render("Emails", template))
+}
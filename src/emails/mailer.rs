@@ -0,0 +1,42 @@
+use crate::config;
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+    message::{Attachment, MultiPart, SinglePart, header::ContentType},
+    transport::smtp::authentication::Credentials,
+};
+
+// Delivers a plain text email with an HTML attachment through the configured SMTP server
+pub(crate) async fn send(
+    recipient: &str,
+    subject: &str,
+    body: &str,
+    attachment_name: &str,
+    attachment: String,
+) -> anyhow::Result<()> {
+    let config = config::get();
+    let message = Message::builder()
+        .from(config.mail_from.parse()?)
+        .to(recipient.trim().parse()?)
+        .subject(subject)
+        .multipart(
+            MultiPart::mixed()
+                .singlepart(SinglePart::plain(body.to_string()))
+                .singlepart(
+                    Attachment::new(attachment_name.to_string())
+                        .body(attachment, ContentType::TEXT_HTML),
+                ),
+        )?;
+
+    let mut transport = if config.smtp_starttls {
+        AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(&config.smtp_host)?
+    } else {
+        AsyncSmtpTransport::<Tokio1Executor>::builder_dangerous(&config.smtp_host)
+    }
+    .port(config.smtp_port);
+    if let (Some(username), Some(password)) = (&config.smtp_username, &config.smtp_password) {
+        transport = transport.credentials(Credentials::new(username.clone(), password.clone()));
+    }
+
+    transport.build().send(message).await?;
+    Ok(())
+}
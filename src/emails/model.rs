@@ -0,0 +1,158 @@
+use crate::{config, forms::validate_email, schema::emails, time::timestamp};
+use diesel::prelude::*;
+use diesel::sql_types::*;
+use once_cell::sync::Lazy;
+use regex::{Captures, Regex};
+use time::OffsetDateTime;
+use ulid::Ulid;
+
+pub(crate) const STATUS_SENT: &str = "sent";
+pub(crate) const STATUS_FAILED: &str = "failed";
+
+static PLACEHOLDER_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"\{(quote_name|total|share_link)\}").unwrap());
+
+// Replaces the placeholders of an email template in one pass, so that a quote name containing
+// `{total}` is left as written
+pub(crate) fn fill(template: &str, quote_name: &str, total: &str, share_link: &str) -> String {
+    PLACEHOLDER_REGEX
+        .replace_all(template, |captures: &Captures<'_>| match &captures[1] {
+            "quote_name" => quote_name,
+            "total" => total,
+            _ => share_link,
+        })
+        .into_owned()
+}
+
+#[derive(Debug, Insertable, Queryable)]
+#[diesel(table_name = emails)]
+pub(crate) struct Email {
+    pub(crate) id: String,
+    pub(crate) quote_id: String,
+    pub(crate) user_id: String,
+    pub(crate) recipient: String,
+    pub(crate) subject: String,
+    pub(crate) body: String,
+    pub(crate) status: String,
+    pub(crate) error: Option<String>,
+    pub(crate) created_at: OffsetDateTime,
+}
+
+impl Email {
+    // The outcome of the delivery is kept, failures with the reason given by the server
+    pub(crate) fn new(
+        user_id: &str,
+        quote_id: &str,
+        recipient: &str,
+        subject: String,
+        body: String,
+        outcome: &anyhow::Result<()>,
+    ) -> Email {
+        Email {
+            id: Ulid::new().to_string(),
+            quote_id: quote_id.to_string(),
+            user_id: user_id.to_string(),
+            recipient: recipient.trim().to_string(),
+            subject,
+            body,
+            status: String::from(if outcome.is_ok() {
+                STATUS_SENT
+            } else {
+                STATUS_FAILED
+            }),
+            error: outcome.as_ref().err().map(|error| format!("{error:#}")),
+            created_at: OffsetDateTime::now_utc(),
+        }
+    }
+}
+
+#[derive(Debug, QueryableByName)]
+pub(crate) struct EmailWithAuthor {
+    #[diesel(sql_type = Text)]
+    pub(crate) recipient: String,
+    #[diesel(sql_type = Text)]
+    pub(crate) subject: String,
+    #[diesel(sql_type = Text)]
+    pub(crate) status: String,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub(crate) error: Option<String>,
+    #[diesel(sql_type = Text)]
+    pub(crate) author_name: String,
+    #[diesel(sql_type = TimestamptzSqlite)]
+    pub(crate) created_at: OffsetDateTime,
+}
+
+#[derive(Debug, FromForm)]
+pub struct EmailForm {
+    #[field(validate = len(1..))]
+    pub(crate) quote_id: String,
+    #[field(validate = validate_email())]
+    pub(crate) recipient: String,
+    #[field(validate = len(1..).or_else(msg!("Please enter a subject")))]
+    pub(crate) subject: String,
+    #[field(validate = len(1..).or_else(msg!("Please enter a message")))]
+    pub(crate) body: String,
+}
+
+#[derive(Debug)]
+pub struct EmailFormPresenter {
+    pub quote_id: String,
+    pub recipient: String,
+    pub subject: String,
+    pub body: String,
+}
+
+impl EmailFormPresenter {
+    // A new email starts from the configured templates, placeholders are filled when sending
+    pub fn new(quote_id: &str) -> EmailFormPresenter {
+        EmailFormPresenter {
+            quote_id: quote_id.to_string(),
+            recipient: String::new(),
+            subject: config::get().quote_email_subject.clone(),
+            body: config::get().quote_email_body.clone(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct EmailPresenter {
+    pub recipient: String,
+    pub subject: String,
+    pub sent: bool,
+    pub error: Option<String>,
+    pub author_name: String,
+    pub created_at: String,
+}
+
+impl From<EmailWithAuthor> for EmailPresenter {
+    fn from(value: EmailWithAuthor) -> Self {
+        EmailPresenter {
+            recipient: value.recipient,
+            subject: value.subject,
+            sent: value.status == STATUS_SENT,
+            error: value.error,
+            author_name: value.author_name,
+            created_at: timestamp(value.created_at),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn fill() {
+        let text = super::fill(
+            "{quote_name} costs {total}, see {share_link}",
+            "Wedding",
+            "$1,200.00",
+            "http://localhost:8000/shared/abc",
+        );
+        assert_eq!(
+            "Wedding costs $1,200.00, see http://localhost:8000/shared/abc",
+            text
+        );
+
+        let text = super::fill("{quote_name}: {total}", "Gala {total}", "$80.00", "");
+        assert_eq!("Gala {total}: $80.00", text);
+    }
+}
@@ -0,0 +1,47 @@
+use crate::{
+    Result,
+    emails::model::{Email, EmailWithAuthor},
+    schema::emails,
+};
+use diesel::prelude::*;
+
+pub(crate) fn all_for_quote<S: AsRef<str>>(
+    connection: &mut SqliteConnection,
+    quote_id: S,
+) -> Result<Vec<EmailWithAuthor>> {
+    // language=SQL
+    let sql = r#"
+      select
+        e.recipient,
+        e.subject,
+        e.status,
+        e.error,
+        u.name as author_name,
+        e.created_at
+      from emails e
+        inner join users u on e.user_id = u.id
+      where e.quote_id = ?
+      order by e.created_at desc, e.id desc
+    "#;
+    let records = diesel::dsl::sql_query(sql)
+        .bind::<diesel::sql_types::Text, _>(quote_id.as_ref())
+        .load(connection)?;
+    Ok(records)
+}
+
+pub(crate) fn insert(connection: &mut SqliteConnection, record: &Email) -> Result {
+    diesel::dsl::insert_into(emails::table)
+        .values(record)
+        .execute(connection)?;
+    Ok(())
+}
+
+pub(crate) fn delete_all_for_quote<S: AsRef<str>>(
+    tx: &mut SqliteConnection,
+    quote_id: S,
+) -> Result {
+    _ = diesel::dsl::delete(emails::table)
+        .filter(emails::quote_id.eq(quote_id.as_ref()))
+        .execute(tx)?;
+    Ok(())
+}
@@ -0,0 +1,132 @@
+use crate::{
+    emails::model::{EmailFormPresenter, EmailPresenter},
+    line_item_dates::model::LineItemDatePresenter,
+    line_items::model::LineItemPresenter,
+    quotes::model::QuotePresenter,
+    share_links::view::Shared,
+};
+use std::collections::HashMap;
+
+markup::define! {
+    // Placeholder loading the emails sent for a quote
+    EmailsLoader<'a>(quote_id: &'a str) {
+        div[id = "quote_emails",
+            "hx-get" = {format!("/emails/quote/{quote_id}")},
+            "hx-trigger" = "load",
+            "hx-swap" = "outerHTML"] {}
+    }
+
+    Emails<'a>(quote_id: &'a str, emails: &'a Vec<EmailPresenter>) {
+        div[id = "quote_emails", class = "mt-12"] {
+            div[class = "flex flex-wrap items-center justify-between gap-3 mb-2"] {
+                h2[class = "text-[1.5rem] font-bold"] { "Emails" }
+                a[class = "button button-prime",
+                    href = {format!("/emails/new/{quote_id}")},
+                    "hx-get" = {format!("/emails/new/{quote_id}")},
+                    "hx-target" = "#email_new",
+                    "hx-trigger" = "click",
+                    "hx-swap" = "outerHTML"] { "Send to customer" }
+            }
+            div[id = "email_new"] {}
+            @if !emails.is_empty() {
+                div[class = "bg-white rounded-md p-4 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]"] {
+                    ul[class = "flex flex-col gap-2"] {
+                        @for email in *emails {
+                            li[class = "text-[0.875rem]"] {
+                                div[class = "flex flex-wrap items-center gap-2"] {
+                                    span[class = "font-bold"] { @email.subject }
+                                    span { "to " @email.recipient }
+                                    span[class = "text-[0.75rem] opacity-70"] {
+                                        @email.author_name " · " @email.created_at
+                                    }
+                                    @if email.sent {
+                                        span[class = "ml-auto"] { "Sent" }
+                                    } else {
+                                        span[class = "ml-auto font-bold text-primary"] { "Failed" }
+                                    }
+                                }
+                                @if let Some(error) = &email.error {
+                                    p[class = "text-primary"] { @error }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    NewForm<'a>(form: &'a EmailFormPresenter, errors: &'a Vec<String>) {
+        div[id = "email_new"] {
+            form[action = "/emails/create",
+                method = "post",
+                "hx-post" = "/emails/create",
+                "hx-target" = "#quote_emails",
+                "hx-swap" = "outerHTML",
+                class = "flex flex-col gap-3 bg-white rounded-md mb-4 p-4 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]",
+                autocomplete = "off",
+                novalidate,
+                "accept-charset" = "UTF-8"] {
+
+                @if !errors.is_empty() {
+                    div[class = "w-full text-primary bg-primary-bg p-2 rounded-md"] {
+                        @for message in *errors {
+                            p { @message }
+                        }
+                    }
+                }
+
+                input[name = "quote_id", "type" = "hidden", value = &form.quote_id] {}
+                label["for" = "email_recipient"] { "To" }
+                input[id = "email_recipient",
+                    name = "recipient",
+                    class = "form-input",
+                    autofocus = "autofocus",
+                    placeholder = "customer@example.com",
+                    "type" = "email",
+                    value = &form.recipient] {}
+                label["for" = "email_subject"] { "Subject" }
+                input[id = "email_subject",
+                    name = "subject",
+                    class = "form-input",
+                    "type" = "text",
+                    value = &form.subject] {}
+                label["for" = "email_body"] { "Message" }
+                textarea[id = "email_body",
+                    name = "body",
+                    class = "form-input",
+                    rows = "8"] { @form.body }
+                p[class = "text-[0.75rem] opacity-70"] {
+                    "{quote_name}, {total} and {share_link} are replaced when sending. "
+                    "The quote is attached to the email."
+                }
+                div[class = "flex gap-2 justify-end"] {
+                    a[class = "button button-light",
+                        href = {format!("/quotes/show/{}", form.quote_id)},
+                        "_" = "on click halt the event then put '' into #email_new"] { "Cancel" }
+                    input[name = "commit",
+                        "type" = "submit",
+                        value = "Send",
+                        class = "button button-secondary",
+                        "_" = "on click add { pointer-events: none }"] {}
+                }
+            }
+        }
+    }
+
+    // The quote attached to emails, as shown to the customer on the shared page
+    QuoteDocument<'a>(quote: &'a QuotePresenter,
+                      line_item_dates: &'a Vec<LineItemDatePresenter>,
+                      line_items: &'a HashMap<String, Vec<LineItemPresenter>>) {
+        @markup::doctype()
+        html[lang = "en"] {
+            head {
+                meta[charset = "utf-8"] {}
+                title { "Quote " @quote.name }
+            }
+            body {
+                @Shared { quote, line_item_dates, line_items, answer: "" }
+            }
+        }
+    }
+}
@@ -1,13 +1,19 @@
 use rocket::{
     Request,
+    http::Status,
     response::{Debug, Responder, Result},
 };
+use std::fmt;
 
 // Make our own error that wraps `anyhow::Error`.
+#[derive(Debug)]
 pub(crate) struct AppError(anyhow::Error);
 
 impl<'r> Responder<'r, 'r> for AppError {
     fn respond_to(self, request: &Request<'_>) -> Result<'r> {
+        if let Some(error) = self.0.downcast_ref::<Locked>() {
+            return (Status::Conflict, error.to_string()).respond_to(request);
+        }
         Debug(self.0).respond_to(request)
     }
 }
@@ -22,3 +28,15 @@ where
         Self(err.into())
     }
 }
+
+// Raised when changing a quote the customer has already accepted or declined
+#[derive(Debug)]
+pub(crate) struct Locked;
+
+impl fmt::Display for Locked {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("This quote has been answered by the customer and can no longer be changed")
+    }
+}
+
+impl std::error::Error for Locked {}
@@ -1,9 +1,25 @@
-use crate::{currency::FORM_CURRENCY_REGEX, time::DATE_REGEX};
-use once_cell::sync::Lazy;
-use regex::Regex;
-use rocket::form::{Contextual, Form};
-
-pub(crate) static QUANTITY_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\d+$").unwrap());
+use crate::{
+    acceptances::model::{SIGNATURE_MAX_LENGTH, SIGNATURE_PREFIX},
+    attachments::model::{content_type, file_name},
+    config,
+    currency::FORM_CURRENCY_REGEX,
+    line_item_dates::model::{
+        COLLISION_MERGE, COLLISION_SKIP, MAX_OCCURRENCES, MAX_SHIFT_DAYS, REPEAT_DAILY,
+        REPEAT_WEEKDAYS, REPEAT_WEEKLY,
+    },
+    line_items::model::{
+        BULK_DELETE, BULK_MOVE, BULK_PRICE, BULK_QUANTITY, TRANSFER_COPY, TRANSFER_MOVE,
+    },
+    quantity::{UNITS, quantity_regex},
+    quotes::model::{STATUS_ACCEPTED, STATUS_REJECTED},
+    time::{DATE_FORMAT, DATE_REGEX, TIME_FORMAT, TIME_REGEX, TIMEZONE_REGEX},
+    webhooks::model::{EVENTS, internal_host},
+};
+use rocket::{
+    form::{Contextual, Form},
+    fs::TempFile,
+};
+use time::{Date, Time};
 
 pub(crate) fn css_for_field<'b, T>(
     form: &Form<Contextual<'_, T>>,
@@ -22,13 +38,182 @@ pub(crate) fn validate_date<'v>(date: &str) -> rocket::form::Result<'v, ()> {
     if date.is_empty() {
         Err(rocket::form::Error::validation("Please enter a date"))?;
     }
-    if !DATE_REGEX.is_match(date) {
+    if !DATE_REGEX.is_match(date) || Date::parse(date, DATE_FORMAT).is_err() {
         Err(rocket::form::Error::validation("Please enter a valid date"))?;
     }
 
     Ok(())
 }
 
+pub(crate) fn validate_optional_date<'v>(date: &Option<String>) -> rocket::form::Result<'v, ()> {
+    match date {
+        Some(date) if !date.is_empty() => validate_date(date),
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn validate_optional_time<'v>(time: &Option<String>) -> rocket::form::Result<'v, ()> {
+    match time.as_deref() {
+        Some(time) if !time.is_empty() => {
+            if !TIME_REGEX.is_match(time) || Time::parse(time, TIME_FORMAT).is_err() {
+                Err(rocket::form::Error::validation("Please enter a valid time"))?;
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+// Time slots end on the day they start, so an end time comes after a start time
+pub(crate) fn validate_end_time<'v>(
+    end_time: &Option<String>,
+    start_time: &Option<String>,
+) -> rocket::form::Result<'v, ()> {
+    validate_optional_time(end_time)?;
+    let Some(end_time) = end_time.as_deref().filter(|time| !time.is_empty()) else {
+        return Ok(());
+    };
+    let Some(start_time) = start_time.as_deref().filter(|time| !time.is_empty()) else {
+        Err(rocket::form::Error::validation(
+            "Please enter a start time before the end time",
+        ))?
+    };
+    let (Ok(start_time), Ok(end_time)) = (
+        Time::parse(start_time, TIME_FORMAT),
+        Time::parse(end_time, TIME_FORMAT),
+    ) else {
+        return Ok(());
+    };
+    if end_time <= start_time {
+        Err(rocket::form::Error::validation(
+            "Please enter an end time after the start time",
+        ))?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn validate_timezone<'v>(timezone: &Option<String>) -> rocket::form::Result<'v, ()> {
+    match timezone.as_deref() {
+        Some(timezone) if !timezone.is_empty() && !TIMEZONE_REGEX.is_match(timezone) => Err(
+            rocket::form::Error::validation("Please enter a timezone such as Europe/Paris"),
+        )?,
+        _ => Ok(()),
+    }
+}
+
+// Only quotes created from a template need a start date, to place the days of the template
+pub(crate) fn validate_start_date<'v>(
+    start_date: &Option<String>,
+    template_id: &Option<String>,
+) -> rocket::form::Result<'v, ()> {
+    match template_id {
+        Some(template_id) if !template_id.is_empty() => {
+            validate_date(start_date.as_deref().unwrap_or(""))
+        }
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn validate_frequency<'v>(frequency: &str) -> rocket::form::Result<'v, ()> {
+    if ![REPEAT_DAILY, REPEAT_WEEKDAYS, REPEAT_WEEKLY].contains(&frequency) {
+        Err(rocket::form::Error::validation(
+            "Please choose how often to repeat",
+        ))?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn validate_weekdays<'v>(
+    weekdays: &[String],
+    frequency: &str,
+) -> rocket::form::Result<'v, ()> {
+    if frequency == REPEAT_WEEKLY && weekdays.is_empty() {
+        Err(rocket::form::Error::validation(
+            "Please choose at least one day of the week",
+        ))?;
+    }
+
+    Ok(())
+}
+
+// An end date, if any, within a year of the start so that the rule stays reasonable
+pub(crate) fn validate_until<'v>(
+    until: &Option<String>,
+    start_date: &str,
+) -> rocket::form::Result<'v, ()> {
+    validate_optional_date(until)?;
+    let until = until.as_deref().unwrap_or("");
+    let (Ok(until), Ok(start)) = (
+        Date::parse(until, DATE_FORMAT),
+        Date::parse(start_date, DATE_FORMAT),
+    ) else {
+        return Ok(());
+    };
+    if until < start {
+        Err(rocket::form::Error::validation(
+            "Please enter an end date after the start date",
+        ))?;
+    }
+    if (until - start).whole_days() >= MAX_OCCURRENCES as i64 {
+        Err(rocket::form::Error::validation(
+            "Please enter an end date within a year of the start date",
+        ))?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn validate_recurrence_end<'v>(
+    count: &Option<usize>,
+    until: &Option<String>,
+) -> rocket::form::Result<'v, ()> {
+    match count {
+        Some(count) if *count == 0 || *count > MAX_OCCURRENCES => {
+            Err(rocket::form::Error::validation(format!(
+                "Please enter a number of dates between 1 and {MAX_OCCURRENCES}"
+            )))?
+        }
+        None if until.as_deref().unwrap_or("").is_empty() => Err(rocket::form::Error::validation(
+            "Please enter an end date or a number of dates",
+        ))?,
+        _ => Ok(()),
+    }
+}
+
+// Dates are moved either by a number of days or to a new start date
+pub(crate) fn validate_shift<'v>(
+    days: &Option<i64>,
+    start_date: &Option<String>,
+) -> rocket::form::Result<'v, ()> {
+    let start_date = start_date.as_deref().unwrap_or("");
+    match days {
+        Some(_) if !start_date.is_empty() => Err(rocket::form::Error::validation(
+            "Please enter either a number of days or a start date",
+        ))?,
+        Some(days) if !(-MAX_SHIFT_DAYS..=MAX_SHIFT_DAYS).contains(days) => {
+            Err(rocket::form::Error::validation(format!(
+                "Please enter a number of days between -{MAX_SHIFT_DAYS} and {MAX_SHIFT_DAYS}"
+            )))?
+        }
+        None if start_date.is_empty() => Err(rocket::form::Error::validation(
+            "Please enter a number of days or a start date",
+        ))?,
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn validate_collision<'v>(on_collision: &str) -> rocket::form::Result<'v, ()> {
+    if on_collision != COLLISION_SKIP && on_collision != COLLISION_MERGE {
+        Err(rocket::form::Error::validation(
+            "Please choose what to do with existing dates",
+        ))?;
+    }
+
+    Ok(())
+}
+
 pub(crate) fn validate_amount<'v>(amount: &str) -> rocket::form::Result<'v, ()> {
     if amount.is_empty() {
         Err(rocket::form::Error::validation("Please enter an amount"))?;
@@ -42,11 +227,20 @@ pub(crate) fn validate_amount<'v>(amount: &str) -> rocket::form::Result<'v, ()>
     Ok(())
 }
 
+pub(crate) fn validate_optional_amount<'v>(
+    amount: &Option<String>,
+) -> rocket::form::Result<'v, ()> {
+    match amount {
+        Some(amount) if !amount.is_empty() => validate_amount(amount),
+        _ => Ok(()),
+    }
+}
+
 pub(crate) fn validate_quantity<'v>(quantity: &str) -> rocket::form::Result<'v, ()> {
     if quantity.is_empty() {
         Err(rocket::form::Error::validation("Please enter a quantity"))?;
     }
-    if !QUANTITY_REGEX.is_match(quantity) {
+    if !quantity_regex().is_match(quantity) {
         Err(rocket::form::Error::validation(
             "Please enter a valid quantity",
         ))?;
@@ -54,3 +248,146 @@ pub(crate) fn validate_quantity<'v>(quantity: &str) -> rocket::form::Result<'v,
 
     Ok(())
 }
+
+pub(crate) fn validate_unit<'v>(unit: &str) -> rocket::form::Result<'v, ()> {
+    if !UNITS.contains(&unit) {
+        Err(rocket::form::Error::validation("Please choose a unit"))?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn validate_transfer<'v>(action: &str) -> rocket::form::Result<'v, ()> {
+    if action != TRANSFER_COPY && action != TRANSFER_MOVE {
+        Err(rocket::form::Error::validation(
+            "Please choose to copy or move",
+        ))?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn validate_bulk_action<'v>(action: &str) -> rocket::form::Result<'v, ()> {
+    if ![BULK_DELETE, BULK_QUANTITY, BULK_PRICE, BULK_MOVE].contains(&action) {
+        Err(rocket::form::Error::validation("Please choose an action"))?;
+    }
+
+    Ok(())
+}
+
+// The new quantity, or the percentage to change prices by, price cuts stopping short of free
+pub(crate) fn validate_bulk_value<'v>(
+    value: &Option<String>,
+    action: &str,
+) -> rocket::form::Result<'v, ()> {
+    let value = value.as_deref().unwrap_or("");
+    match action {
+        BULK_QUANTITY => validate_quantity(value),
+        BULK_PRICE => match value.parse::<f64>() {
+            Ok(percent) if percent.is_finite() && percent > -100f64 => Ok(()),
+            _ => Err(rocket::form::Error::validation(
+                "Please enter a percentage above -100",
+            ))?,
+        },
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn validate_attachment<'v>(file: &TempFile<'_>) -> rocket::form::Result<'v, ()> {
+    if file.len() == 0 {
+        Err(rocket::form::Error::validation("Please choose a file"))?;
+    }
+    let max_size = config::get().attachment_max_size;
+    if file.len() > max_size.as_u64() || i32::try_from(file.len()).is_err() {
+        Err(rocket::form::Error::validation(format!(
+            "Please choose a file of at most {max_size}"
+        )))?;
+    }
+    if file_name(file)
+        .and_then(|name| content_type(&name))
+        .is_none()
+    {
+        Err(rocket::form::Error::validation(
+            "Please choose a PDF, image, text or office document",
+        ))?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn validate_decision<'v>(decision: &str) -> rocket::form::Result<'v, ()> {
+    if decision != STATUS_ACCEPTED && decision != STATUS_REJECTED {
+        Err(rocket::form::Error::validation(
+            "Please accept or decline the quote",
+        ))?;
+    }
+
+    Ok(())
+}
+
+// Signatures are drawn in the browser and sent as a PNG data URL, an empty one means no drawing
+pub(crate) fn validate_signature<'v>(signature: &Option<String>) -> rocket::form::Result<'v, ()> {
+    match signature {
+        Some(signature) if !signature.is_empty() => {
+            if !signature.starts_with(SIGNATURE_PREFIX) {
+                Err(rocket::form::Error::validation(
+                    "Please draw your signature again",
+                ))?;
+            }
+            if signature.len() > SIGNATURE_MAX_LENGTH {
+                Err(rocket::form::Error::validation(
+                    "Please draw a simpler signature",
+                ))?;
+            }
+            Ok(())
+        }
+        _ => Ok(()),
+    }
+}
+
+pub(crate) fn validate_email<'v>(email: &str) -> rocket::form::Result<'v, ()> {
+    if email.trim().parse::<lettre::Address>().is_err() {
+        Err(rocket::form::Error::validation(
+            "Please enter a valid email address",
+        ))?;
+    }
+
+    Ok(())
+}
+
+// Local and private network hosts are refused, deliveries must not reach services behind the server
+pub(crate) fn validate_webhook_url<'v>(url: &str) -> rocket::form::Result<'v, ()> {
+    let url = url.trim();
+    let parsed = reqwest::Url::parse(url).ok();
+    let host = parsed.as_ref().and_then(|parsed| parsed.host_str());
+    if !(url.starts_with("https://") || url.starts_with("http://"))
+        || url.contains(char::is_whitespace)
+        || host.is_none()
+    {
+        Err(rocket::form::Error::validation(
+            "Please enter an http(s) URL",
+        ))?;
+    }
+    if host.is_some_and(internal_host) {
+        Err(rocket::form::Error::validation(
+            "Please enter a public URL, local and private network addresses are not allowed",
+        ))?;
+    }
+
+    Ok(())
+}
+
+pub(crate) fn validate_events<'v>(events: &[String]) -> rocket::form::Result<'v, ()> {
+    if events.is_empty() {
+        Err(rocket::form::Error::validation(
+            "Please choose at least one event",
+        ))?;
+    }
+    if events.iter().any(|event| !EVENTS.contains(&event.as_str())) {
+        Err(rocket::form::Error::validation(
+            "Please choose known events",
+        ))?;
+    }
+
+    Ok(())
+}
@@ -11,9 +11,21 @@ markup::define! {
                 script["type" = "module", src = {format!("/dist/{}", env!("SCRIPT"))}] {}
             }
             body[class = "flex flex-col min-h-full bg-background text-body leading-[1.5]"] {
-                header[class = "flex items-center px-4 py-2 mb-10 bg-white shadow-[2px_4px_10px_hsl(0,0%,0%,0.1)]"] {
+                header[class = "flex items-center px-4 py-2 mb-10 bg-white shadow-[2px_4px_10px_hsl(0,0%,0%,0.1)] print:hidden"] {
                     div[class = "font-bold text-xl/[1.5] text-header"] { "ABC Corp" }
-                    div[class = "font-bold ml-auto mr-3 text-header"] { "Accountant" }
+                    nav[class = "flex gap-4 ml-8 font-bold"] {
+                        a[href = "/quotes"] { "Quotes" }
+                        a[href = "/calendar"] { "Calendar" }
+                        a[href = "/schedule"] { "Schedule" }
+                        a[href = "/catalog_items"] { "Catalog" }
+                        a[href = "/resources"] { "Resources" }
+                        a[href = "/templates"] { "Templates" }
+                        a[href = "/webhooks"] { "Webhooks" }
+                    }
+                    div[class = "font-bold ml-auto mr-3 text-header",
+                        "hx-get" = "/users/current",
+                        "hx-trigger" = "load",
+                        "hx-swap" = "outerHTML"] {}
                     a[class = "button button-dark", href = "#"] { "Sign out" }
                 }
                 div[id = "flash", class = "fixed top-20 left-1/2 -translate-x-1/2 flex flex-col items-center gap-3 m-w-full w-max px-4 py-0"] {}
@@ -22,6 +34,33 @@ markup::define! {
         }
     }
 
+    // Layout of the pages shown to customers, without the navigation and user menu of the team
+    CustomerLayout<Head: markup::Render, Body: markup::Render>(head: Head, body: Body) {
+        @markup::doctype()
+        html[lang = "en", class = "h-full overflow-y-auto"] {
+            head {
+                meta[name = "viewport", content = "width=device-width,initial-scale=1"] {}
+                meta[charset = "utf-8"] {}
+                @head
+                link[rel = "stylesheet", href = {format!("/dist/{}", env!("STYLESHEET"))}] {}
+                link[rel = "icon", href = {format!("/dist/{}", env!("FAVICON"))}] {}
+                script["type" = "module", src = {format!("/dist/{}", env!("SCRIPT"))}] {}
+            }
+            body[class = "flex flex-col min-h-full bg-background text-body leading-[1.5]"] {
+                header[class = "flex items-center px-4 py-2 mb-10 bg-white shadow-[2px_4px_10px_hsl(0,0%,0%,0.1)]"] {
+                    div[class = "font-bold text-xl/[1.5] text-header"] { "ABC Corp" }
+                }
+                @body
+            }
+        }
+    }
+
+    Page<Content: markup::Render>(content: Content) {
+        main[id = "container", class = "w-full px-4 py-0 mx-auto my-0 max-w-[60rem]"] {
+            @content
+        }
+    }
+
     Flash<'a>(message: &'a str) {
         div[id = "flash", "hx-swap-oob" = "innerHTML"] {
             div[class = "text-[0.875rem] text-white px-4 py-2 bg-dark rounded-full animate-appear-then-fade",
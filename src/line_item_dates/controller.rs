@@ -1,78 +1,152 @@
 use crate::{
     Db, Result,
+    layout::Flash,
     line_item_dates::{
         self,
-        model::{DeleteForm, EditLineItemDateForm, LineItemDatePresenter, NewLineItemDateForm},
+        model::{
+            CopyLineItemDateForm, DeleteForm, EditLineItemDateForm, LineItemDatePresenter,
+            NewLineItemDateForm, RecurrenceForm, RescheduleForm,
+        },
         view::*,
     },
     line_items::{self, model::LineItemPresenter},
-    quotes,
-    rocket_ext::HtmxResponder,
+    quotes::{self, model::QuotePresenter},
+    rocket_ext::{AcceptJson, HtmxRequest, HtmxResponder, quote_changed},
+    time::long_form,
+    users::model::CurrentUser,
 };
 use rocket::{
     fairing::AdHoc,
-    form::{Contextual, Form},
-    http::Header,
-    response::content::RawHtml,
+    form::{Context, Contextual, Form},
+    http::{Header, Status},
+    response::{Redirect, content::RawHtml},
 };
 
 pub(crate) fn stage() -> AdHoc {
     AdHoc::on_ignite("LineItemDate Stage", |rocket| async {
         rocket.mount(
             "/line_item_dates",
-            routes![line_item_date, new, create, edit, update, delete],
+            routes![
+                line_item_date,
+                nav,
+                new,
+                create,
+                edit,
+                update,
+                delete,
+                schedule,
+                preview,
+                reschedule,
+                recurrence,
+                recurrence_preview,
+                repeat,
+                copy_form,
+                copy
+            ],
         )
     })
 }
 
 #[get("/<id>")]
-async fn line_item_date(db: Db, id: String) -> Result<RawHtml<String>> {
+async fn line_item_date(
+    db: Db,
+    accept: AcceptJson,
+    htmx: HtmxRequest,
+    id: String,
+) -> Result<HtmxResponder> {
     let record = db
         .run(move |conn| {
-            let quote = line_item_dates::query::read(conn, &id)?;
+            let quote = line_item_dates::query::read_with_subtotal(conn, &id)?;
             Result::Ok(quote)
         })
         .await?;
 
+    let line_item_date: LineItemDatePresenter = record.into();
+    if accept.0 {
+        return HtmxResponder::json(&line_item_date);
+    }
+
     let template = LineItemDateInfo {
-        line_item_date: &record.into(),
+        line_item_date: &line_item_date,
+    };
+    Ok(HtmxResponder::Ok(htmx.render("Date", template)))
+}
+
+#[get("/nav/<quote_id>")]
+async fn nav(db: Db, quote_id: String) -> Result<RawHtml<String>> {
+    let line_item_dates = quote_dates(&db, quote_id).await?;
+
+    let template = DateNav {
+        line_item_dates: &line_item_dates,
     };
     Ok(RawHtml(template.to_string()))
 }
 
 #[get("/new/<quote_id>")]
-pub(crate) async fn new(quote_id: &str) -> Result<RawHtml<String>> {
+pub(crate) async fn new(htmx: HtmxRequest, quote_id: &str) -> Result<RawHtml<String>> {
     let template = NewForm { quote_id };
-    let html = template.to_string();
+    let html = htmx.render("New date", template);
     Ok(RawHtml(html))
 }
 
 #[post("/create", data = "<form>")]
 pub(crate) async fn create(
     db: Db,
+    user: CurrentUser,
+    accept: AcceptJson,
+    htmx: HtmxRequest,
     form: Form<Contextual<'_, NewLineItemDateForm>>,
 ) -> Result<HtmxResponder> {
     match form.value {
         Some(ref lid_form) => {
             let lid_form = lid_form.clone();
-            let line_item_date = db
+            let (line_item_date, previous_id) = db
                 .run(move |conn| {
-                    let record = line_item_dates::query::insert(conn, &lid_form)?;
-                    Result::Ok(record)
+                    let record = line_item_dates::query::insert(conn, &user.0, &lid_form)?;
+                    let previous_id = line_item_dates::query::previous_id(
+                        conn,
+                        &record.quote_id,
+                        record.date,
+                        record.start_time,
+                    )?;
+                    Result::Ok((record, previous_id))
                 })
                 .await?;
 
+            let line_item_date: LineItemDatePresenter = line_item_date.into();
+            if accept.0 {
+                return HtmxResponder::json(&line_item_date);
+            }
+
+            if !htmx.0 {
+                let uri = format!("/quotes/show/{}", line_item_date.quote_id);
+                return Ok(HtmxResponder::Redirect(Redirect::to(uri)));
+            }
+
             let content = Create {
-                line_item_date: &line_item_date.into(),
+                line_item_date: &line_item_date,
                 line_items: &Vec::new(),
                 message: "Date was successfully created.",
             }
             .to_string();
 
-            Ok(HtmxResponder::Ok(content))
+            let (retarget, reswap) = placement(previous_id);
+            Ok(HtmxResponder::Retarget {
+                content,
+                retarget,
+                reswap,
+            })
         }
         None => {
+            if accept.0 {
+                return Ok(HtmxResponder::invalid(&form.context));
+            }
+
             let template = NewFormWithErrors { form: &form };
+            if !htmx.0 {
+                return Ok(HtmxResponder::Ok(htmx.render("New date", template)));
+            }
+
             let content = template.to_string();
             Ok(HtmxResponder::Retarget {
                 content,
@@ -84,7 +158,12 @@ pub(crate) async fn create(
 }
 
 #[get("/edit/<id>")]
-pub(crate) async fn edit(db: Db, id: String) -> Result<RawHtml<String>> {
+pub(crate) async fn edit(
+    db: Db,
+    accept: AcceptJson,
+    htmx: HtmxRequest,
+    id: String,
+) -> Result<HtmxResponder> {
     let record = db
         .run(move |conn| {
             let quote = line_item_dates::query::read(conn, &id)?;
@@ -93,27 +172,50 @@ pub(crate) async fn edit(db: Db, id: String) -> Result<RawHtml<String>> {
         .await?;
 
     let line_item_date: &LineItemDatePresenter = &record.into();
+    if accept.0 {
+        return HtmxResponder::json(line_item_date);
+    }
+
     let template = EditForm { line_item_date };
-    let html = template.to_string();
-    Ok(RawHtml(html))
+    let html = htmx.render("Edit date", template);
+    Ok(HtmxResponder::Ok(html))
 }
 
 #[post("/update", data = "<form>")]
 pub(crate) async fn update(
     db: Db,
+    user: CurrentUser,
+    accept: AcceptJson,
+    htmx: HtmxRequest,
     form: Form<Contextual<'_, EditLineItemDateForm>>,
-) -> Result<RawHtml<String>> {
+) -> Result<HtmxResponder> {
     match form.value {
         Some(ref lid_form) => {
             let lid_form = lid_form.clone();
-            let line_item_date = db
+            let (line_item_date, previous_id) = db
                 .run(move |conn| {
-                    let record = line_item_dates::query::update(conn, &lid_form)?;
-                    Result::Ok(record)
+                    let record = line_item_dates::query::update(conn, &user.0, &lid_form)?;
+                    let previous_id = line_item_dates::query::previous_id(
+                        conn,
+                        &record.quote_id,
+                        record.date,
+                        record.start_time,
+                    )?;
+                    Result::Ok((record, previous_id))
                 })
                 .await?;
 
-            let lid_id = line_item_date.id.clone();
+            let line_item_date: LineItemDatePresenter = line_item_date.into();
+            if accept.0 {
+                return HtmxResponder::json(&line_item_date);
+            }
+
+            if !htmx.0 {
+                let uri = format!("/quotes/show/{}", line_item_date.quote_id);
+                return Ok(HtmxResponder::Redirect(Redirect::to(uri)));
+            }
+
+            let lid_id = line_item_date.id();
             let line_items = db
                 .run(move |conn| {
                     let line_items = line_items::query::all_for_line_item_date(conn, &lid_id)?
@@ -125,37 +227,436 @@ pub(crate) async fn update(
                 .await?;
 
             let content = Update {
-                line_item_date: &line_item_date.into(),
+                line_item_date: &line_item_date,
                 line_items: &line_items,
                 message: "Date was successfully updated.",
             }
             .to_string();
 
-            Ok(RawHtml(content))
+            let (retarget, reswap) = placement(previous_id);
+            Ok(HtmxResponder::RetargetChanged {
+                content,
+                retarget,
+                reswap,
+                trigger: quote_changed(),
+            })
         }
         None => {
+            if accept.0 {
+                return Ok(HtmxResponder::invalid(&form.context));
+            }
+
             let template = EditFormWithErrors { form: &form };
-            let content = template.to_string();
-            Ok(RawHtml(content))
+            Ok(HtmxResponder::Ok(htmx.render("Edit date", template)))
         }
     }
 }
 
 #[post("/delete", data = "<form>")]
-async fn delete(db: Db, form: Form<DeleteForm>) -> Result<RawHtml<String>> {
+async fn delete(
+    db: Db,
+    user: CurrentUser,
+    accept: AcceptJson,
+    htmx: HtmxRequest,
+    form: Form<DeleteForm>,
+) -> Result<HtmxResponder> {
     let quote = db
         .run(move |conn| {
-            let line_item_date = line_item_dates::query::delete(conn, &form.id)?;
+            let line_item_date = line_item_dates::query::delete(conn, &user.0, &form.id)?;
             let quote = quotes::query::read(conn, &line_item_date.quote_id)?;
             Result::Ok(quote)
         })
         .await?;
 
-    Ok(RawHtml(
-        Destroy {
-            quote: &quote.into(),
-            message: "Date was successfully destroyed.",
+    if accept.0 {
+        return Ok(HtmxResponder::Empty(Status::NoContent));
+    }
+
+    if !htmx.0 {
+        let uri = format!("/quotes/show/{}", quote.id);
+        return Ok(HtmxResponder::Redirect(Redirect::to(uri)));
+    }
+
+    let content = Destroy {
+        quote: &quote.into(),
+        message: "Date was successfully destroyed.",
+    }
+    .to_string();
+    Ok(HtmxResponder::Changed {
+        content,
+        trigger: quote_changed(),
+    })
+}
+
+#[get("/reschedule/<quote_id>")]
+async fn schedule(htmx: HtmxRequest, quote_id: &str) -> RawHtml<String> {
+    let template = Reschedule {
+        quote_id,
+        errors: &Vec::new(),
+    };
+    RawHtml(htmx.render("Reschedule", template))
+}
+
+#[post("/reschedule/preview", data = "<form>")]
+async fn preview(
+    db: Db,
+    htmx: HtmxRequest,
+    form: Form<Contextual<'_, RescheduleForm>>,
+) -> Result<RawHtml<String>> {
+    let quote_id = form.context.field_value("quote_id").unwrap_or("");
+    let Some(reschedule_form) = form.value.clone() else {
+        let template = Reschedule {
+            quote_id,
+            errors: &errors(&form.context),
+        };
+        return Ok(RawHtml(htmx.render("Reschedule", template)));
+    };
+
+    let moves = db
+        .run(move |conn| line_item_dates::query::shifted(conn, &reschedule_form))
+        .await?;
+    let days = match moves.first() {
+        Some((before, after)) => (after.date - before.date).whole_days(),
+        None => 0,
+    };
+    if days == 0 {
+        let template = Reschedule {
+            quote_id,
+            errors: &vec![String::from(
+                "Please enter a number of days or a new start date",
+            )],
+        };
+        return Ok(RawHtml(htmx.render("Reschedule", template)));
+    }
+
+    let moves = moves
+        .into_iter()
+        .map(|(before, after)| (long_form(before.date), long_form(after.date)))
+        .collect::<Vec<(String, String)>>();
+    let template = ReschedulePreview {
+        quote_id,
+        days,
+        moves: &moves,
+    };
+    Ok(RawHtml(htmx.render("Reschedule", template)))
+}
+
+#[post("/reschedule", data = "<form>")]
+async fn reschedule(
+    db: Db,
+    user: CurrentUser,
+    accept: AcceptJson,
+    htmx: HtmxRequest,
+    form: Form<Contextual<'_, RescheduleForm>>,
+) -> Result<HtmxResponder> {
+    let Some(reschedule_form) = form.value.clone() else {
+        if accept.0 {
+            return Ok(HtmxResponder::invalid(&form.context));
         }
-        .to_string(),
-    ))
+
+        let template = Reschedule {
+            quote_id: form.context.field_value("quote_id").unwrap_or(""),
+            errors: &errors(&form.context),
+        };
+        if !htmx.0 {
+            return Ok(HtmxResponder::Ok(htmx.render("Reschedule", template)));
+        }
+        return Ok(HtmxResponder::Retarget {
+            content: template.to_string(),
+            retarget: Header::new("HX-Retarget", "#line_item_date_reschedule"),
+            reswap: Header::new("HX-Reswap", "outerHTML"),
+        });
+    };
+
+    let quote_id = reschedule_form.quote_id.clone();
+    let line_item_dates = db
+        .run(move |conn| {
+            let records = line_item_dates::query::reschedule(conn, &user.0, &reschedule_form)?
+                .into_iter()
+                .map(|record| record.into())
+                .collect::<Vec<LineItemDatePresenter>>();
+            Result::Ok(records)
+        })
+        .await?;
+
+    if accept.0 {
+        return HtmxResponder::json(&line_item_dates);
+    }
+
+    if !htmx.0 {
+        let uri = format!("/quotes/show/{quote_id}");
+        return Ok(HtmxResponder::Redirect(Redirect::to(uri)));
+    }
+
+    Ok(HtmxResponder::Refresh {
+        content: String::new(),
+        refresh: Header::new("HX-Refresh", "true"),
+    })
+}
+
+#[get("/repeat/<quote_id>")]
+async fn recurrence(db: Db, htmx: HtmxRequest, quote_id: String) -> Result<RawHtml<String>> {
+    let line_item_dates = quote_dates(&db, quote_id.clone()).await?;
+    let template = Recurrence {
+        quote_id: &quote_id,
+        line_item_dates: &line_item_dates,
+        errors: &Vec::new(),
+    };
+    Ok(RawHtml(htmx.render("Repeat", template)))
+}
+
+#[post("/repeat/preview", data = "<form>")]
+async fn recurrence_preview(
+    db: Db,
+    htmx: HtmxRequest,
+    form: Form<Contextual<'_, RecurrenceForm>>,
+) -> Result<RawHtml<String>> {
+    let quote_id = form
+        .context
+        .field_value("quote_id")
+        .unwrap_or("")
+        .to_string();
+    let Some(recurrence_form) = form.value.clone() else {
+        let line_item_dates = quote_dates(&db, quote_id.clone()).await?;
+        let template = Recurrence {
+            quote_id: &quote_id,
+            line_item_dates: &line_item_dates,
+            errors: &errors(&form.context),
+        };
+        return Ok(RawHtml(htmx.render("Repeat", template)));
+    };
+
+    let dates = recurrence_form.dates();
+    if dates.is_empty() {
+        let line_item_dates = quote_dates(&db, quote_id.clone()).await?;
+        let template = Recurrence {
+            quote_id: &quote_id,
+            line_item_dates: &line_item_dates,
+            errors: &vec![String::from(
+                "No date matches, please check the days and the end",
+            )],
+        };
+        return Ok(RawHtml(htmx.render("Repeat", template)));
+    }
+
+    let existing = db
+        .run(move |conn| line_item_dates::query::existing_dates(conn, &quote_id))
+        .await?;
+    let dates = dates
+        .into_iter()
+        .map(|date| (long_form(date), existing.contains(&date)))
+        .collect::<Vec<(String, bool)>>();
+    let template = RecurrencePreview {
+        form: &recurrence_form,
+        dates: &dates,
+    };
+    Ok(RawHtml(htmx.render("Repeat", template)))
+}
+
+#[post("/repeat", data = "<form>")]
+async fn repeat(
+    db: Db,
+    user: CurrentUser,
+    accept: AcceptJson,
+    htmx: HtmxRequest,
+    form: Form<Contextual<'_, RecurrenceForm>>,
+) -> Result<HtmxResponder> {
+    let Some(recurrence_form) = form.value.clone() else {
+        if accept.0 {
+            return Ok(HtmxResponder::invalid(&form.context));
+        }
+
+        let quote_id = form
+            .context
+            .field_value("quote_id")
+            .unwrap_or("")
+            .to_string();
+        let line_item_dates = quote_dates(&db, quote_id.clone()).await?;
+        let template = Recurrence {
+            quote_id: &quote_id,
+            line_item_dates: &line_item_dates,
+            errors: &errors(&form.context),
+        };
+        if !htmx.0 {
+            return Ok(HtmxResponder::Ok(htmx.render("Repeat", template)));
+        }
+        return Ok(HtmxResponder::Retarget {
+            content: template.to_string(),
+            retarget: Header::new("HX-Retarget", "#line_item_date_recurrence"),
+            reswap: Header::new("HX-Reswap", "outerHTML"),
+        });
+    };
+
+    let quote_id = recurrence_form.quote_id.clone();
+    let line_item_dates = db
+        .run(move |conn| {
+            let records = line_item_dates::query::repeat(conn, &user.0, &recurrence_form)?
+                .into_iter()
+                .map(|record| record.into())
+                .collect::<Vec<LineItemDatePresenter>>();
+            Result::Ok(records)
+        })
+        .await?;
+
+    if accept.0 {
+        return HtmxResponder::json(&line_item_dates);
+    }
+
+    if !htmx.0 {
+        let uri = format!("/quotes/show/{quote_id}");
+        return Ok(HtmxResponder::Redirect(Redirect::to(uri)));
+    }
+
+    Ok(HtmxResponder::Refresh {
+        content: String::new(),
+        refresh: Header::new("HX-Refresh", "true"),
+    })
+}
+
+#[get("/copy/<id>")]
+async fn copy_form(db: Db, htmx: HtmxRequest, id: String) -> Result<RawHtml<String>> {
+    let (line_item_date, quotes) = db
+        .run(move |conn| {
+            let line_item_date = line_item_dates::query::read(conn, &id)?;
+            let quotes = quotes::query::all(conn)?
+                .into_iter()
+                .map(|record| record.into())
+                .filter(|quote: &QuotePresenter| !quote.is_locked())
+                .collect::<Vec<QuotePresenter>>();
+            Result::Ok((line_item_date, quotes))
+        })
+        .await?;
+
+    let template = CopyForm {
+        line_item_date: &line_item_date.into(),
+        quotes: &quotes,
+    };
+    Ok(RawHtml(htmx.render("Copy date", template)))
+}
+
+#[post("/copy", data = "<form>")]
+async fn copy(
+    db: Db,
+    user: CurrentUser,
+    accept: AcceptJson,
+    htmx: HtmxRequest,
+    form: Form<Contextual<'_, CopyLineItemDateForm>>,
+) -> Result<HtmxResponder> {
+    let Some(copy_form) = form.value.clone() else {
+        if accept.0 {
+            return Ok(HtmxResponder::invalid(&form.context));
+        }
+        let message = form
+            .context
+            .errors()
+            .map(|error| error.to_string())
+            .collect::<Vec<String>>()
+            .join(" ");
+        return Ok(HtmxResponder::Ok(Flash { message: &message }.to_string()));
+    };
+
+    let source_id = copy_form.id.clone();
+    let (source, line_item_date, created, previous_id) = db
+        .run(move |conn| {
+            let (record, created) = line_item_dates::query::copy(conn, &user.0, &copy_form)?;
+            let source = line_item_dates::query::read(conn, &source_id)?;
+            let previous_id = line_item_dates::query::previous_id(
+                conn,
+                &record.quote_id,
+                record.date,
+                record.start_time,
+            )?;
+            let record = line_item_dates::query::read_with_subtotal(conn, &record.id)?;
+            Result::Ok((source, record, created, previous_id))
+        })
+        .await?;
+
+    let line_item_date: LineItemDatePresenter = line_item_date.into();
+    if accept.0 {
+        return HtmxResponder::json(&line_item_date);
+    }
+
+    if !htmx.0 {
+        let uri = format!("/quotes/show/{}", source.quote_id);
+        return Ok(HtmxResponder::Redirect(Redirect::to(uri)));
+    }
+
+    let source: LineItemDatePresenter = source.into();
+    if line_item_date.quote_id != source.quote_id {
+        let content = CloseTransfer {
+            line_item_date: &source,
+            message: "Date was successfully copied to the other quote.",
+        }
+        .to_string();
+        return Ok(HtmxResponder::Ok(content));
+    }
+
+    let (lid_id, quote_id) = (line_item_date.id(), line_item_date.quote_id.clone());
+    let (line_items, quote) = db
+        .run(move |conn| {
+            let line_items = line_items::query::all_for_line_item_date(conn, &lid_id)?
+                .into_iter()
+                .map(|record| record.into())
+                .collect::<Vec<LineItemPresenter>>();
+            let quote = quotes::query::read(conn, &quote_id)?;
+            Result::Ok((line_items, quote))
+        })
+        .await?;
+
+    let content = Copy {
+        source: &source,
+        line_item_date: &line_item_date,
+        line_items: &line_items,
+        quote: &quote.into(),
+        message: "Date was successfully copied.",
+    }
+    .to_string();
+
+    // A new date goes in chronological order, a date copied into an existing one replaces it
+    let (retarget, reswap) = if created {
+        placement(previous_id)
+    } else {
+        (
+            Header::new("HX-Retarget", format!("#{}", line_item_date.dom_id())),
+            Header::new("HX-Reswap", "outerHTML"),
+        )
+    };
+    Ok(HtmxResponder::RetargetChanged {
+        content,
+        retarget,
+        reswap,
+        trigger: quote_changed(),
+    })
+}
+
+fn errors(context: &Context<'_>) -> Vec<String> {
+    context
+        .errors()
+        .map(|error| error.to_string())
+        .collect::<Vec<String>>()
+}
+
+async fn quote_dates(db: &Db, quote_id: String) -> Result<Vec<LineItemDatePresenter>> {
+    db.run(move |conn| {
+        let records = line_item_dates::query::all(conn, quote_id)?
+            .into_iter()
+            .map(|record| record.into())
+            .collect::<Vec<LineItemDatePresenter>>();
+        Result::Ok(records)
+    })
+    .await
+}
+
+// Where to swap a date block so that the dates stay in chronological order
+fn placement(previous_id: Option<String>) -> (Header<'static>, Header<'static>) {
+    match previous_id {
+        Some(id) => (
+            Header::new("HX-Retarget", format!("#line_item_date_{id}")),
+            Header::new("HX-Reswap", "afterend"),
+        ),
+        None => (
+            Header::new("HX-Retarget", "#line_item_dates"),
+            Header::new("HX-Reswap", "afterbegin"),
+        ),
+    }
 }
@@ -1,31 +1,176 @@
 use crate::{
-    forms::validate_date,
+    audit_entries::model::Audited,
+    forms::{
+        validate_collision, validate_date, validate_end_time, validate_frequency,
+        validate_optional_date, validate_optional_time, validate_recurrence_end, validate_shift,
+        validate_timezone, validate_until, validate_weekdays,
+    },
     quotes::model::QuoteWithTotal,
     schema::line_item_dates,
-    time::{long_form, parse_date, short_form},
+    time::{long_form, parse_date, parse_optional_time, short_form, time_form},
 };
+use currency_rs::Currency;
 use diesel::prelude::*;
-use time::{Date, OffsetDateTime};
+use diesel::sql_types::*;
+use rocket::serde::Serialize;
+use time::{Date, Duration, OffsetDateTime, Time, Weekday};
 use ulid::Ulid;
 
-#[derive(Debug, Insertable, Queryable)]
+pub(crate) const REPEAT_DAILY: &str = "daily";
+pub(crate) const REPEAT_WEEKDAYS: &str = "weekdays";
+pub(crate) const REPEAT_WEEKLY: &str = "weekly";
+pub(crate) const COLLISION_SKIP: &str = "skip";
+pub(crate) const COLLISION_MERGE: &str = "merge";
+// A rule giving more dates than this is most likely a mistake
+pub(crate) const MAX_OCCURRENCES: usize = 366;
+// Ten years either way, further is a typo
+pub(crate) const MAX_SHIFT_DAYS: i64 = 3650;
+pub(crate) const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Monday,
+    Weekday::Tuesday,
+    Weekday::Wednesday,
+    Weekday::Thursday,
+    Weekday::Friday,
+    Weekday::Saturday,
+    Weekday::Sunday,
+];
+
+#[derive(Debug, QueryableByName)]
+pub struct LineItemDateWithSubtotal {
+    #[diesel(sql_type = Text)]
+    pub id: String,
+    #[diesel(sql_type = Text)]
+    pub quote_id: String,
+    #[diesel(sql_type = diesel::sql_types::Date)]
+    pub date: Date,
+    #[diesel(sql_type = Nullable<diesel::sql_types::Time>)]
+    pub start_time: Option<Time>,
+    #[diesel(sql_type = Nullable<diesel::sql_types::Time>)]
+    pub end_time: Option<Time>,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub timezone: Option<String>,
+    #[diesel(sql_type = currency_rs::diesel2::sqlite::sql_types::Currency)]
+    pub subtotal: Currency,
+}
+
+// A date line items can be copied or moved to, named after its quote
+#[derive(Debug, QueryableByName)]
+pub struct LineItemDateOption {
+    #[diesel(sql_type = Text)]
+    pub id: String,
+    #[diesel(sql_type = Text)]
+    pub quote_name: String,
+    #[diesel(sql_type = diesel::sql_types::Date)]
+    pub date: Date,
+    #[diesel(sql_type = Nullable<diesel::sql_types::Time>)]
+    pub start_time: Option<Time>,
+    #[diesel(sql_type = Nullable<diesel::sql_types::Time>)]
+    pub end_time: Option<Time>,
+}
+
+impl LineItemDateOption {
+    pub fn label(&self) -> String {
+        match time_range(self.start_time, self.end_time, &None) {
+            Some(range) => format!("{} · {} {range}", self.quote_name, long_form(self.date)),
+            None => format!("{} · {}", self.quote_name, long_form(self.date)),
+        }
+    }
+}
+
+// Time slot of a date, e.g. `09:00–12:30 Europe/Paris` or `from 14:00`
+pub(crate) fn time_range(
+    start_time: Option<Time>,
+    end_time: Option<Time>,
+    timezone: &Option<String>,
+) -> Option<String> {
+    let range = match (start_time, end_time) {
+        (Some(start_time), Some(end_time)) => {
+            format!("{}–{}", time_form(start_time), time_form(end_time))
+        }
+        (Some(start_time), None) => format!("from {}", time_form(start_time)),
+        _ => return None,
+    };
+    match timezone {
+        Some(timezone) => Some(format!("{range} {timezone}")),
+        None => Some(range),
+    }
+}
+
+// The time slot of a form, a timezone only making sense along with a start time
+fn slot(
+    start_time: &Option<String>,
+    end_time: &Option<String>,
+    timezone: &Option<String>,
+) -> (Option<Time>, Option<Time>, Option<String>) {
+    let start_time = parse_optional_time(start_time);
+    let end_time = start_time.and(parse_optional_time(end_time));
+    let timezone = start_time.and(
+        timezone
+            .as_deref()
+            .map(str::trim)
+            .filter(|timezone| !timezone.is_empty())
+            .map(str::to_string),
+    );
+    (start_time, end_time, timezone)
+}
+
+#[derive(Debug, Insertable, Queryable, Serialize)]
+#[serde(crate = "rocket::serde")]
 pub struct LineItemDate {
     pub id: String,
     pub quote_id: String,
+    #[serde(serialize_with = "crate::time::serialize")]
     pub date: Date,
+    #[serde(skip)]
     pub created_at: OffsetDateTime,
+    #[serde(skip)]
     pub updated_at: OffsetDateTime,
+    #[serde(serialize_with = "crate::time::serialize_optional_time")]
+    pub start_time: Option<Time>,
+    #[serde(serialize_with = "crate::time::serialize_optional_time")]
+    pub end_time: Option<Time>,
+    pub timezone: Option<String>,
+}
+
+impl Audited for LineItemDate {
+    const ENTITY: &'static str = "line_item_date";
+
+    fn entity_id(&self) -> &str {
+        &self.id
+    }
+}
+
+impl LineItemDate {
+    // The same date moved by a number of days, `None` when it falls outside of the calendar
+    pub(crate) fn shifted(&self, days: i64) -> Option<LineItemDate> {
+        let date = self.date.checked_add(Duration::days(days))?;
+        Some(LineItemDate {
+            id: self.id.clone(),
+            quote_id: self.quote_id.clone(),
+            date,
+            created_at: self.created_at,
+            updated_at: OffsetDateTime::now_utc(),
+            start_time: self.start_time,
+            end_time: self.end_time,
+            timezone: self.timezone.clone(),
+        })
+    }
 }
 
 impl From<&EditLineItemDateForm> for LineItemDate {
     fn from(value: &EditLineItemDateForm) -> Self {
         let date = parse_date(&value.date);
+        let (start_time, end_time, timezone) =
+            slot(&value.start_time, &value.end_time, &value.timezone);
         LineItemDate {
             id: value.id.clone(),
             quote_id: value.quote_id.clone(),
             date,
             created_at: OffsetDateTime::now_utc(),
             updated_at: OffsetDateTime::now_utc(),
+            start_time,
+            end_time,
+            timezone,
         }
     }
 }
@@ -33,12 +178,17 @@ impl From<&EditLineItemDateForm> for LineItemDate {
 impl From<&NewLineItemDateForm> for LineItemDate {
     fn from(value: &NewLineItemDateForm) -> Self {
         let date = parse_date(&value.date);
+        let (start_time, end_time, timezone) =
+            slot(&value.start_time, &value.end_time, &value.timezone);
         LineItemDate {
             id: Ulid::new().to_string(),
             quote_id: value.quote_id.clone(),
             date,
             created_at: OffsetDateTime::now_utc(),
             updated_at: OffsetDateTime::now_utc(),
+            start_time,
+            end_time,
+            timezone,
         }
     }
 }
@@ -51,6 +201,12 @@ pub struct EditLineItemDateForm {
     pub quote_id: String,
     #[field(validate = validate_date())]
     pub date: String,
+    #[field(validate = validate_optional_time())]
+    pub start_time: Option<String>,
+    #[field(validate = validate_end_time(&self.start_time))]
+    pub end_time: Option<String>,
+    #[field(validate = validate_timezone())]
+    pub timezone: Option<String>,
 }
 
 #[derive(Clone, Debug, FromForm)]
@@ -59,13 +215,136 @@ pub struct NewLineItemDateForm {
     pub quote_id: String,
     #[field(validate = validate_date())]
     pub date: String,
+    #[field(validate = validate_optional_time())]
+    pub start_time: Option<String>,
+    #[field(validate = validate_end_time(&self.start_time))]
+    pub end_time: Option<String>,
+    #[field(validate = validate_timezone())]
+    pub timezone: Option<String>,
 }
 
-#[derive(Debug, Default)]
+// Copies a date and its line items to a quote, possibly the same one, on the date given
+#[derive(Clone, Debug, FromForm)]
+pub(crate) struct CopyLineItemDateForm {
+    #[field(validate = len(1..))]
+    pub(crate) id: String,
+    #[field(validate = len(1..).or_else(msg!("Please choose a quote")))]
+    pub(crate) quote_id: String,
+    #[field(validate = validate_date())]
+    pub(crate) date: String,
+}
+
+// Moves every date of a quote by a number of days, or so that the first date falls on `start_date`
+#[derive(Clone, Debug, FromForm)]
+pub(crate) struct RescheduleForm {
+    #[field(validate = len(1..))]
+    pub(crate) quote_id: String,
+    #[field(validate = validate_shift(&self.start_date))]
+    pub(crate) days: Option<i64>,
+    #[field(validate = validate_optional_date())]
+    pub(crate) start_date: Option<String>,
+}
+
+impl RescheduleForm {
+    pub(crate) fn offset(&self, first: Date) -> i64 {
+        match self.start_date.as_deref().filter(|date| !date.is_empty()) {
+            Some(start_date) => (parse_date(start_date) - first).whole_days(),
+            None => self.days.unwrap_or(0),
+        }
+    }
+}
+
+// Generates dates from `start_date` every day, every weekday or on some days of every week, until a
+// date or for a number of dates, optionally copying the line items of a date of the quote into each.
+// Days already on the quote without a time slot are skipped, or get the line items copied as well.
+#[derive(Clone, Debug, FromForm)]
+pub struct RecurrenceForm {
+    #[field(validate = len(1..))]
+    pub quote_id: String,
+    #[field(validate = validate_date())]
+    pub start_date: String,
+    #[field(validate = validate_frequency())]
+    pub frequency: String,
+    #[field(validate = validate_weekdays(&self.frequency))]
+    pub weekdays: Vec<String>,
+    #[field(validate = validate_until(&self.start_date))]
+    pub until: Option<String>,
+    #[field(validate = validate_recurrence_end(&self.until))]
+    pub count: Option<usize>,
+    pub source_id: Option<String>,
+    #[field(validate = validate_collision())]
+    pub on_collision: String,
+}
+
+impl RecurrenceForm {
+    pub(crate) fn dates(&self) -> Vec<Date> {
+        let start = parse_date(&self.start_date);
+        let until = match self.until.as_deref().filter(|date| !date.is_empty()) {
+            Some(until) => parse_date(until),
+            None => start + Duration::weeks(MAX_OCCURRENCES as i64),
+        };
+        let count = self.count.unwrap_or(MAX_OCCURRENCES).min(MAX_OCCURRENCES);
+        let weekdays = self.on_weekdays();
+
+        let mut dates = Vec::new();
+        let mut next = Some(start);
+        while let Some(date) = next.filter(|date| *date <= until && dates.len() < count) {
+            if weekdays.contains(&date.weekday()) {
+                dates.push(date);
+            }
+            next = date.next_day();
+        }
+        dates
+    }
+
+    pub(crate) fn source_id(&self) -> Option<&str> {
+        self.source_id.as_deref().filter(|id| !id.is_empty())
+    }
+
+    pub(crate) fn is_merge(&self) -> bool {
+        self.on_collision == COLLISION_MERGE
+    }
+
+    fn on_weekdays(&self) -> Vec<Weekday> {
+        match self.frequency.as_str() {
+            REPEAT_DAILY => WEEKDAYS.to_vec(),
+            REPEAT_WEEKDAYS => WEEKDAYS[..5].to_vec(),
+            _ => WEEKDAYS
+                .into_iter()
+                .filter(|weekday| self.weekdays.contains(&weekday.to_string()))
+                .collect(),
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
 pub struct LineItemDatePresenter {
     pub id: Option<String>,
     pub quote_id: String,
+    #[serde(serialize_with = "crate::time::serialize_optional")]
     pub date: Option<Date>,
+    #[serde(serialize_with = "crate::time::serialize_optional_time")]
+    pub start_time: Option<Time>,
+    #[serde(serialize_with = "crate::time::serialize_optional_time")]
+    pub end_time: Option<Time>,
+    pub timezone: Option<String>,
+    #[serde(serialize_with = "crate::currency::serialize")]
+    pub subtotal: Currency,
+}
+
+impl Default for LineItemDatePresenter {
+    fn default() -> Self {
+        LineItemDatePresenter {
+            id: None,
+            quote_id: String::from(""),
+            date: None,
+            start_time: None,
+            end_time: None,
+            timezone: None,
+            subtotal: Currency::new_float(0f64, None),
+        }
+    }
 }
 
 impl LineItemDatePresenter {
@@ -91,6 +370,18 @@ impl LineItemDatePresenter {
         format!("edit_line_item_date_{}", self.id())
     }
 
+    pub fn transfer_dom_id(&self) -> String {
+        format!("line_item_date_{}_transfer", self.id())
+    }
+
+    pub fn bulk_dom_id(&self) -> String {
+        format!("line_item_date_{}_bulk", self.id())
+    }
+
+    pub fn subtotal_dom_id(&self) -> String {
+        format!("line_item_date_{}_subtotal", self.id())
+    }
+
     pub fn date_long_form(&self) -> String {
         match self.date {
             Some(date) => long_form(date),
@@ -104,6 +395,18 @@ impl LineItemDatePresenter {
             None => "".to_string(),
         }
     }
+
+    pub fn time_range(&self) -> Option<String> {
+        time_range(self.start_time, self.end_time, &self.timezone)
+    }
+
+    pub fn start_time_form(&self) -> String {
+        self.start_time.map(time_form).unwrap_or_default()
+    }
+
+    pub fn end_time_form(&self) -> String {
+        self.end_time.map(time_form).unwrap_or_default()
+    }
 }
 
 impl From<LineItemDate> for LineItemDatePresenter {
@@ -112,6 +415,24 @@ impl From<LineItemDate> for LineItemDatePresenter {
             id: Some(value.id),
             quote_id: value.quote_id.to_string(),
             date: Some(value.date),
+            start_time: value.start_time,
+            end_time: value.end_time,
+            timezone: value.timezone,
+            subtotal: Currency::new_float(0f64, None),
+        }
+    }
+}
+
+impl From<LineItemDateWithSubtotal> for LineItemDatePresenter {
+    fn from(value: LineItemDateWithSubtotal) -> Self {
+        LineItemDatePresenter {
+            id: Some(value.id),
+            quote_id: value.quote_id,
+            date: Some(value.date),
+            start_time: value.start_time,
+            end_time: value.end_time,
+            timezone: value.timezone,
+            subtotal: value.subtotal,
         }
     }
 }
@@ -119,10 +440,16 @@ impl From<LineItemDate> for LineItemDatePresenter {
 impl From<EditLineItemDateForm> for LineItemDatePresenter {
     fn from(value: EditLineItemDateForm) -> Self {
         let date = parse_date(&value.date);
+        let (start_time, end_time, timezone) =
+            slot(&value.start_time, &value.end_time, &value.timezone);
         LineItemDatePresenter {
             id: Some(value.id),
             quote_id: value.quote_id,
             date: Some(date),
+            start_time,
+            end_time,
+            timezone,
+            subtotal: Currency::new_float(0f64, None),
         }
     }
 }
@@ -130,10 +457,16 @@ impl From<EditLineItemDateForm> for LineItemDatePresenter {
 impl From<NewLineItemDateForm> for LineItemDatePresenter {
     fn from(value: NewLineItemDateForm) -> Self {
         let date = parse_date(&value.date);
+        let (start_time, end_time, timezone) =
+            slot(&value.start_time, &value.end_time, &value.timezone);
         LineItemDatePresenter {
             id: None,
             quote_id: value.quote_id,
             date: Some(date),
+            start_time,
+            end_time,
+            timezone,
+            subtotal: Currency::new_float(0f64, None),
         }
     }
 }
@@ -142,3 +475,61 @@ impl From<NewLineItemDateForm> for LineItemDatePresenter {
 pub(crate) struct DeleteForm {
     pub(crate) id: String,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{REPEAT_DAILY, REPEAT_WEEKDAYS, REPEAT_WEEKLY, RecurrenceForm};
+    use time::{Date, Month};
+
+    fn form(
+        frequency: &str,
+        weekdays: &[&str],
+        until: &str,
+        count: Option<usize>,
+    ) -> RecurrenceForm {
+        RecurrenceForm {
+            quote_id: String::from("quote"),
+            start_date: String::from("2024-02-26"),
+            frequency: frequency.to_string(),
+            weekdays: weekdays.iter().map(|weekday| weekday.to_string()).collect(),
+            until: Some(until.to_string()),
+            count,
+            source_id: None,
+            on_collision: String::from("skip"),
+        }
+    }
+
+    fn day(month: Month, day: u8) -> Date {
+        Date::from_calendar_date(2024, month, day).unwrap()
+    }
+
+    #[test]
+    fn recurrences() {
+        let dates = form(REPEAT_DAILY, &[], "2024-03-01", None).dates();
+        assert_eq!(5, dates.len());
+        assert_eq!(day(Month::February, 29), dates[3]);
+
+        let dates = form(REPEAT_WEEKDAYS, &[], "", Some(6)).dates();
+        assert_eq!(day(Month::March, 4), dates[5]);
+
+        let dates = form(
+            REPEAT_WEEKLY,
+            &["Monday", "Thursday"],
+            "2024-03-11",
+            Some(8),
+        )
+        .dates();
+        let expected = vec![
+            day(Month::February, 26),
+            day(Month::February, 29),
+            day(Month::March, 4),
+            day(Month::March, 7),
+            day(Month::March, 11),
+        ];
+        assert_eq!(expected, dates);
+
+        let dates = form(REPEAT_WEEKLY, &["Monday"], "", Some(8)).dates();
+        assert_eq!(8, dates.len());
+        assert_eq!(day(Month::April, 15), dates[7]);
+    }
+}
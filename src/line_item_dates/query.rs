@@ -1,21 +1,157 @@
 use crate::{
     Result,
-    line_item_dates::model::{EditLineItemDateForm, LineItemDate, NewLineItemDateForm},
-    line_items,
+    audit_entries::{
+        self,
+        model::{ACTION_CREATE, ACTION_DELETE, ACTION_UPDATE, AuditEntry},
+    },
+    line_item_dates::model::{
+        CopyLineItemDateForm, EditLineItemDateForm, LineItemDate, LineItemDateOption,
+        LineItemDateWithSubtotal, NewLineItemDateForm, RecurrenceForm, RescheduleForm,
+    },
+    line_items::{self, model::LineItemPresenter},
+    quotes::{self, model::STATUS_DRAFT},
     schema::line_item_dates,
+    time::parse_date,
+    users::model::User,
+    webhooks::{
+        self,
+        model::{
+            LINE_ITEM_CREATED, LINE_ITEM_DATE_CREATED, LINE_ITEM_DATE_DELETED,
+            LINE_ITEM_DATE_UPDATED,
+        },
+    },
 };
+use anyhow::anyhow;
 use diesel::prelude::*;
+use time::{Date, OffsetDateTime, Time};
+use ulid::Ulid;
 
 pub(crate) fn all<S: AsRef<str>>(
     connection: &mut SqliteConnection,
     id: S,
-) -> Result<Vec<LineItemDate>> {
-    let records = line_item_dates::table
-        .filter(line_item_dates::quote_id.eq(&id.as_ref()))
-        .get_results(connection)?;
+) -> Result<Vec<LineItemDateWithSubtotal>> {
+    // language=SQL
+    let sql = r#"
+      select
+        lid.id,
+        lid.quote_id,
+        lid."date",
+        lid.start_time,
+        lid.end_time,
+        lid.timezone,
+        (select
+            coalesce(sum(li.quantity * li.unit_price), 0)
+          from line_items li
+          where li.line_item_date_id = lid.id) as subtotal
+      from line_item_dates lid
+      where lid.quote_id = ?
+      order by lid."date", lid.start_time
+    "#;
+    let records = diesel::dsl::sql_query(sql)
+        .bind::<diesel::sql_types::Text, _>(id.as_ref())
+        .load(connection)?;
     Ok(records)
 }
 
+// Dates of the quotes still in draft, those of the quote given first
+pub(crate) fn options<S: AsRef<str>>(
+    connection: &mut SqliteConnection,
+    quote_id: S,
+) -> Result<Vec<LineItemDateOption>> {
+    // language=SQL
+    let sql = r#"
+      select
+        lid.id,
+        q.name as quote_name,
+        lid."date",
+        lid.start_time,
+        lid.end_time
+      from line_item_dates lid
+        inner join quotes q on lid.quote_id = q.id
+      where q.status = ?
+      order by q.id = ? desc, q.name, q.id, lid."date", lid.start_time
+    "#;
+    let records = diesel::dsl::sql_query(sql)
+        .bind::<diesel::sql_types::Text, _>(STATUS_DRAFT)
+        .bind::<diesel::sql_types::Text, _>(quote_id.as_ref())
+        .load(connection)?;
+    Ok(records)
+}
+
+// The date right before `date` and `start_time` in the quote, after which a block for it is
+// inserted. A day without a time slot comes before the time slots of that day.
+pub(crate) fn previous_id<S: AsRef<str>>(
+    connection: &mut SqliteConnection,
+    quote_id: S,
+    date: Date,
+    start_time: Option<Time>,
+) -> Result<Option<String>> {
+    let query = line_item_dates::table
+        .filter(line_item_dates::quote_id.eq(quote_id.as_ref()))
+        .into_boxed();
+    let query = match start_time {
+        Some(start_time) => query.filter(
+            line_item_dates::date
+                .lt(date)
+                .or(line_item_dates::date.eq(date).and(
+                    line_item_dates::start_time
+                        .is_null()
+                        .or(line_item_dates::start_time.lt(start_time)),
+                )),
+        ),
+        None => query.filter(line_item_dates::date.lt(date)),
+    };
+    let id = query
+        .order_by((
+            line_item_dates::date.desc(),
+            line_item_dates::start_time.desc(),
+        ))
+        .select(line_item_dates::id)
+        .first(connection)
+        .optional()?;
+    Ok(id)
+}
+
+// Days of the quote without a time slot
+pub(crate) fn existing_dates<S: AsRef<str>>(
+    connection: &mut SqliteConnection,
+    quote_id: S,
+) -> Result<Vec<Date>> {
+    let dates = line_item_dates::table
+        .filter(line_item_dates::quote_id.eq(quote_id.as_ref()))
+        .filter(line_item_dates::start_time.is_null())
+        .order_by(line_item_dates::date)
+        .select(line_item_dates::date)
+        .load(connection)?;
+    Ok(dates)
+}
+
+pub(crate) fn read_with_subtotal<S: AsRef<str>>(
+    connection: &mut SqliteConnection,
+    id: S,
+) -> Result<LineItemDateWithSubtotal> {
+    // language=SQL
+    let sql = r#"
+      select
+        lid.id,
+        lid.quote_id,
+        lid."date",
+        lid.start_time,
+        lid.end_time,
+        lid.timezone,
+        (select
+            coalesce(sum(li.quantity * li.unit_price), 0)
+          from line_items li
+          where li.line_item_date_id = lid.id) as subtotal
+      from line_item_dates lid
+      where lid.id = ?
+    "#;
+    let record = diesel::dsl::sql_query(sql)
+        .bind::<diesel::sql_types::Text, _>(id.as_ref())
+        .get_result(connection)?;
+    Ok(record)
+}
+
 pub(crate) fn read<S: AsRef<str>>(
     connection: &mut SqliteConnection,
     id: S,
@@ -28,52 +164,307 @@ pub(crate) fn read<S: AsRef<str>>(
 
 pub(crate) fn insert(
     connection: &mut SqliteConnection,
+    actor: &User,
     form: &NewLineItemDateForm,
 ) -> Result<LineItemDate> {
     let record: LineItemDate = form.into();
 
-    diesel::dsl::insert_into(line_item_dates::table)
-        .values(&record)
-        .execute(connection)?;
+    connection.transaction::<_, crate::error::AppError, _>(|tx| {
+        quotes::query::ensure_editable(tx, &record.quote_id)?;
+
+        diesel::dsl::insert_into(line_item_dates::table)
+            .values(&record)
+            .execute(tx)?;
+
+        let entry = AuditEntry::new(actor, ACTION_CREATE, &record.quote_id, None, Some(&record))?;
+        audit_entries::query::insert(tx, &entry)?;
+        webhooks::query::enqueue(tx, LINE_ITEM_DATE_CREATED, &record.quote_id, &record)
+    })?;
 
     Ok(record)
 }
 
 pub(crate) fn update(
     connection: &mut SqliteConnection,
+    actor: &User,
     form: &EditLineItemDateForm,
-) -> Result<LineItemDate> {
+) -> Result<LineItemDateWithSubtotal> {
     let record: LineItemDate = form.into();
 
-    diesel::dsl::update(line_item_dates::table)
-        .set((
-            line_item_dates::date.eq(&record.date),
-            line_item_dates::updated_at.eq(&record.updated_at),
-        ))
-        .filter(line_item_dates::id.eq(&record.id))
-        .execute(connection)?;
+    connection.transaction::<_, crate::error::AppError, _>(|tx| {
+        let before = read(tx, &record.id)?;
+        quotes::query::ensure_editable(tx, &before.quote_id)?;
+
+        diesel::dsl::update(line_item_dates::table)
+            .set((
+                line_item_dates::date.eq(&record.date),
+                line_item_dates::start_time.eq(&record.start_time),
+                line_item_dates::end_time.eq(&record.end_time),
+                line_item_dates::timezone.eq(&record.timezone),
+                line_item_dates::updated_at.eq(&record.updated_at),
+            ))
+            .filter(line_item_dates::id.eq(&record.id))
+            .execute(tx)?;
+
+        let entry = AuditEntry::new(
+            actor,
+            ACTION_UPDATE,
+            &before.quote_id,
+            Some(&before),
+            Some(&record),
+        )?;
+        audit_entries::query::insert(tx, &entry)?;
+        webhooks::query::enqueue(tx, LINE_ITEM_DATE_UPDATED, &before.quote_id, &record)
+    })?;
 
-    read(connection, &record.id)
+    read_with_subtotal(connection, &record.id)
 }
 
 pub(crate) fn delete<S: AsRef<str>>(
     connection: &mut SqliteConnection,
+    actor: &User,
     id: S,
 ) -> Result<LineItemDate> {
     let record = read(connection, &id)?;
-    _ = connection.transaction::<_, _, _>(|tx| {
+    connection.transaction::<_, crate::error::AppError, _>(|tx| {
+        quotes::query::ensure_editable(tx, &record.quote_id)?;
         line_items::query::delete_all_for_date(tx, &id)?;
 
         _ = diesel::dsl::delete(line_item_dates::table)
             .filter(line_item_dates::id.eq(&id.as_ref()))
             .execute(tx)?;
 
-        Ok::<(), crate::error::AppError>(())
-    });
+        let entry = AuditEntry::new(actor, ACTION_DELETE, &record.quote_id, Some(&record), None)?;
+        audit_entries::query::insert(tx, &entry)?;
+        webhooks::query::enqueue(tx, LINE_ITEM_DATE_DELETED, &record.quote_id, &record)
+    })?;
 
     Ok(record)
 }
 
+// Every date of the quote before and after the move asked for, in chronological order
+pub(crate) fn shifted(
+    connection: &mut SqliteConnection,
+    form: &RescheduleForm,
+) -> Result<Vec<(LineItemDate, LineItemDate)>> {
+    let records: Vec<LineItemDate> = line_item_dates::table
+        .filter(line_item_dates::quote_id.eq(&form.quote_id))
+        .order_by((line_item_dates::date, line_item_dates::start_time))
+        .load(connection)?;
+    let Some(first) = records.first() else {
+        return Ok(Vec::new());
+    };
+
+    let offset = form.offset(first.date);
+    records
+        .into_iter()
+        .map(|record| {
+            let after = record
+                .shifted(offset)
+                .ok_or_else(|| anyhow!("{} cannot be moved by {offset} days", record.date))?;
+            Ok((record, after))
+        })
+        .collect()
+}
+
+pub(crate) fn reschedule(
+    connection: &mut SqliteConnection,
+    actor: &User,
+    form: &RescheduleForm,
+) -> Result<Vec<LineItemDate>> {
+    connection.transaction::<_, crate::error::AppError, _>(|tx| {
+        quotes::query::ensure_editable(tx, &form.quote_id)?;
+
+        // The unique index on the quote and date is checked row by row, so the dates are moved
+        // starting with the one furthest in the direction of the move to never overlap another
+        let mut moves = shifted(tx, form)?;
+        match moves.first() {
+            Some((before, after)) if after.date == before.date => {
+                return Ok(moves.into_iter().map(|(before, _)| before).collect());
+            }
+            Some((before, after)) if after.date > before.date => moves.reverse(),
+            _ => {}
+        }
+
+        let mut records = Vec::with_capacity(moves.len());
+        for (before, after) in moves {
+            diesel::dsl::update(line_item_dates::table)
+                .set((
+                    line_item_dates::date.eq(&after.date),
+                    line_item_dates::updated_at.eq(&after.updated_at),
+                ))
+                .filter(line_item_dates::id.eq(&after.id))
+                .execute(tx)?;
+
+            let entry = AuditEntry::new(
+                actor,
+                ACTION_UPDATE,
+                &before.quote_id,
+                Some(&before),
+                Some(&after),
+            )?;
+            audit_entries::query::insert(tx, &entry)?;
+            webhooks::query::enqueue(tx, LINE_ITEM_DATE_UPDATED, &before.quote_id, &after)?;
+            records.push(after);
+        }
+
+        records.sort_by_key(|record| (record.date, record.start_time));
+        Ok(records)
+    })
+}
+
+// Copies a date and its line items to a quote, into the date of that quote on the same day and time
+// slot if any. Returns the date copied to, and whether it had to be created.
+pub(crate) fn copy(
+    connection: &mut SqliteConnection,
+    actor: &User,
+    form: &CopyLineItemDateForm,
+) -> Result<(LineItemDate, bool)> {
+    let date = parse_date(&form.date);
+
+    connection.transaction::<_, crate::error::AppError, _>(|tx| {
+        quotes::query::ensure_editable(tx, &form.quote_id)?;
+        let source = read(tx, &form.id)?;
+
+        let existing: Option<LineItemDate> = line_item_dates::table
+            .filter(line_item_dates::quote_id.eq(&form.quote_id))
+            .filter(line_item_dates::date.eq(date))
+            .filter(line_item_dates::start_time.is(source.start_time))
+            .first(tx)
+            .optional()?;
+        let created = existing.is_none();
+        let record = match existing {
+            Some(record) => record,
+            None => {
+                let record = LineItemDate {
+                    id: Ulid::new().to_string(),
+                    quote_id: form.quote_id.clone(),
+                    date,
+                    created_at: OffsetDateTime::now_utc(),
+                    updated_at: OffsetDateTime::now_utc(),
+                    start_time: source.start_time,
+                    end_time: source.end_time,
+                    timezone: source.timezone.clone(),
+                };
+                diesel::dsl::insert_into(line_item_dates::table)
+                    .values(&record)
+                    .execute(tx)?;
+
+                let entry =
+                    AuditEntry::new(actor, ACTION_CREATE, &record.quote_id, None, Some(&record))?;
+                audit_entries::query::insert(tx, &entry)?;
+                webhooks::query::enqueue(tx, LINE_ITEM_DATE_CREATED, &record.quote_id, &record)?;
+                record
+            }
+        };
+
+        for line_item in line_items::query::copy_all(tx, &source.id, &record.id)? {
+            let entry = AuditEntry::new(
+                actor,
+                ACTION_CREATE,
+                &record.quote_id,
+                None,
+                Some(&line_item),
+            )?;
+            audit_entries::query::insert(tx, &entry)?;
+            webhooks::query::enqueue(
+                tx,
+                LINE_ITEM_CREATED,
+                &record.quote_id,
+                &LineItemPresenter::from(&line_item),
+            )?;
+        }
+
+        Ok((record, created))
+    })
+}
+
+// Creates the dates of a recurrence rule, copying the line items of the source date into each.
+// Returns the dates created or merged into, the source date itself being always left alone.
+pub(crate) fn repeat(
+    connection: &mut SqliteConnection,
+    actor: &User,
+    form: &RecurrenceForm,
+) -> Result<Vec<LineItemDate>> {
+    connection.transaction::<_, crate::error::AppError, _>(|tx| {
+        quotes::query::ensure_editable(tx, &form.quote_id)?;
+        let source = match form.source_id() {
+            Some(id) => Some(read(tx, id)?).filter(|source| source.quote_id == form.quote_id),
+            None => None,
+        };
+
+        let mut records = Vec::new();
+        for date in form.dates() {
+            let existing: Option<LineItemDate> = line_item_dates::table
+                .filter(line_item_dates::quote_id.eq(&form.quote_id))
+                .filter(line_item_dates::date.eq(date))
+                .filter(line_item_dates::start_time.is_null())
+                .first(tx)
+                .optional()?;
+            let record = match existing {
+                Some(_) if !form.is_merge() => continue,
+                Some(record) if source.as_ref().is_some_and(|source| source.id == record.id) => {
+                    continue;
+                }
+                Some(record) => record,
+                None => {
+                    let record = LineItemDate {
+                        id: Ulid::new().to_string(),
+                        quote_id: form.quote_id.clone(),
+                        date,
+                        created_at: OffsetDateTime::now_utc(),
+                        updated_at: OffsetDateTime::now_utc(),
+                        start_time: None,
+                        end_time: None,
+                        timezone: None,
+                    };
+                    diesel::dsl::insert_into(line_item_dates::table)
+                        .values(&record)
+                        .execute(tx)?;
+
+                    let entry = AuditEntry::new(
+                        actor,
+                        ACTION_CREATE,
+                        &record.quote_id,
+                        None,
+                        Some(&record),
+                    )?;
+                    audit_entries::query::insert(tx, &entry)?;
+                    webhooks::query::enqueue(
+                        tx,
+                        LINE_ITEM_DATE_CREATED,
+                        &record.quote_id,
+                        &record,
+                    )?;
+                    record
+                }
+            };
+
+            if let Some(source) = &source {
+                for line_item in line_items::query::copy_all(tx, &source.id, &record.id)? {
+                    let entry = AuditEntry::new(
+                        actor,
+                        ACTION_CREATE,
+                        &record.quote_id,
+                        None,
+                        Some(&line_item),
+                    )?;
+                    audit_entries::query::insert(tx, &entry)?;
+                    webhooks::query::enqueue(
+                        tx,
+                        LINE_ITEM_CREATED,
+                        &record.quote_id,
+                        &LineItemPresenter::from(&line_item),
+                    )?;
+                }
+            }
+            records.push(record);
+        }
+
+        Ok(records)
+    })
+}
+
 pub(crate) fn delete_all_for_quote<S: AsRef<str>>(tx: &mut SqliteConnection, id: S) -> Result {
     line_items::query::delete_all_for_quote(tx, &id)?;
 
@@ -83,3 +474,200 @@ pub(crate) fn delete_all_for_quote<S: AsRef<str>>(tx: &mut SqliteConnection, id:
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        line_item_dates::model::{
+            COLLISION_MERGE, COLLISION_SKIP, CopyLineItemDateForm, REPEAT_DAILY, RecurrenceForm,
+            RescheduleForm,
+        },
+        line_items,
+        schema::line_item_dates,
+        time::parse_date,
+        users,
+    };
+    use diesel::prelude::*;
+    use diesel::sql_types::{Integer, Text};
+    use time::Date;
+
+    // One of the quotes added by the migrations
+    const QUOTE_ID: &str = "01HE2X4FKPDTVHHB6C2HZD5Z53";
+
+    fn insert_date(connection: &mut SqliteConnection, id: &str, date: &str) {
+        // language=SQL
+        let sql = r#"
+          insert into line_item_dates (id, quote_id, "date", created_at, updated_at)
+          values (?, ?, ?, strftime('%Y-%m-%dT%H:%M:%fZ'), strftime('%Y-%m-%dT%H:%M:%fZ'))
+        "#;
+        diesel::dsl::sql_query(sql)
+            .bind::<Text, _>(id)
+            .bind::<Text, _>(QUOTE_ID)
+            .bind::<Text, _>(date)
+            .execute(connection)
+            .unwrap();
+    }
+
+    fn insert_line_item(
+        connection: &mut SqliteConnection,
+        id: &str,
+        line_item_date_id: &str,
+        position: i32,
+    ) {
+        // language=SQL
+        let sql = r#"
+          insert into line_items
+            (id, line_item_date_id, name, quantity, unit_price, created_at, updated_at, position)
+          values
+            (?, ?, 'Chairs', 1, 2.5, strftime('%Y-%m-%dT%H:%M:%fZ'), strftime('%Y-%m-%dT%H:%M:%fZ'), ?)
+        "#;
+        diesel::dsl::sql_query(sql)
+            .bind::<Text, _>(id)
+            .bind::<Text, _>(line_item_date_id)
+            .bind::<Integer, _>(position)
+            .execute(connection)
+            .unwrap();
+    }
+
+    fn positions(connection: &mut SqliteConnection, line_item_date_id: &str) -> Vec<i32> {
+        line_items::query::all_for_line_item_date(connection, line_item_date_id)
+            .unwrap()
+            .iter()
+            .map(|line_item| line_item.position)
+            .collect()
+    }
+
+    fn dates(connection: &mut SqliteConnection) -> Vec<Date> {
+        line_item_dates::table
+            .select(line_item_dates::date)
+            .filter(line_item_dates::quote_id.eq(QUOTE_ID))
+            .order_by(line_item_dates::date)
+            .load(connection)
+            .unwrap()
+    }
+
+    #[test]
+    fn reschedule() {
+        let mut connection = crate::test_connection();
+        let user = users::query::current(&mut connection, None).unwrap();
+        insert_date(&mut connection, "date_1", "2024-03-01");
+        insert_date(&mut connection, "date_2", "2024-03-02");
+        insert_date(&mut connection, "date_3", "2024-03-04");
+
+        // The first two dates move onto the day of the next one, only free once that one moved
+        let form = RescheduleForm {
+            quote_id: String::from(QUOTE_ID),
+            days: Some(1),
+            start_date: None,
+        };
+        let records = super::reschedule(&mut connection, &user, &form).unwrap();
+        let expected = vec![
+            parse_date("2024-03-02"),
+            parse_date("2024-03-03"),
+            parse_date("2024-03-05"),
+        ];
+        assert_eq!(
+            expected,
+            records
+                .iter()
+                .map(|record| record.date)
+                .collect::<Vec<Date>>()
+        );
+        assert_eq!(expected, dates(&mut connection));
+
+        let form = RescheduleForm {
+            quote_id: String::from(QUOTE_ID),
+            days: None,
+            start_date: Some(String::from("2024-02-29")),
+        };
+        super::reschedule(&mut connection, &user, &form).unwrap();
+        let expected = vec![
+            parse_date("2024-02-29"),
+            parse_date("2024-03-01"),
+            parse_date("2024-03-03"),
+        ];
+        assert_eq!(expected, dates(&mut connection));
+    }
+
+    #[test]
+    fn copy() {
+        let mut connection = crate::test_connection();
+        let user = users::query::current(&mut connection, None).unwrap();
+        insert_date(&mut connection, "source", "2024-03-01");
+        insert_date(&mut connection, "existing", "2024-03-02");
+        insert_line_item(&mut connection, "item_1", "source", 0);
+        insert_line_item(&mut connection, "item_2", "source", 1);
+        insert_line_item(&mut connection, "item_3", "existing", 0);
+
+        // The line items go after those of the date already on that day
+        let form = CopyLineItemDateForm {
+            id: String::from("source"),
+            quote_id: String::from(QUOTE_ID),
+            date: String::from("2024-03-02"),
+        };
+        let (record, created) = super::copy(&mut connection, &user, &form).unwrap();
+        assert!(!created);
+        assert_eq!("existing", record.id);
+        assert_eq!(vec![0, 1, 2], positions(&mut connection, "existing"));
+
+        let form = CopyLineItemDateForm {
+            date: String::from("2024-03-03"),
+            ..form
+        };
+        let (record, created) = super::copy(&mut connection, &user, &form).unwrap();
+        assert!(created);
+        assert_eq!(parse_date("2024-03-03"), record.date);
+        assert_eq!(vec![0, 1], positions(&mut connection, &record.id));
+        assert_eq!(vec![0, 1], positions(&mut connection, "source"));
+    }
+
+    #[test]
+    fn repeat() {
+        let mut connection = crate::test_connection();
+        let user = users::query::current(&mut connection, None).unwrap();
+        insert_date(&mut connection, "source", "2024-03-01");
+        insert_date(&mut connection, "existing", "2024-03-03");
+        insert_line_item(&mut connection, "item_1", "source", 0);
+
+        // The source date and the day already on the quote are both skipped
+        let form = RecurrenceForm {
+            quote_id: String::from(QUOTE_ID),
+            start_date: String::from("2024-03-01"),
+            frequency: String::from(REPEAT_DAILY),
+            weekdays: Vec::new(),
+            until: Some(String::from("2024-03-04")),
+            count: None,
+            source_id: Some(String::from("source")),
+            on_collision: String::from(COLLISION_SKIP),
+        };
+        let records = super::repeat(&mut connection, &user, &form).unwrap();
+        assert_eq!(
+            vec![parse_date("2024-03-02"), parse_date("2024-03-04")],
+            records
+                .iter()
+                .map(|record| record.date)
+                .collect::<Vec<Date>>()
+        );
+        assert_eq!(vec![0], positions(&mut connection, &records[0].id));
+        assert!(positions(&mut connection, "existing").is_empty());
+
+        // Merging copies into the days already there, still leaving the source date alone
+        let form = RecurrenceForm {
+            until: Some(String::from("2024-03-03")),
+            on_collision: String::from(COLLISION_MERGE),
+            ..form
+        };
+        let merged = super::repeat(&mut connection, &user, &form).unwrap();
+        assert_eq!(
+            vec![records[0].id.as_str(), "existing"],
+            merged
+                .iter()
+                .map(|record| record.id.as_str())
+                .collect::<Vec<&str>>()
+        );
+        assert_eq!(vec![0, 1], positions(&mut connection, &records[0].id));
+        assert_eq!(vec![0], positions(&mut connection, "existing"));
+        assert_eq!(vec![0], positions(&mut connection, "source"));
+        assert_eq!(4, dates(&mut connection).len());
+    }
+}
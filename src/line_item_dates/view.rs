@@ -1,8 +1,16 @@
 use crate::{
+    audit_entries::view::ActivityLoader,
     forms::css_for_field,
     layout::Flash,
-    line_item_dates::model::{EditLineItemDateForm, LineItemDatePresenter, NewLineItemDateForm},
-    line_items::{model::LineItemPresenter, view::LineItem},
+    line_item_dates::model::{
+        COLLISION_MERGE, COLLISION_SKIP, EditLineItemDateForm, LineItemDatePresenter,
+        NewLineItemDateForm, REPEAT_DAILY, REPEAT_WEEKDAYS, REPEAT_WEEKLY, RecurrenceForm,
+        WEEKDAYS,
+    },
+    line_items::{
+        model::LineItemPresenter,
+        view::{BulkToolbar, LineItem},
+    },
     quotes::{model::QuotePresenter, view::SwapFooter},
 };
 use rocket::form::{Contextual, Form};
@@ -17,17 +25,26 @@ markup::define! {
                 div[class = "bg-white rounded-md mt-2 p-4 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]"] {
                     // header
                     div[class = "flex flex-wrap items-start bg-light gap-2 mb-3 p-2 rounded-md"] {
+                        // selection and drag handle
+                        div[class = "flex-[0_0_3rem]"] {}
                         // name
                         div[class = "flex-1 font-bold text-[0.875rem] tracking-[1px] uppercase"] { "Article" }
                         // quantity
-                        div[class = "display-[revert] flex-[0_0_7rem] font-bold text-[0.875rem] tracking-[1px] uppercase"] { "Quantity" }
+                        div[class = "display-[revert] flex-[0_0_11rem] font-bold text-[0.875rem] tracking-[1px] uppercase"] { "Quantity" }
                         // price
                         div[class = "display-[revert] flex-[0_0_9rem] font-bold text-[0.875rem] tracking-[1px] uppercase"] { "Price" }
                         // actions
                         div[class = "flex flex-[0_0_10rem] order-[revert] gap-2 font-bold text-[0.875rem] tracking-[1px] uppercase"] {}
                     }
 
-                    div[id = {format!("line_item_date_{}_line_items", line_item_date.id())}] {
+                    @let line_items_dom_id = format!("line_item_date_{}_line_items", line_item_date.id());
+                    div[id = &line_items_dom_id,
+                        class = "sortable",
+                        "hx-post" = "/line_items/reorder",
+                        "hx-trigger" = "end",
+                        "hx-swap" = "none",
+                        "hx-vals" = {format!(r#"{{"line_item_date_id": "{}"}}"#, line_item_date.id())},
+                        "hx-include" = {format!("#{} input[name='ids']", line_items_dom_id)}] {
                         @for line_item in *line_items {
                             @LineItem { line_item }
                         }
@@ -35,10 +52,14 @@ markup::define! {
 
                     @let line_item_new_id = format!("line_item_date_{}_line_item_new", line_item_date.id());
                     div[id = &line_item_new_id] {}
+                    div[id = &line_item_date.transfer_dom_id()] {}
+
+                    @BulkToolbar { line_item_date }
 
                     div[class = "p-4 text-center border-2 border-dashed border-[hsl(0,6%,93%)] rounded-md"] {
                         @let target = format!("#line_item_date_{}_line_item_new", line_item_date.id());
                         a[class = "button button-prime",
+                            href = {format!("/line_items/new/{}", line_item_date.id())},
                             "hx-get" = {format!("/line_items/new/{}", line_item_date.id())},
                             "hx-target" = &target,
                             "hx-trigger" = "click",
@@ -54,9 +75,15 @@ markup::define! {
             div[class= "flex items-center justify-between gap-2"] {
                 h2[class = "text-[1.5rem] font-bold"] {
                     @line_item_date.date_long_form()
+                    @if let Some(time_range) = line_item_date.time_range() {
+                        span[class = "ml-2 font-normal text-muted"] { @time_range }
+                    }
                 }
+                @Subtotal { line_item_date }
                 div[class = "flex gap-2"] {
-                    form["hx-post" = "/line_item_dates/delete",
+                    form[action = "/line_item_dates/delete",
+                         method = "post",
+                         "hx-post" = "/line_item_dates/delete",
                         "hx-target" = {format!("#{}", line_item_date.dom_id())},
                         "hx-swap" = "delete"] {
 
@@ -67,6 +94,13 @@ markup::define! {
                         button[class = "button button-light", "hx-confirm" = "Are you sure?", "type" = "submit"] {"Delete"}
                     }
                     a[class = "button button-light",
+                        href = {format!("/line_item_dates/copy/{}", line_item_date.id())},
+                        "hx-get" = {format!("/line_item_dates/copy/{}", line_item_date.id())},
+                        "hx-target" = {format!("#{}", line_item_date.transfer_dom_id())},
+                        "hx-trigger" = "click",
+                        "hx-swap" = "innerHTML"] { "Copy" }
+                    a[class = "button button-light",
+                        href = {format!("/line_item_dates/edit/{}", line_item_date.id())},
                         "hx-get" = {format!("/line_item_dates/edit/{}", line_item_date.id())},
                         "hx-target" = {format!("#{}", line_item_date.edit_dom_id())},
                         "hx-trigger" = "click"] { "Edit" }
@@ -75,9 +109,53 @@ markup::define! {
         }
     }
 
+    // Jump links to the dates of long quotes, kept beside the content on wide screens
+    DateNav<'a>(line_item_dates: &'a Vec<LineItemDatePresenter>) {
+        nav[id = "line_item_date_nav", class = "hidden 2xl:block fixed top-28 left-4 w-52 max-h-[calc(100vh-10rem)] overflow-y-auto"] {
+            @if line_item_dates.len() > 1 {
+                h2[class = "mb-2 font-bold text-[0.875rem] tracking-[1px] uppercase"] { "Dates" }
+                ol[class = "flex flex-col gap-1"] {
+                    @for line_item_date in *line_item_dates {
+                        li {
+                            a[href = format!("#{}", line_item_date.dom_id())] {
+                                @line_item_date.date_long_form()
+                                @if let Some(time_range) = line_item_date.time_range() {
+                                    br;
+                                    span[class = "text-muted"] { @time_range }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    DateNavLoader<'a>(quote_id: &'a str) {
+        div[id = "line_item_date_nav",
+            "hx-swap-oob" = "true",
+            "hx-get" = {format!("/line_item_dates/nav/{quote_id}")},
+            "hx-trigger" = "load",
+            "hx-swap" = "outerHTML"] {}
+    }
+
+    Subtotal<'a>(line_item_date: &'a LineItemDatePresenter) {
+        div[id = &line_item_date.subtotal_dom_id(), class = "ml-auto mr-2 font-bold"] {
+            @line_item_date.subtotal.format()
+        }
+    }
+
+    SwapSubtotal<'a>(line_item_date: &'a LineItemDatePresenter) {
+        div[id = &line_item_date.subtotal_dom_id(), "hx-swap-oob" = "true", class = "ml-auto mr-2 font-bold"] {
+            @line_item_date.subtotal.format()
+        }
+    }
+
     EditForm<'a>(line_item_date: &'a LineItemDatePresenter) {
         div[id = line_item_date.edit_dom_id()] {
             form[id = {format!("form_{}", line_item_date.edit_dom_id())},
+                action = "/line_item_dates/update",
+                method = "post",
                 "hx-post" = "/line_item_dates/update",
                 "hx-target" = {format!("#{}", line_item_date.dom_id())},
                 "hx-swap" = "outerHTML",
@@ -106,7 +184,13 @@ markup::define! {
                         "type" = "date",
                         value = line_item_date.date_short_form()] {}
                 }
+                @TimeFields {
+                    start_time: &line_item_date.start_time_form(),
+                    end_time: &line_item_date.end_time_form(),
+                    timezone: line_item_date.timezone.as_deref().unwrap_or(""),
+                }
                 a[class = "button button-light",
+                    href = {format!("/line_item_dates/{}", line_item_date.id())},
                     "hx-get" = {format!("/line_item_dates/{}", line_item_date.id())},
                     "hx-target" = {format!("#{}", line_item_date.edit_dom_id())},
                     "hx-trigger" = "click",
@@ -125,10 +209,15 @@ markup::define! {
         @let id = context.field_value("id").unwrap_or("");
         @let quote_id = context.field_value("quote_id").unwrap_or("");
         @let date = context.field_value("date").unwrap_or("");
+        @let start_time = context.field_value("start_time").unwrap_or("");
+        @let end_time = context.field_value("end_time").unwrap_or("");
+        @let timezone = context.field_value("timezone").unwrap_or("");
         @let dom_id = format!("line_item_date_{}", id);
         @let edit_dom_id = format!("edit_line_item_date_{}", id);
         div[id = &dom_id] {
             form[id = {format!("form_{}", edit_dom_id)},
+                action = "/line_item_dates/update",
+                method = "post",
                 "hx-post" = "/line_item_dates/update",
                 "hx-target" = {format!("#{}", dom_id)},
                 "hx-swap" = "outerHTML",
@@ -162,7 +251,9 @@ markup::define! {
                         "type" = "date",
                         value = &date] {}
                 }
+                @TimeFields { start_time, end_time, timezone }
                 a[class = "button button-light",
+                    href = {format!("/line_item_dates/{}", id)},
                     "hx-get" = {format!("/line_item_dates/{}", id)},
                     "hx-target" = {format!("#{}", edit_dom_id)},
                     "hx-trigger" = "click",
@@ -176,9 +267,36 @@ markup::define! {
         }
     }
 
+    // Optional time slot of a date, left empty for the whole day
+    TimeFields<'a>(start_time: &'a str, end_time: &'a str, timezone: &'a str) {
+        div[class = "flex items-center gap-2"] {
+            label[class = "visually-hidden", "for" = "line_item_date_start_time"] { "Start time" }
+            input[id = "line_item_date_start_time",
+                name = "start_time",
+                class = "form-input [flex:0_0_8rem]",
+                "type" = "time",
+                value = start_time] {}
+            label["for" = "line_item_date_end_time"] { "to" }
+            input[id = "line_item_date_end_time",
+                name = "end_time",
+                class = "form-input [flex:0_0_8rem]",
+                "type" = "time",
+                value = end_time] {}
+            label[class = "visually-hidden", "for" = "line_item_date_timezone"] { "Timezone" }
+            input[id = "line_item_date_timezone",
+                name = "timezone",
+                class = "form-input [flex:0_0_11rem]",
+                placeholder = "Timezone",
+                "type" = "text",
+                value = timezone] {}
+        }
+    }
+
     NewForm<'a>(quote_id: &'a str) {
         div[id = "line_item_date_new"] {
             form[id = "form_new",
+                action = "/line_item_dates/create",
+                method = "post",
                 "hx-post" = "/line_item_dates/create",
                 "hx-target" = "#line_item_dates",
                 "hx-swap" = "afterbegin",
@@ -200,6 +318,7 @@ markup::define! {
                         required,
                         "type" = "date"] {}
                 }
+                @TimeFields { start_time: "", end_time: "", timezone: "" }
                 a[class = "button button-light",
                     "_" = "on click remove #form_new"] { "Cancel" }
                 input[name = "commit",
@@ -214,8 +333,13 @@ markup::define! {
     NewFormWithErrors<'a, 'r>(form: &'a Form<Contextual<'r, NewLineItemDateForm>>) {
         @let quote_id = form.context.field_value("quote_id").unwrap_or("");
         @let date = form.context.field_value("date").unwrap_or("");
+        @let start_time = form.context.field_value("start_time").unwrap_or("");
+        @let end_time = form.context.field_value("end_time").unwrap_or("");
+        @let timezone = form.context.field_value("timezone").unwrap_or("");
         div[id = "line_item_date_new"] {
             form[id = "form_new",
+                action = "/line_item_dates/create",
+                method = "post",
                 "hx-post" = "/line_item_dates/create",
                 "hx-target" = "#line_item_dates",
                 "hx-swap" = "afterbegin",
@@ -245,6 +369,7 @@ markup::define! {
                         "type" = "date",
                         value = &date] {}
                 }
+                @TimeFields { start_time, end_time, timezone }
                 a[class = "button button-light",
                     "_" = "on click remove #form_new"] { "Cancel" }
                 input[name = "commit",
@@ -256,23 +381,302 @@ markup::define! {
         }
     }
 
+    Reschedule<'a>(quote_id: &'a str, errors: &'a Vec<String>) {
+        div[id = "line_item_date_reschedule"] {
+            form[id = "form_reschedule",
+                action = "/line_item_dates/reschedule/preview",
+                method = "post",
+                "hx-post" = "/line_item_dates/reschedule/preview",
+                "hx-target" = "#line_item_date_reschedule",
+                "hx-swap" = "outerHTML",
+                class = "flex flex-wrap justify-between items-center gap-2 mt-8 mb-1.5",
+                autocomplete = "off",
+                novalidate,
+                "accept-charset" = "UTF-8"] {
+
+                @if !errors.is_empty() {
+                    div[class = "w-full text-primary bg-primary-bg p-2 rounded-md"] {
+                        @for message in *errors {
+                            p { @message }
+                        }
+                    }
+                }
+
+                input[name = "quote_id", "type" = "hidden", value = quote_id] {}
+                div[class = "flex flex-wrap items-center gap-2 [flex:1]"] {
+                    label["for" = "reschedule_days"] { "Move by" }
+                    input[id = "reschedule_days",
+                        name = "days",
+                        class = "form-input [flex:0_0_7rem]",
+                        autofocus = "autofocus",
+                        "type" = "number",
+                        step = "1"] {}
+                    label["for" = "reschedule_start_date"] { "days, or start on" }
+                    input[id = "reschedule_start_date",
+                        name = "start_date",
+                        class = "form-input [flex:0_0_12rem]",
+                        "type" = "date"] {}
+                }
+                a[class = "button button-light",
+                    "_" = "on click remove #form_reschedule"] { "Cancel" }
+                input[name = "commit",
+                    "type" = "submit",
+                    value = "Preview",
+                    class = "button button-secondary"] {}
+            }
+        }
+    }
+
+    ReschedulePreview<'a>(quote_id: &'a str, days: i64, moves: &'a Vec<(String, String)>) {
+        div[id = "line_item_date_reschedule", class = "mt-8 mb-1.5 bg-white rounded-md p-4 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]"] {
+            h2[class = "text-[1.5rem] font-bold mb-2"] {
+                @if *days > 0 {
+                    {format!("Move every date {days} days later")}
+                } else {
+                    {format!("Move every date {} days earlier", -days)}
+                }
+            }
+            ol[class = "flex flex-col gap-1 mb-4"] {
+                @for (before, after) in *moves {
+                    li { @before " → " strong { @after } }
+                }
+            }
+            form[action = "/line_item_dates/reschedule",
+                method = "post",
+                "hx-post" = "/line_item_dates/reschedule",
+                class = "flex justify-end gap-2",
+                "accept-charset" = "UTF-8"] {
+                input[name = "quote_id", "type" = "hidden", value = quote_id] {}
+                input[name = "days", "type" = "hidden", value = days] {}
+                a[class = "button button-light",
+                    href = {format!("/line_item_dates/reschedule/{quote_id}")},
+                    "hx-get" = {format!("/line_item_dates/reschedule/{quote_id}")},
+                    "hx-target" = "#line_item_date_reschedule",
+                    "hx-swap" = "outerHTML"] { "Back" }
+                input[name = "commit",
+                    "type" = "submit",
+                    value = "Reschedule",
+                    class = "button button-secondary",
+                    "_" = "on click add { pointer-events: none }"] {}
+            }
+        }
+    }
+
+    Recurrence<'a>(quote_id: &'a str,
+                   line_item_dates: &'a Vec<LineItemDatePresenter>,
+                   errors: &'a Vec<String>) {
+        div[id = "line_item_date_recurrence"] {
+            form[id = "form_recurrence",
+                action = "/line_item_dates/repeat/preview",
+                method = "post",
+                "hx-post" = "/line_item_dates/repeat/preview",
+                "hx-target" = "#line_item_date_recurrence",
+                "hx-swap" = "outerHTML",
+                class = "flex flex-col gap-3 mt-8 mb-1.5 bg-white rounded-md p-4 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]",
+                autocomplete = "off",
+                novalidate,
+                "accept-charset" = "UTF-8"] {
+
+                @if !errors.is_empty() {
+                    div[class = "w-full text-primary bg-primary-bg p-2 rounded-md"] {
+                        @for message in *errors {
+                            p { @message }
+                        }
+                    }
+                }
+
+                input[name = "quote_id", "type" = "hidden", value = quote_id] {}
+                div[class = "flex flex-wrap items-center gap-2"] {
+                    label["for" = "recurrence_start_date"] { "Starting on" }
+                    input[id = "recurrence_start_date",
+                        name = "start_date",
+                        class = "form-input [flex:0_0_12rem]",
+                        autofocus = "autofocus",
+                        "type" = "date"] {}
+                    label[class = "visually-hidden", "for" = "recurrence_frequency"] { "Repeat" }
+                    select[id = "recurrence_frequency", name = "frequency", class = "form-input [flex:0_0_14rem]"] {
+                        option[value = REPEAT_DAILY] { "every day" }
+                        option[value = REPEAT_WEEKDAYS] { "every weekday" }
+                        option[value = REPEAT_WEEKLY] { "every week on" }
+                    }
+                    @for weekday in WEEKDAYS {
+                        @let id = format!("recurrence_{}", weekday.to_string().to_lowercase());
+                        label[class = "flex items-center gap-1", "for" = &id] {
+                            input[id = &id, name = "weekdays", "type" = "checkbox", value = weekday.to_string()] {}
+                            {&weekday.to_string()[..3]}
+                        }
+                    }
+                }
+                div[class = "flex flex-wrap items-center gap-2"] {
+                    label["for" = "recurrence_until"] { "until" }
+                    input[id = "recurrence_until",
+                        name = "until",
+                        class = "form-input [flex:0_0_12rem]",
+                        "type" = "date"] {}
+                    label["for" = "recurrence_count"] { "or for" }
+                    input[id = "recurrence_count",
+                        name = "count",
+                        class = "form-input [flex:0_0_7rem]",
+                        "type" = "number",
+                        min = "1",
+                        step = "1"] {}
+                    span { "dates" }
+                }
+                div[class = "flex flex-wrap items-center gap-2"] {
+                    label["for" = "recurrence_source_id"] { "Copy the line items of" }
+                    select[id = "recurrence_source_id", name = "source_id", class = "form-input [flex:0_1_16rem]"] {
+                        option[value = ""] { "no date" }
+                        @for line_item_date in *line_item_dates {
+                            option[value = line_item_date.id()] {
+                                @line_item_date.date_long_form()
+                                @if let Some(time_range) = line_item_date.time_range() { " " @time_range }
+                            }
+                        }
+                    }
+                    label["for" = "recurrence_on_collision"] { "into each date, and" }
+                    select[id = "recurrence_on_collision", name = "on_collision", class = "form-input [flex:0_1_20rem]"] {
+                        option[value = COLLISION_SKIP] { "skip dates already on the quote" }
+                        option[value = COLLISION_MERGE] { "add to dates already on the quote" }
+                    }
+                }
+                div[class = "flex justify-end gap-2"] {
+                    a[class = "button button-light",
+                        "_" = "on click remove #form_recurrence"] { "Cancel" }
+                    input[name = "commit",
+                        "type" = "submit",
+                        value = "Preview",
+                        class = "button button-secondary"] {}
+                }
+            }
+        }
+    }
+
+    // The dates a rule gives, those already on the quote marked as skipped or merged into
+    RecurrencePreview<'a>(form: &'a RecurrenceForm, dates: &'a Vec<(String, bool)>) {
+        div[id = "line_item_date_recurrence", class = "mt-8 mb-1.5 bg-white rounded-md p-4 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]"] {
+            h2[class = "text-[1.5rem] font-bold mb-2"] {
+                @if dates.len() == 1 { "1 date" } else { {format!("{} dates", dates.len())} }
+            }
+            ol[class = "flex flex-col gap-1 mb-4"] {
+                @for (date, exists) in *dates {
+                    @if !exists {
+                        li { strong { @date } }
+                    } else if form.is_merge() {
+                        li { @date " · already on the quote, items added" }
+                    } else {
+                        li[class = "text-muted line-through"] { @date " · already on the quote, skipped" }
+                    }
+                }
+            }
+            form[action = "/line_item_dates/repeat",
+                method = "post",
+                "hx-post" = "/line_item_dates/repeat",
+                class = "flex justify-end gap-2",
+                "accept-charset" = "UTF-8"] {
+                input[name = "quote_id", "type" = "hidden", value = &form.quote_id] {}
+                input[name = "start_date", "type" = "hidden", value = &form.start_date] {}
+                input[name = "frequency", "type" = "hidden", value = &form.frequency] {}
+                @for weekday in &form.weekdays {
+                    input[name = "weekdays", "type" = "hidden", value = weekday] {}
+                }
+                input[name = "until", "type" = "hidden", value = &form.until] {}
+                input[name = "count", "type" = "hidden", value = &form.count] {}
+                input[name = "source_id", "type" = "hidden", value = &form.source_id] {}
+                input[name = "on_collision", "type" = "hidden", value = &form.on_collision] {}
+                a[class = "button button-light",
+                    href = {format!("/line_item_dates/repeat/{}", form.quote_id)},
+                    "hx-get" = {format!("/line_item_dates/repeat/{}", form.quote_id)},
+                    "hx-target" = "#line_item_date_recurrence",
+                    "hx-swap" = "outerHTML"] { "Back" }
+                input[name = "commit",
+                    "type" = "submit",
+                    value = "Create dates",
+                    class = "button button-secondary",
+                    "_" = "on click add { pointer-events: none }"] {}
+            }
+        }
+    }
+
+    CopyForm<'a>(line_item_date: &'a LineItemDatePresenter, quotes: &'a Vec<QuotePresenter>) {
+        @let form_id = format!("form_copy_{}", line_item_date.id());
+        form[id = &form_id,
+            action = "/line_item_dates/copy",
+            method = "post",
+            "hx-post" = "/line_item_dates/copy",
+            "hx-swap" = "none",
+            class = "flex flex-wrap items-center gap-2 mb-3 p-2 rounded-md bg-light",
+            autocomplete = "off",
+            novalidate,
+            "accept-charset" = "UTF-8"] {
+
+            input[name = "id", "type" = "hidden", value = &line_item_date.id()] {}
+            span { "Copy the date and its items to" }
+            label[class = "visually-hidden", "for" = {format!("copy_quote_{}", line_item_date.id())}] { "Quote" }
+            select[id = {format!("copy_quote_{}", line_item_date.id())},
+                name = "quote_id",
+                class = "form-input [flex:1]"] {
+                @for quote in *quotes {
+                    option[value = quote.id(), selected = quote.id() == line_item_date.quote_id] { @quote.name }
+                }
+            }
+            label[class = "visually-hidden", "for" = {format!("copy_date_{}", line_item_date.id())}] { "Date" }
+            input[id = {format!("copy_date_{}", line_item_date.id())},
+                name = "date",
+                class = "form-input [flex:0_0_12rem]",
+                required,
+                "type" = "date",
+                value = line_item_date.date_short_form()] {}
+            a[class = "button button-light",
+                "_" = {format!("on click remove #{form_id}")}] { "Cancel" }
+            input[name = "commit",
+                "type" = "submit",
+                value = "Copy date",
+                class = "button button-secondary",
+                "_" = "on click add { pointer-events: none }"] {}
+        }
+    }
+
+    // A date copied to the quote on the page, as a new block or in place of the date it was merged into
+    Copy<'a>(source: &'a LineItemDatePresenter,
+             line_item_date: &'a LineItemDatePresenter,
+             line_items: &'a Vec<LineItemPresenter>,
+             quote: &'a QuotePresenter,
+             message: &'a str) {
+        @LineItemDate{ line_item_date, line_items }
+        @CloseTransfer { line_item_date: source, message }
+        @DateNavLoader { quote_id: &line_item_date.quote_id }
+        @SwapFooter { quote }
+    }
+
+    CloseTransfer<'a>(line_item_date: &'a LineItemDatePresenter, message: &'a str) {
+        div[id = &line_item_date.transfer_dom_id(), "hx-swap-oob" = "innerHTML"] {}
+        @Flash { message }
+    }
+
     Create<'a>(line_item_date: &'a LineItemDatePresenter,
                line_items: &'a Vec<LineItemPresenter>,
                message: &'a str) {
         @LineItemDate{ line_item_date, line_items }
         div[id = "line_item_date_new", "hx-swap-oob"="innerHTML"]{}
+        @DateNavLoader { quote_id: &line_item_date.quote_id }
+        @ActivityLoader { quote_id: &line_item_date.quote_id, oob: true }
         @Flash{ message }
     }
 
     Update<'a>(line_item_date: &'a LineItemDatePresenter,
                line_items: &'a Vec<LineItemPresenter>,
                message: &'a str) {
+        // The block is inserted again at its chronological position, in place of the current one
+        div[id = &line_item_date.dom_id(), "hx-swap-oob" = "delete"] {}
         @LineItemDate{ line_item_date, line_items }
+        @DateNavLoader { quote_id: &line_item_date.quote_id }
+        @ActivityLoader { quote_id: &line_item_date.quote_id, oob: true }
         @Flash{ message }
     }
 
     Destroy<'a>(quote: &'a QuotePresenter, message: &'a str) {
         @Flash{ message }
+        @DateNavLoader { quote_id: &quote.id() }
         @SwapFooter{ quote }
     }
 }
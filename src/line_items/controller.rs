@@ -1,31 +1,54 @@
 use crate::{
-    Db, Result, line_item_dates,
+    Db, Result,
+    layout::Flash,
+    line_item_dates::{self, model::LineItemDatePresenter},
     line_items::{
         self,
-        model::{DeleteForm, EditLineItemForm, LineItemPresenter, NewLineItemForm},
+        model::{
+            BulkLineItemsForm, DeleteForm, EditLineItemForm, LineItemPresenter, NewLineItemForm,
+            ReorderLineItemsForm, TransferLineItemsForm,
+        },
         view::*,
     },
     quotes,
-    rocket_ext::HtmxResponder,
+    rocket_ext::{AcceptJson, HtmxRequest, HtmxResponder, quote_changed},
+    users::model::CurrentUser,
 };
+use itertools::Itertools;
 use rocket::{
     fairing::AdHoc,
-    form::{Contextual, Form},
-    http::Header,
-    response::content::RawHtml,
+    form::{Contextual, Error, Form},
+    http::{Header, Status},
+    response::{Redirect, content::RawHtml},
 };
 
 pub(crate) fn stage() -> AdHoc {
     AdHoc::on_ignite("LineItem Stage", |rocket| async {
         rocket.mount(
             "/line_items",
-            routes![line_item, new, create, edit, update, delete],
+            routes![
+                line_item,
+                new,
+                create,
+                edit,
+                update,
+                reorder,
+                delete,
+                transfer_form,
+                transfer,
+                bulk
+            ],
         )
     })
 }
 
 #[get("/<id>")]
-async fn line_item(db: Db, id: String) -> Result<RawHtml<String>> {
+async fn line_item(
+    db: Db,
+    accept: AcceptJson,
+    htmx: HtmxRequest,
+    id: String,
+) -> Result<HtmxResponder> {
     let line_item = db
         .run(move |conn| {
             let line_item = line_items::query::read(conn, id)?;
@@ -33,14 +56,24 @@ async fn line_item(db: Db, id: String) -> Result<RawHtml<String>> {
         })
         .await?;
 
+    let line_item: LineItemPresenter = line_item.into();
+    if accept.0 {
+        return HtmxResponder::json(&line_item);
+    }
+
     let template = LineItem {
-        line_item: &line_item.into(),
+        line_item: &line_item,
     };
-    Ok(RawHtml(template.to_string()))
+    Ok(HtmxResponder::Ok(htmx.render("Item", template)))
 }
 
 #[get("/new/<line_item_date_id>")]
-async fn new(db: Db, line_item_date_id: String) -> Result<RawHtml<String>> {
+async fn new(
+    db: Db,
+    user: CurrentUser,
+    htmx: HtmxRequest,
+    line_item_date_id: String,
+) -> Result<RawHtml<String>> {
     let lid_id = line_item_date_id.clone();
     let quote = db
         .run(move |conn| {
@@ -49,46 +82,79 @@ async fn new(db: Db, line_item_date_id: String) -> Result<RawHtml<String>> {
         })
         .await?;
 
-    Ok(RawHtml(
-        NewForm {
-            line_item: &LineItemPresenter::from_line_item_date(line_item_date_id),
-            quote: &quote.into(),
-        }
-        .to_string(),
-    ))
+    let template = NewForm {
+        line_item: &LineItemPresenter::from_line_item_date(line_item_date_id),
+        quote: &quote.into(),
+        can_view_margins: user.0.can_view_margins(),
+    };
+    Ok(RawHtml(htmx.render("New item", template)))
 }
 
 #[post("/create", data = "<form>")]
-async fn create(db: Db, form: Form<Contextual<'_, NewLineItemForm>>) -> Result<HtmxResponder> {
+async fn create(
+    db: Db,
+    user: CurrentUser,
+    accept: AcceptJson,
+    htmx: HtmxRequest,
+    form: Form<Contextual<'_, NewLineItemForm>>,
+) -> Result<HtmxResponder> {
+    let can_view_margins = user.0.can_view_margins();
     match form.value {
         Some(ref li_form) => {
             let quote_id = li_form.quote_id.clone();
             let li_form = li_form.clone();
             let line_item = db
                 .run(move |conn| {
-                    let line_item = line_items::query::insert(conn, &li_form)?;
+                    let line_item = line_items::query::insert(conn, &user.0, &li_form)?;
                     Result::Ok(line_item)
                 })
                 .await?;
 
-            let quote = db
+            let line_item: LineItemPresenter = line_item.into();
+            if accept.0 {
+                return HtmxResponder::json(&line_item);
+            }
+
+            if !htmx.0 {
+                let uri = format!("/quotes/show/{}", quote_id);
+                return Ok(HtmxResponder::Redirect(Redirect::to(uri)));
+            }
+
+            let lid_id = line_item.line_item_date_id.clone();
+            let (line_item_date, quote) = db
                 .run(move |conn| {
+                    let line_item_date = line_item_dates::query::read_with_subtotal(conn, &lid_id)?;
                     let quote = quotes::query::read(conn, &quote_id)?;
-                    Result::Ok(quote)
+                    Result::Ok((line_item_date, quote))
                 })
                 .await?;
 
             let content = Create {
-                line_item: &line_item.into(),
+                line_item: &line_item,
+                line_item_date: &line_item_date.into(),
                 quote: &quote.into(),
                 message: "Item was successfully created.",
             }
             .to_string();
 
-            Ok(HtmxResponder::Ok(content))
+            Ok(HtmxResponder::Changed {
+                content,
+                trigger: quote_changed(),
+            })
         }
         None => {
-            let template = NewFormWithErrors { form: &form };
+            if accept.0 {
+                return Ok(HtmxResponder::invalid(&form.context));
+            }
+
+            let template = NewFormWithErrors {
+                form: &form,
+                can_view_margins,
+            };
+            if !htmx.0 {
+                return Ok(HtmxResponder::Ok(htmx.render("New item", template)));
+            }
+
             let content = template.to_string();
             let line_item_date_id = form.context.field_value("line_item_date_id").unwrap_or("");
             let retarget = format!("#line_item_date_{}_line_item_new", line_item_date_id);
@@ -102,7 +168,13 @@ async fn create(db: Db, form: Form<Contextual<'_, NewLineItemForm>>) -> Result<H
 }
 
 #[get("/edit/<id>")]
-async fn edit(db: Db, id: String) -> Result<RawHtml<String>> {
+async fn edit(
+    db: Db,
+    user: CurrentUser,
+    accept: AcceptJson,
+    htmx: HtmxRequest,
+    id: String,
+) -> Result<HtmxResponder> {
     let line_item = db
         .run(move |conn| {
             let line_item = line_items::query::read(conn, id)?;
@@ -110,6 +182,11 @@ async fn edit(db: Db, id: String) -> Result<RawHtml<String>> {
         })
         .await?;
 
+    let line_item: LineItemPresenter = line_item.into();
+    if accept.0 {
+        return HtmxResponder::json(&line_item);
+    }
+
     let lid_id = line_item.line_item_date_id.clone();
     let quote = db
         .run(move |conn| {
@@ -119,67 +196,438 @@ async fn edit(db: Db, id: String) -> Result<RawHtml<String>> {
         })
         .await?;
 
-    Ok(RawHtml(
-        EditForm {
-            line_item: &line_item.into(),
-            quote: &quote.into(),
-        }
-        .to_string(),
-    ))
+    let template = EditForm {
+        line_item: &line_item,
+        quote: &quote.into(),
+        can_view_margins: user.0.can_view_margins(),
+    };
+    Ok(HtmxResponder::Ok(htmx.render("Edit item", template)))
 }
 
 #[post("/update", data = "<form>")]
-async fn update(db: Db, form: Form<Contextual<'_, EditLineItemForm>>) -> Result<RawHtml<String>> {
+async fn update(
+    db: Db,
+    user: CurrentUser,
+    accept: AcceptJson,
+    htmx: HtmxRequest,
+    form: Form<Contextual<'_, EditLineItemForm>>,
+) -> Result<HtmxResponder> {
+    let can_view_margins = user.0.can_view_margins();
     match form.value {
         Some(ref li_form) => {
             let quote_id = li_form.quote_id.clone();
             let li_form = li_form.clone();
             let line_item = db
                 .run(move |conn| {
-                    let line_item = line_items::query::update(conn, &li_form)?;
+                    let line_item = line_items::query::update(conn, &user.0, &li_form)?;
                     Result::Ok(line_item)
                 })
                 .await?;
 
-            let quote = db
+            let line_item: LineItemPresenter = line_item.into();
+            if accept.0 {
+                return HtmxResponder::json(&line_item);
+            }
+
+            if !htmx.0 {
+                let uri = format!("/quotes/show/{}", quote_id);
+                return Ok(HtmxResponder::Redirect(Redirect::to(uri)));
+            }
+
+            let lid_id = line_item.line_item_date_id.clone();
+            let (line_item_date, quote) = db
                 .run(move |conn| {
+                    let line_item_date = line_item_dates::query::read_with_subtotal(conn, &lid_id)?;
                     let quote = quotes::query::read(conn, &quote_id)?;
-                    Result::Ok(quote)
+                    Result::Ok((line_item_date, quote))
                 })
                 .await?;
 
             let content = Update {
-                line_item: &line_item.into(),
+                line_item: &line_item,
+                line_item_date: &line_item_date.into(),
                 quote: &quote.into(),
                 message: "Item was successfully updated.",
             }
             .to_string();
 
-            Ok(RawHtml(content))
+            Ok(HtmxResponder::Changed {
+                content,
+                trigger: quote_changed(),
+            })
         }
         None => {
-            let template = EditFormWithErrors { form: &form };
-            let content = template.to_string();
-            Ok(RawHtml(content))
+            if accept.0 {
+                return Ok(HtmxResponder::invalid(&form.context));
+            }
+
+            let template = EditFormWithErrors {
+                form: &form,
+                can_view_margins,
+            };
+            Ok(HtmxResponder::Ok(htmx.render("Edit item", template)))
         }
     }
 }
 
+#[post("/reorder", data = "<form>")]
+async fn reorder(db: Db, user: CurrentUser, form: Form<ReorderLineItemsForm>) -> Result<Status> {
+    let reordered = db
+        .run(move |conn| line_items::query::reorder(conn, &user.0, &form))
+        .await?;
+
+    if !reordered {
+        return Ok(Status::Conflict);
+    }
+    Ok(Status::NoContent)
+}
+
 #[post("/delete", data = "<form>")]
-async fn delete(db: Db, form: Form<DeleteForm>) -> Result<RawHtml<String>> {
-    let quote = db
+async fn delete(
+    db: Db,
+    user: CurrentUser,
+    accept: AcceptJson,
+    htmx: HtmxRequest,
+    form: Form<DeleteForm>,
+) -> Result<HtmxResponder> {
+    let (line_item_date, quote) = db
         .run(move |conn| {
-            let line_item = line_items::query::delete(conn, &form.id)?;
+            let line_item = line_items::query::delete(conn, &user.0, &form.id)?;
+            let line_item_date =
+                line_item_dates::query::read_with_subtotal(conn, &line_item.line_item_date_id)?;
             let quote = quotes::query::from_line_item_date_id(conn, &line_item.line_item_date_id)?;
-            Result::Ok(quote)
+            Result::Ok((line_item_date, quote))
+        })
+        .await?;
+
+    if accept.0 {
+        return Ok(HtmxResponder::Empty(Status::NoContent));
+    }
+
+    if !htmx.0 {
+        let uri = format!("/quotes/show/{}", quote.id);
+        return Ok(HtmxResponder::Redirect(Redirect::to(uri)));
+    }
+
+    let content = Destroy {
+        line_item_date: &line_item_date.into(),
+        quote: &quote.into(),
+        message: "Item was successfully destroyed.",
+    }
+    .to_string();
+    Ok(HtmxResponder::Changed {
+        content,
+        trigger: quote_changed(),
+    })
+}
+
+#[get("/transfer/<line_item_date_id>?<selected>")]
+async fn transfer_form(
+    db: Db,
+    htmx: HtmxRequest,
+    line_item_date_id: String,
+    selected: Vec<String>,
+) -> Result<RawHtml<String>> {
+    let (line_item_date, options) = db
+        .run(move |conn| {
+            let line_item_date = line_item_dates::query::read(conn, &line_item_date_id)?;
+            let options = line_item_dates::query::options(conn, &line_item_date.quote_id)?;
+            Result::Ok((line_item_date, options))
+        })
+        .await?;
+
+    let template = TransferForm {
+        line_item_date: &line_item_date.into(),
+        ids: &selected,
+        options: &options,
+    };
+    Ok(RawHtml(htmx.render("Copy or move items", template)))
+}
+
+#[post("/transfer", data = "<form>")]
+async fn transfer(
+    db: Db,
+    user: CurrentUser,
+    accept: AcceptJson,
+    htmx: HtmxRequest,
+    form: Form<Contextual<'_, TransferLineItemsForm>>,
+) -> Result<HtmxResponder> {
+    let Some(transfer_form) = form.value.clone() else {
+        if accept.0 {
+            return Ok(HtmxResponder::invalid(&form.context));
+        }
+        let message = form
+            .context
+            .errors()
+            .map(|error| error.to_string())
+            .collect::<Vec<String>>()
+            .join(" ");
+        return Ok(HtmxResponder::Ok(Flash { message: &message }.to_string()));
+    };
+
+    let is_move = transfer_form.is_move();
+    let target_id = transfer_form.line_item_date_id.clone();
+    let transfers = db
+        .run(move |conn| line_items::query::transfer(conn, &user.0, &transfer_form))
+        .await?;
+
+    if accept.0 {
+        let line_items = transfers
+            .into_iter()
+            .map(|(_, line_item)| line_item.into())
+            .collect::<Vec<LineItemPresenter>>();
+        return HtmxResponder::json(&line_items);
+    }
+
+    if transfers.is_empty() {
+        let content = Flash {
+            message: "Items are already on that date.",
+        }
+        .to_string();
+        return Ok(HtmxResponder::Ok(content));
+    }
+
+    // The page is the one of the quote the items came from
+    let source_ids = transfers
+        .iter()
+        .map(|(source_id, _)| source_id.clone())
+        .unique()
+        .collect::<Vec<String>>();
+    let (quote, sources, target) = db
+        .run(move |conn| {
+            let quote = quotes::query::from_line_item_date_id(conn, &source_ids[0])?;
+            let sources = source_ids
+                .iter()
+                .map(|id| line_item_dates::query::read_with_subtotal(conn, id))
+                .collect::<Result<Vec<_>>>()?;
+            let target = line_item_dates::query::read_with_subtotal(conn, &target_id)?;
+            Result::Ok((quote, sources, target))
+        })
+        .await?;
+
+    if !htmx.0 {
+        let uri = format!("/quotes/show/{}", quote.id);
+        return Ok(HtmxResponder::Redirect(Redirect::to(uri)));
+    }
+
+    let target: LineItemDatePresenter = target.into();
+    let message = match (is_move, target.quote_id == quote.id) {
+        (true, true) => "Items were successfully moved.",
+        (true, false) => "Items were successfully moved to the other quote.",
+        (false, true) => "Items were successfully copied.",
+        (false, false) => "Items were successfully copied to the other quote.",
+    };
+    let moved = transfers
+        .iter()
+        .filter(|_| is_move)
+        .map(|(_, line_item)| format!("line_item_{}", line_item.id))
+        .collect::<Vec<String>>();
+    let line_items = transfers
+        .into_iter()
+        .map(|(_, line_item)| line_item.into())
+        .collect::<Vec<LineItemPresenter>>();
+    let sources = sources
+        .into_iter()
+        .map(|record| record.into())
+        .collect::<Vec<LineItemDatePresenter>>();
+    let target = (target.quote_id == quote.id).then_some(target);
+
+    let content = Transfer {
+        moved: &moved,
+        line_items: &line_items,
+        target: &target,
+        sources: &sources,
+        quote: &quote.into(),
+        message,
+    }
+    .to_string();
+    Ok(HtmxResponder::Changed {
+        content,
+        trigger: quote_changed(),
+    })
+}
+
+#[post("/bulk", data = "<form>")]
+async fn bulk(
+    db: Db,
+    user: CurrentUser,
+    accept: AcceptJson,
+    htmx: HtmxRequest,
+    mut form: Form<Contextual<'_, BulkLineItemsForm>>,
+) -> Result<HtmxResponder> {
+    // JSON clients name the date to move to, the page asks for it first
+    if accept.0
+        && form
+            .value
+            .as_ref()
+            .is_some_and(|value| value.is_move() && value.target().is_none())
+    {
+        form.context
+            .push_error(Error::validation("Please choose a date").with_name("target_id"));
+        form.value = None;
+    }
+
+    let Some(bulk_form) = form.value.clone() else {
+        if accept.0 {
+            return Ok(HtmxResponder::invalid(&form.context));
+        }
+        let message = form
+            .context
+            .errors()
+            .map(|error| error.to_string())
+            .collect::<Vec<String>>()
+            .join(" ");
+        return Ok(HtmxResponder::Ok(Flash { message: &message }.to_string()));
+    };
+
+    if bulk_form.is_move() {
+        return bulk_move(db, user, accept, htmx, bulk_form).await;
+    }
+
+    let is_delete = bulk_form.is_delete();
+    let lid_id = bulk_form.line_item_date_id.clone();
+    let (records, line_item_date, line_items, quote) = db
+        .run(move |conn| {
+            let records = line_items::query::bulk(conn, &user.0, &bulk_form)?;
+            let line_item_date = line_item_dates::query::read_with_subtotal(conn, &lid_id)?;
+            let line_items = line_items::query::all_for_line_item_date(conn, &lid_id)?
+                .into_iter()
+                .map(|record| record.into())
+                .collect::<Vec<LineItemPresenter>>();
+            let quote = quotes::query::read(conn, &line_item_date.quote_id)?;
+            Result::Ok((records, line_item_date, line_items, quote))
+        })
+        .await?;
+
+    if accept.0 {
+        if is_delete {
+            return Ok(HtmxResponder::Empty(Status::NoContent));
+        }
+        let records = records
+            .into_iter()
+            .map(|record| record.into())
+            .collect::<Vec<LineItemPresenter>>();
+        return HtmxResponder::json(&records);
+    }
+
+    if !htmx.0 {
+        let uri = format!("/quotes/show/{}", quote.id);
+        return Ok(HtmxResponder::Redirect(Redirect::to(uri)));
+    }
+
+    let message = match (is_delete, records.len()) {
+        (true, 1) => String::from("1 item was successfully destroyed."),
+        (true, count) => format!("{count} items were successfully destroyed."),
+        (false, 1) => String::from("1 item was successfully updated."),
+        (false, count) => format!("{count} items were successfully updated."),
+    };
+    let content = Bulk {
+        line_item_date: &line_item_date.into(),
+        line_items: &line_items,
+        quote: &quote.into(),
+        message: &message,
+    }
+    .to_string();
+    Ok(HtmxResponder::Changed {
+        content,
+        trigger: quote_changed(),
+    })
+}
+
+// Moves the selected items to the chosen date, asking for the date first
+async fn bulk_move(
+    db: Db,
+    user: CurrentUser,
+    accept: AcceptJson,
+    htmx: HtmxRequest,
+    bulk_form: BulkLineItemsForm,
+) -> Result<HtmxResponder> {
+    let source_id = bulk_form.line_item_date_id.clone();
+    let Some(target_id) = bulk_form.target().map(String::from) else {
+        let (line_item_date, options) = db
+            .run(move |conn| {
+                let line_item_date = line_item_dates::query::read(conn, &source_id)?;
+                let options = line_item_dates::query::options(conn, &line_item_date.quote_id)?;
+                Result::Ok((line_item_date, options))
+            })
+            .await?;
+
+        let line_item_date: LineItemDatePresenter = line_item_date.into();
+        let template = BulkMoveForm {
+            line_item_date: &line_item_date,
+            selected: &bulk_form.selected,
+            options: &options,
+        };
+        if !htmx.0 {
+            return Ok(HtmxResponder::Ok(htmx.render("Move items", template)));
+        }
+        return Ok(HtmxResponder::Retarget {
+            content: template.to_string(),
+            retarget: Header::new(
+                "HX-Retarget",
+                format!("#{}", line_item_date.transfer_dom_id()),
+            ),
+            reswap: Header::new("HX-Reswap", "innerHTML"),
+        });
+    };
+
+    let (records, source, target, quote) = db
+        .run(move |conn| {
+            let records = line_items::query::bulk(conn, &user.0, &bulk_form)?;
+            let source = line_item_dates::query::read_with_subtotal(conn, &source_id)?;
+            let target = line_item_dates::query::read_with_subtotal(conn, &target_id)?;
+            let quote = quotes::query::read(conn, &source.quote_id)?;
+            Result::Ok((records, source, target, quote))
         })
         .await?;
 
-    Ok(RawHtml(
-        Destroy {
-            quote: &quote.into(),
-            message: "Item was successfully destroyed.",
+    if accept.0 {
+        let records = records
+            .into_iter()
+            .map(|record| record.into())
+            .collect::<Vec<LineItemPresenter>>();
+        return HtmxResponder::json(&records);
+    }
+
+    if !htmx.0 {
+        let uri = format!("/quotes/show/{}", quote.id);
+        return Ok(HtmxResponder::Redirect(Redirect::to(uri)));
+    }
+
+    if records.is_empty() {
+        let content = Flash {
+            message: "Items are already on that date.",
         }
-        .to_string(),
-    ))
+        .to_string();
+        return Ok(HtmxResponder::Ok(content));
+    }
+
+    let target: LineItemDatePresenter = target.into();
+    let message = match records.len() {
+        1 => String::from("1 item was successfully moved."),
+        count => format!("{count} items were successfully moved."),
+    };
+    let moved = records
+        .iter()
+        .map(|line_item| format!("line_item_{}", line_item.id))
+        .collect::<Vec<String>>();
+    let line_items = records
+        .into_iter()
+        .map(|record| record.into())
+        .collect::<Vec<LineItemPresenter>>();
+    let target = (target.quote_id == quote.id).then_some(target);
+
+    let content = Transfer {
+        moved: &moved,
+        line_items: &line_items,
+        target: &target,
+        sources: &vec![source.into()],
+        quote: &quote.into(),
+        message: &message,
+    }
+    .to_string();
+    Ok(HtmxResponder::Changed {
+        content,
+        trigger: quote_changed(),
+    })
 }
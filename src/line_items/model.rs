@@ -1,22 +1,91 @@
 use crate::{
-    forms::{validate_amount, validate_quantity},
+    audit_entries::model::Audited,
+    forms::{
+        validate_amount, validate_optional_amount, validate_quantity, validate_transfer,
+        validate_unit,
+    },
+    quantity::{DEFAULT_UNIT, format_quantity, parse_quantity},
     schema::line_items,
 };
 use currency_rs::Currency;
 use diesel::prelude::*;
+use rocket::serde::Serialize;
 use time::OffsetDateTime;
 use ulid::Ulid;
 
-#[derive(Debug, Insertable, Queryable, Selectable)]
+#[derive(Debug, Insertable, Queryable, Selectable, Serialize)]
+#[serde(crate = "rocket::serde")]
 pub(crate) struct LineItem {
     pub(crate) id: String,
     pub(crate) line_item_date_id: String,
     pub(crate) name: String,
     pub(crate) description: Option<String>,
-    pub(crate) quantity: i32,
+    pub(crate) quantity: f64,
+    #[serde(serialize_with = "crate::currency::serialize")]
     pub(crate) unit_price: Currency,
+    #[serde(skip)]
     pub(crate) created_at: OffsetDateTime,
+    #[serde(skip)]
     pub(crate) updated_at: OffsetDateTime,
+    #[serde(skip)]
+    pub(crate) position: i32,
+    pub(crate) unit: String,
+    #[serde(serialize_with = "crate::currency::serialize")]
+    pub(crate) cost_price: Currency,
+    pub(crate) resource_id: Option<String>,
+}
+
+pub(crate) const TRANSFER_COPY: &str = "copy";
+pub(crate) const TRANSFER_MOVE: &str = "move";
+
+pub(crate) const BULK_DELETE: &str = "delete";
+pub(crate) const BULK_QUANTITY: &str = "quantity";
+pub(crate) const BULK_PRICE: &str = "price";
+pub(crate) const BULK_MOVE: &str = "move";
+
+impl LineItem {
+    // A new line item with the same content, last of another date
+    pub(crate) fn copy_to(&self, line_item_date_id: &str, position: i32) -> LineItem {
+        LineItem {
+            id: Ulid::new().to_string(),
+            line_item_date_id: line_item_date_id.to_string(),
+            name: self.name.clone(),
+            description: self.description.clone(),
+            quantity: self.quantity,
+            unit_price: self.unit_price.clone(),
+            created_at: OffsetDateTime::now_utc(),
+            updated_at: OffsetDateTime::now_utc(),
+            position,
+            unit: self.unit.clone(),
+            cost_price: self.cost_price.clone(),
+            resource_id: self.resource_id.clone(),
+        }
+    }
+}
+
+impl Audited for LineItem {
+    const ENTITY: &'static str = "line_item";
+
+    fn entity_id(&self) -> &str {
+        &self.id
+    }
+}
+
+// Order of the line items of a date, audited when they are dragged around
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
+pub(crate) struct LineItemOrder {
+    pub(crate) line_item_date_id: String,
+    pub(crate) date: String,
+    pub(crate) line_items: Vec<String>,
+}
+
+impl Audited for LineItemOrder {
+    const ENTITY: &'static str = "line_item_date";
+
+    fn entity_id(&self) -> &str {
+        &self.line_item_date_id
+    }
 }
 
 // FIXME: Should be TryFrom due to potential bad parse from quantity
@@ -33,11 +102,15 @@ impl From<&EditLineItemForm> for LineItem {
             line_item_date_id: value.line_item_date_id.clone(),
             name: value.name.clone(),
             description,
-            quantity: value.quantity.parse::<i32>().unwrap_or(0),
+            quantity: parse_quantity(&value.quantity),
             unit_price: Currency::new_string(value.unit_price.as_str(), None)
                 .unwrap_or(Currency::new_float(0f64, None)),
             created_at: OffsetDateTime::now_utc(),
             updated_at: OffsetDateTime::now_utc(),
+            position: 0,
+            unit: value.unit.clone(),
+            cost_price: cost_price(&value.cost_price),
+            resource_id: resource_id(&value.resource_id),
         }
     }
 }
@@ -56,15 +129,34 @@ impl From<&NewLineItemForm> for LineItem {
             line_item_date_id: value.line_item_date_id.clone(),
             name: value.name.clone(),
             description,
-            quantity: value.quantity.parse::<i32>().unwrap_or(0),
+            quantity: parse_quantity(&value.quantity),
             unit_price: Currency::new_string(value.unit_price.as_str(), None)
                 .unwrap_or(Currency::new_float(0f64, None)),
             created_at: OffsetDateTime::now_utc(),
             updated_at: OffsetDateTime::now_utc(),
+            position: 0,
+            unit: value.unit.clone(),
+            cost_price: cost_price(&value.cost_price),
+            resource_id: resource_id(&value.resource_id),
         }
     }
 }
 
+// The resource select sends an empty value for no resource
+fn resource_id(resource_id: &Option<String>) -> Option<String> {
+    resource_id
+        .as_ref()
+        .filter(|resource_id| !resource_id.is_empty())
+        .cloned()
+}
+
+fn cost_price(cost_price: &Option<String>) -> Currency {
+    cost_price
+        .as_deref()
+        .and_then(|cost_price| Currency::new_string(cost_price, None).ok())
+        .unwrap_or(Currency::new_float(0f64, None))
+}
+
 #[derive(Clone, Debug, FromForm)]
 pub struct EditLineItemForm {
     #[field(validate = len(1..))]
@@ -78,8 +170,13 @@ pub struct EditLineItemForm {
     pub(crate) description: Option<String>,
     #[field(validate = validate_quantity())]
     pub(crate) quantity: String,
+    #[field(validate = validate_unit())]
+    pub(crate) unit: String,
     #[field(validate = validate_amount())]
     pub(crate) unit_price: String,
+    #[field(validate = validate_optional_amount())]
+    pub(crate) cost_price: Option<String>,
+    pub(crate) resource_id: Option<String>,
 }
 
 #[derive(Clone, Debug, FromForm)]
@@ -93,18 +190,100 @@ pub struct NewLineItemForm {
     pub(crate) description: Option<String>,
     #[field(validate = validate_quantity())]
     pub(crate) quantity: String,
+    #[field(validate = validate_unit())]
+    pub(crate) unit: String,
     #[field(validate = validate_amount())]
     pub(crate) unit_price: String,
+    #[field(validate = validate_optional_amount())]
+    pub(crate) cost_price: Option<String>,
+    pub(crate) resource_id: Option<String>,
+}
+
+#[derive(Clone, Debug, FromForm)]
+pub(crate) struct ReorderLineItemsForm {
+    #[field(validate = len(1..))]
+    pub(crate) line_item_date_id: String,
+    pub(crate) ids: Vec<String>,
+}
+
+// Copies or moves line items, possibly of another quote, to the end of a date
+#[derive(Clone, Debug, FromForm)]
+pub(crate) struct TransferLineItemsForm {
+    #[field(validate = len(1..).or_else(msg!("Please select at least one item")))]
+    pub(crate) ids: Vec<String>,
+    #[field(validate = len(1..).or_else(msg!("Please choose a date")))]
+    pub(crate) line_item_date_id: String,
+    #[field(validate = validate_transfer())]
+    pub(crate) action: String,
+}
+
+impl TransferLineItemsForm {
+    pub(crate) fn is_move(&self) -> bool {
+        self.action == TRANSFER_MOVE
+    }
+}
+
+// Deletes, sets the quantity of, adjusts by a percentage the price of, or moves to `target_id` the
+// selected items of a date. A move without a target asks for the date first.
+#[derive(Clone, Debug, FromForm)]
+pub(crate) struct BulkLineItemsForm {
+    #[field(validate = len(1..))]
+    pub(crate) line_item_date_id: String,
+    #[field(validate = len(1..).or_else(msg!("Please select at least one item")))]
+    pub(crate) selected: Vec<String>,
+    #[field(validate = validate_bulk_action())]
+    pub(crate) action: String,
+    #[field(validate = validate_bulk_value(&self.action))]
+    pub(crate) value: Option<String>,
+    pub(crate) target_id: Option<String>,
+}
+
+impl BulkLineItemsForm {
+    pub(crate) fn is_delete(&self) -> bool {
+        self.action == BULK_DELETE
+    }
+
+    pub(crate) fn is_move(&self) -> bool {
+        self.action == BULK_MOVE
+    }
+
+    pub(crate) fn target(&self) -> Option<&str> {
+        self.target_id.as_deref().filter(|id| !id.is_empty())
+    }
+
+    // Quantity and unit price of a line item once the action is applied
+    pub(crate) fn apply(&self, line_item: &LineItem) -> (f64, Currency) {
+        let value = self.value.as_deref().unwrap_or("");
+        match self.action.as_str() {
+            BULK_QUANTITY => (parse_quantity(value), line_item.unit_price.clone()),
+            BULK_PRICE => {
+                let percent = value.parse::<f64>().unwrap_or(0f64);
+                let unit_price = line_item
+                    .unit_price
+                    .clone()
+                    .multiply(1f64 + percent / 100f64);
+                (line_item.quantity, unit_price)
+            }
+            _ => (line_item.quantity, line_item.unit_price.clone()),
+        }
+    }
 }
 
-#[derive(Debug)]
+#[derive(Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
 pub struct LineItemPresenter {
     pub id: Option<String>,
     pub line_item_date_id: String,
     pub name: String,
     pub description: String,
     pub quantity: String,
+    pub unit: String,
+    #[serde(serialize_with = "crate::currency::serialize")]
     pub unit_price: Currency,
+    // Internal, so never part of JSON responses, webhook payloads or acceptance snapshots
+    #[serde(skip)]
+    pub cost_price: Currency,
+    pub resource_id: String,
 }
 
 impl LineItemPresenter {
@@ -115,7 +294,10 @@ impl LineItemPresenter {
             name: Default::default(),
             description: Default::default(),
             quantity: Default::default(),
+            unit: String::from(DEFAULT_UNIT),
             unit_price: Currency::new_float(0f64, None),
+            cost_price: Currency::new_float(0f64, None),
+            resource_id: Default::default(),
         }
     }
 
@@ -132,17 +314,31 @@ impl LineItemPresenter {
             None => format!("line_item_date_{}_line_item_new", self.line_item_date_id),
         }
     }
+
+    // The bulk toolbar of the date, which the selection checkbox belongs to
+    pub fn bulk_dom_id(&self) -> String {
+        format!("line_item_date_{}_bulk", self.line_item_date_id)
+    }
 }
 
 impl From<LineItem> for LineItemPresenter {
     fn from(value: LineItem) -> Self {
+        (&value).into()
+    }
+}
+
+impl From<&LineItem> for LineItemPresenter {
+    fn from(value: &LineItem) -> Self {
         LineItemPresenter {
-            id: Some(value.id),
-            line_item_date_id: value.line_item_date_id,
-            name: value.name,
-            description: value.description.unwrap_or(String::from("")),
-            quantity: value.quantity.to_string(),
-            unit_price: value.unit_price,
+            id: Some(value.id.clone()),
+            line_item_date_id: value.line_item_date_id.clone(),
+            name: value.name.clone(),
+            description: value.description.clone().unwrap_or(String::from("")),
+            quantity: format_quantity(value.quantity),
+            unit: value.unit.clone(),
+            unit_price: value.unit_price.clone(),
+            cost_price: value.cost_price.clone(),
+            resource_id: value.resource_id.clone().unwrap_or_default(),
         }
     }
 }
@@ -157,7 +353,10 @@ impl From<EditLineItemForm> for LineItemPresenter {
             name: value.name,
             description: value.description.unwrap_or(String::from("")),
             quantity: value.quantity.to_string(),
+            unit: value.unit,
             unit_price,
+            cost_price: cost_price(&value.cost_price),
+            resource_id: value.resource_id.unwrap_or_default(),
         }
     }
 }
@@ -172,7 +371,10 @@ impl From<NewLineItemForm> for LineItemPresenter {
             name: value.name,
             description: value.description.unwrap_or(String::from("")),
             quantity: value.quantity.to_string(),
+            unit: value.unit,
             unit_price,
+            cost_price: cost_price(&value.cost_price),
+            resource_id: value.resource_id.unwrap_or_default(),
         }
     }
 }
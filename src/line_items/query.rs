@@ -1,9 +1,24 @@
 use crate::{
     Result,
-    line_items::model::{EditLineItemForm, LineItem, NewLineItemForm},
+    audit_entries::{
+        self,
+        model::{ACTION_CREATE, ACTION_DELETE, ACTION_REORDER, ACTION_UPDATE, AuditEntry},
+    },
+    line_items::model::{
+        BulkLineItemsForm, EditLineItemForm, LineItem, LineItemOrder, LineItemPresenter,
+        NewLineItemForm, ReorderLineItemsForm, TransferLineItemsForm,
+    },
+    quotes,
     schema::{line_item_dates, line_items},
+    time::short_form,
+    users::model::User,
+    webhooks::{
+        self,
+        model::{LINE_ITEM_CREATED, LINE_ITEM_DELETED, LINE_ITEM_REORDERED, LINE_ITEM_UPDATED},
+    },
 };
 use diesel::prelude::*;
+use time::OffsetDateTime;
 
 pub(crate) fn all_for_quote<S: AsRef<str>>(
     connection: &mut SqliteConnection,
@@ -13,6 +28,7 @@ pub(crate) fn all_for_quote<S: AsRef<str>>(
         .inner_join(line_item_dates::table)
         .select(LineItem::as_select())
         .filter(line_item_dates::quote_id.eq(&quote_id.as_ref()))
+        .order_by((line_items::position, line_items::created_at))
         .get_results(connection)?;
 
     Ok(records)
@@ -24,6 +40,7 @@ pub(crate) fn all_for_line_item_date<S: AsRef<str>>(
 ) -> Result<Vec<LineItem>> {
     let records = line_items::table
         .filter(line_items::line_item_date_id.eq(&line_item_date_id.as_ref()))
+        .order_by((line_items::position, line_items::created_at))
         .get_results(connection)?;
     Ok(records)
 }
@@ -37,45 +54,352 @@ pub(crate) fn read<S: AsRef<str>>(connection: &mut SqliteConnection, id: S) -> R
 
 pub(crate) fn insert(
     connection: &mut SqliteConnection,
+    actor: &User,
     form: &NewLineItemForm,
 ) -> Result<LineItem> {
-    let record: LineItem = form.into();
+    let mut record: LineItem = form.into();
 
-    diesel::dsl::insert_into(line_items::table)
-        .values(&record)
-        .execute(connection)?;
+    connection.transaction::<_, crate::error::AppError, _>(|tx| {
+        let quote_id = quote_id(tx, &record.line_item_date_id)?;
+        quotes::query::ensure_editable(tx, &quote_id)?;
+        record.position = next_position(tx, &record.line_item_date_id)?;
+
+        diesel::dsl::insert_into(line_items::table)
+            .values(&record)
+            .execute(tx)?;
+
+        let entry = AuditEntry::new(actor, ACTION_CREATE, &quote_id, None, Some(&record))?;
+        audit_entries::query::insert(tx, &entry)?;
+        webhooks::query::enqueue(
+            tx,
+            LINE_ITEM_CREATED,
+            &quote_id,
+            &LineItemPresenter::from(&record),
+        )
+    })?;
 
     Ok(record)
 }
 
 pub(crate) fn update(
     connection: &mut SqliteConnection,
+    actor: &User,
     form: &EditLineItemForm,
 ) -> Result<LineItem> {
     let record: LineItem = form.into();
 
-    diesel::dsl::update(line_items::table)
-        .set((
-            line_items::name.eq(&record.name),
-            line_items::description.eq(&record.description),
-            line_items::quantity.eq(&record.quantity),
-            line_items::unit_price.eq(&record.unit_price),
-            line_items::updated_at.eq(&record.updated_at),
-        ))
-        .filter(line_items::id.eq(&record.id))
-        .execute(connection)?;
+    connection.transaction::<_, crate::error::AppError, _>(|tx| {
+        let before = read(tx, &record.id)?;
+        let quote_id = quote_id(tx, &before.line_item_date_id)?;
+        quotes::query::ensure_editable(tx, &quote_id)?;
 
-    read(connection, &record.id)
+        // The field is not shown to users who cannot see margins, so theirs is kept
+        let cost_price = if actor.can_view_margins() {
+            &record.cost_price
+        } else {
+            &before.cost_price
+        };
+
+        diesel::dsl::update(line_items::table)
+            .set((
+                line_items::name.eq(&record.name),
+                line_items::description.eq(&record.description),
+                line_items::quantity.eq(&record.quantity),
+                line_items::unit.eq(&record.unit),
+                line_items::unit_price.eq(&record.unit_price),
+                line_items::cost_price.eq(cost_price),
+                line_items::resource_id.eq(&record.resource_id),
+                line_items::updated_at.eq(&record.updated_at),
+            ))
+            .filter(line_items::id.eq(&record.id))
+            .execute(tx)?;
+
+        let after = read(tx, &record.id)?;
+        let entry = AuditEntry::new(actor, ACTION_UPDATE, &quote_id, Some(&before), Some(&after))?;
+        audit_entries::query::insert(tx, &entry)?;
+        webhooks::query::enqueue(
+            tx,
+            LINE_ITEM_UPDATED,
+            &quote_id,
+            &LineItemPresenter::from(&after),
+        )?;
+
+        Ok(after)
+    })
 }
 
-pub(crate) fn delete<S: AsRef<str>>(connection: &mut SqliteConnection, id: S) -> Result<LineItem> {
-    let record = read(connection, &id)?;
+// Returns false without changing anything when the ids posted are not exactly the line items of
+// the date, as with a page left open while items were added or removed
+pub(crate) fn reorder(
+    connection: &mut SqliteConnection,
+    actor: &User,
+    form: &ReorderLineItemsForm,
+) -> Result<bool> {
+    connection.transaction::<_, crate::error::AppError, _>(|tx| {
+        let quote_id = quote_id(tx, &form.line_item_date_id)?;
+        quotes::query::ensure_editable(tx, &quote_id)?;
+
+        let mut current: Vec<String> = line_items::table
+            .select(line_items::id)
+            .filter(line_items::line_item_date_id.eq(&form.line_item_date_id))
+            .load(tx)?;
+        let mut posted = form.ids.clone();
+        current.sort();
+        posted.sort();
+        if current != posted {
+            return Ok(false);
+        }
 
-    _ = diesel::dsl::delete(line_items::table)
-        .filter(line_items::id.eq(&id.as_ref()))
-        .execute(connection)?;
+        let before = order(tx, &form.line_item_date_id)?;
 
-    Ok(record)
+        for (position, id) in form.ids.iter().enumerate() {
+            _ = diesel::dsl::update(line_items::table)
+                .set(line_items::position.eq(position as i32))
+                .filter(line_items::id.eq(id))
+                .filter(line_items::line_item_date_id.eq(&form.line_item_date_id))
+                .execute(tx)?;
+        }
+
+        let after = order(tx, &form.line_item_date_id)?;
+        if before.line_items == after.line_items {
+            return Ok(true);
+        }
+
+        let entry = AuditEntry::new(
+            actor,
+            ACTION_REORDER,
+            &quote_id,
+            Some(&before),
+            Some(&after),
+        )?;
+        audit_entries::query::insert(tx, &entry)?;
+        webhooks::query::enqueue(tx, LINE_ITEM_REORDERED, &quote_id, &after)?;
+        Ok(true)
+    })
+}
+
+pub(crate) fn delete<S: AsRef<str>>(
+    connection: &mut SqliteConnection,
+    actor: &User,
+    id: S,
+) -> Result<LineItem> {
+    connection.transaction::<_, crate::error::AppError, _>(|tx| {
+        let record = read(tx, &id)?;
+        let quote_id = quote_id(tx, &record.line_item_date_id)?;
+        quotes::query::ensure_editable(tx, &quote_id)?;
+
+        _ = diesel::dsl::delete(line_items::table)
+            .filter(line_items::id.eq(&id.as_ref()))
+            .execute(tx)?;
+
+        let entry = AuditEntry::new(actor, ACTION_DELETE, &quote_id, Some(&record), None)?;
+        audit_entries::query::insert(tx, &entry)?;
+        webhooks::query::enqueue(
+            tx,
+            LINE_ITEM_DELETED,
+            &quote_id,
+            &LineItemPresenter::from(&record),
+        )?;
+
+        Ok(record)
+    })
+}
+
+// Applies a bulk action to the selected line items of a date, returning them as they were deleted
+// or as they are once updated
+pub(crate) fn bulk(
+    connection: &mut SqliteConnection,
+    actor: &User,
+    form: &BulkLineItemsForm,
+) -> Result<Vec<LineItem>> {
+    connection.transaction::<_, crate::error::AppError, _>(|tx| {
+        let quote_id = quote_id(tx, &form.line_item_date_id)?;
+        quotes::query::ensure_editable(tx, &quote_id)?;
+
+        let selected: Vec<LineItem> = line_items::table
+            .filter(line_items::line_item_date_id.eq(&form.line_item_date_id))
+            .filter(line_items::id.eq_any(&form.selected))
+            .order_by((line_items::position, line_items::created_at))
+            .load(tx)?;
+
+        if let Some(target_id) = form.target().filter(|_| form.is_move()) {
+            let ids = selected
+                .into_iter()
+                .map(|line_item| line_item.id)
+                .collect::<Vec<String>>();
+            let records = transfer_all(tx, actor, &ids, target_id, true)?
+                .into_iter()
+                .map(|(_, line_item)| line_item)
+                .collect();
+            return Ok(records);
+        }
+
+        let mut records = Vec::with_capacity(selected.len());
+        for before in selected {
+            if form.is_delete() {
+                diesel::dsl::delete(line_items::table)
+                    .filter(line_items::id.eq(&before.id))
+                    .execute(tx)?;
+
+                let entry = AuditEntry::new(actor, ACTION_DELETE, &quote_id, Some(&before), None)?;
+                audit_entries::query::insert(tx, &entry)?;
+                webhooks::query::enqueue(
+                    tx,
+                    LINE_ITEM_DELETED,
+                    &quote_id,
+                    &LineItemPresenter::from(&before),
+                )?;
+                records.push(before);
+                continue;
+            }
+
+            let (quantity, unit_price) = form.apply(&before);
+            diesel::dsl::update(line_items::table)
+                .set((
+                    line_items::quantity.eq(quantity),
+                    line_items::unit_price.eq(&unit_price),
+                    line_items::updated_at.eq(OffsetDateTime::now_utc()),
+                ))
+                .filter(line_items::id.eq(&before.id))
+                .execute(tx)?;
+
+            let after = read(tx, &before.id)?;
+            let entry =
+                AuditEntry::new(actor, ACTION_UPDATE, &quote_id, Some(&before), Some(&after))?;
+            audit_entries::query::insert(tx, &entry)?;
+            webhooks::query::enqueue(
+                tx,
+                LINE_ITEM_UPDATED,
+                &quote_id,
+                &LineItemPresenter::from(&after),
+            )?;
+            records.push(after);
+        }
+
+        Ok(records)
+    })
+}
+
+// Copies or moves line items to the end of a date. Returns the date each item came from and the
+// copied or moved item, items moved to the date they are already on being left alone.
+pub(crate) fn transfer(
+    connection: &mut SqliteConnection,
+    actor: &User,
+    form: &TransferLineItemsForm,
+) -> Result<Vec<(String, LineItem)>> {
+    connection.transaction::<_, crate::error::AppError, _>(|tx| {
+        transfer_all(
+            tx,
+            actor,
+            &form.ids,
+            &form.line_item_date_id,
+            form.is_move(),
+        )
+    })
+}
+
+// The work of `transfer`, in the transaction of the caller
+fn transfer_all(
+    tx: &mut SqliteConnection,
+    actor: &User,
+    ids: &[String],
+    line_item_date_id: &str,
+    is_move: bool,
+) -> Result<Vec<(String, LineItem)>> {
+    let target_quote_id = quote_id(tx, line_item_date_id)?;
+    quotes::query::ensure_editable(tx, &target_quote_id)?;
+    let mut position = next_position(tx, line_item_date_id)?;
+
+    let mut records = Vec::with_capacity(ids.len());
+    for id in ids {
+        let before = read(tx, id)?;
+        if is_move && before.line_item_date_id == line_item_date_id {
+            continue;
+        }
+        let source_quote_id = quote_id(tx, &before.line_item_date_id)?;
+
+        let after = if is_move {
+            quotes::query::ensure_editable(tx, &source_quote_id)?;
+            diesel::dsl::update(line_items::table)
+                .set((
+                    line_items::line_item_date_id.eq(line_item_date_id),
+                    line_items::position.eq(position),
+                    line_items::updated_at.eq(OffsetDateTime::now_utc()),
+                ))
+                .filter(line_items::id.eq(&before.id))
+                .execute(tx)?;
+            read(tx, &before.id)?
+        } else {
+            let record = before.copy_to(line_item_date_id, position);
+            diesel::dsl::insert_into(line_items::table)
+                .values(&record)
+                .execute(tx)?;
+            record
+        };
+        position += 1;
+
+        if is_move && source_quote_id == target_quote_id {
+            let entry = AuditEntry::new(
+                actor,
+                ACTION_UPDATE,
+                &target_quote_id,
+                Some(&before),
+                Some(&after),
+            )?;
+            audit_entries::query::insert(tx, &entry)?;
+            webhooks::query::enqueue(
+                tx,
+                LINE_ITEM_UPDATED,
+                &target_quote_id,
+                &LineItemPresenter::from(&after),
+            )?;
+        } else {
+            // An item moved to another quote leaves one quote and joins the other
+            if is_move {
+                let entry =
+                    AuditEntry::new(actor, ACTION_DELETE, &source_quote_id, Some(&before), None)?;
+                audit_entries::query::insert(tx, &entry)?;
+                webhooks::query::enqueue(
+                    tx,
+                    LINE_ITEM_DELETED,
+                    &source_quote_id,
+                    &LineItemPresenter::from(&before),
+                )?;
+            }
+            let entry =
+                AuditEntry::new(actor, ACTION_CREATE, &target_quote_id, None, Some(&after))?;
+            audit_entries::query::insert(tx, &entry)?;
+            webhooks::query::enqueue(
+                tx,
+                LINE_ITEM_CREATED,
+                &target_quote_id,
+                &LineItemPresenter::from(&after),
+            )?;
+        }
+
+        records.push((before.line_item_date_id, after));
+    }
+
+    Ok(records)
+}
+
+// Copies the line items of a date to the end of another one, in the transaction copying the date
+pub(crate) fn copy_all(
+    tx: &mut SqliteConnection,
+    line_item_date_id: &str,
+    target_id: &str,
+) -> Result<Vec<LineItem>> {
+    let position = next_position(tx, target_id)?;
+    let records = all_for_line_item_date(tx, line_item_date_id)?
+        .iter()
+        .enumerate()
+        .map(|(index, line_item)| line_item.copy_to(target_id, position + index as i32))
+        .collect::<Vec<LineItem>>();
+    diesel::dsl::insert_into(line_items::table)
+        .values(&records)
+        .execute(tx)?;
+
+    Ok(records)
 }
 
 pub(crate) fn delete_all_for_quote<S: AsRef<str>>(
@@ -105,3 +429,44 @@ pub(crate) fn delete_all_for_date<S: AsRef<str>>(tx: &mut SqliteConnection, id:
 
     Ok(())
 }
+
+fn next_position<S: AsRef<str>>(
+    connection: &mut SqliteConnection,
+    line_item_date_id: S,
+) -> Result<i32> {
+    let position: Option<i32> = line_items::table
+        .filter(line_items::line_item_date_id.eq(&line_item_date_id.as_ref()))
+        .select(diesel::dsl::max(line_items::position))
+        .get_result(connection)?;
+
+    Ok(position.map_or(0, |position| position + 1))
+}
+
+fn quote_id<S: AsRef<str>>(
+    connection: &mut SqliteConnection,
+    line_item_date_id: S,
+) -> Result<String> {
+    let quote_id = line_item_dates::table
+        .filter(line_item_dates::id.eq(&line_item_date_id.as_ref()))
+        .select(line_item_dates::quote_id)
+        .get_result(connection)?;
+
+    Ok(quote_id)
+}
+
+fn order<S: AsRef<str>>(
+    connection: &mut SqliteConnection,
+    line_item_date_id: S,
+) -> Result<LineItemOrder> {
+    let date = crate::line_item_dates::query::read(connection, &line_item_date_id)?.date;
+    let line_items = all_for_line_item_date(connection, &line_item_date_id)?
+        .into_iter()
+        .map(|line_item| line_item.name)
+        .collect();
+
+    Ok(LineItemOrder {
+        line_item_date_id: line_item_date_id.as_ref().to_string(),
+        date: short_form(date),
+        line_items,
+    })
+}
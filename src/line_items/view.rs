@@ -1,29 +1,51 @@
 use crate::{
     forms::css_for_field,
     layout::Flash,
-    line_items::model::{EditLineItemForm, LineItemPresenter, NewLineItemForm},
+    line_item_dates::{
+        model::{LineItemDateOption, LineItemDatePresenter},
+        view::SwapSubtotal,
+    },
+    line_items::model::{
+        BULK_DELETE, BULK_MOVE, BULK_PRICE, BULK_QUANTITY, EditLineItemForm, LineItemPresenter,
+        NewLineItemForm, TRANSFER_COPY, TRANSFER_MOVE,
+    },
+    quantity::{DEFAULT_UNIT, UNITS, step},
     quotes::{model::QuotePresenter, view::SwapFooter},
+    resources::view::ResourceSelect,
 };
 use rocket::form::{Contextual, Form};
 
 markup::define! {
     LineItem<'a>(line_item: &'a LineItemPresenter) {
-        div[id = &line_item.dom_id()] {
+        div[id = &line_item.dom_id(), class = "sortable-item"] {
             div[class = "flex flex-wrap items-start bg-white gap-2 mb-3 p-2 rounded-md"] {
+                div[class = "flex gap-2 flex-[0_0_3rem] mb-0"] {
+                    label[class = "visually-hidden", "for" = {format!("select_{}", line_item.dom_id())}] { "Select" }
+                    input[id = {format!("select_{}", line_item.dom_id())},
+                        name = "selected",
+                        form = &line_item.bulk_dom_id(),
+                        "type" = "checkbox",
+                        value = &line_item.id()] {}
+                    div[class = "sortable-handle cursor-grab select-none text-muted",
+                        title = "Drag to reorder"] { "⠿" }
+                }
+                input[name = "ids", "type" = "hidden", value = &line_item.id()] {}
                 div[class = "flex-1 font-bold mb-0"] {
                     @line_item.name
                     div[class = "basis-full m-w-100 font-normal text-[0.875rem] text-[hsl(0,1%,44%)]"] {
                         @line_item.description
                     }
                 }
-                div[class = "display-[revert] flex-[0_0_7rem] mb-0"] {
-                    @line_item.quantity
+                div[class = "display-[revert] flex-[0_0_11rem] mb-0"] {
+                    @line_item.quantity " " @line_item.unit
                 }
                 div[class = "display-[revert] flex-[0_0_9rem] mb-0"] {
                     @line_item.unit_price.format()
                 }
                 div[class = "flex flex-[0_0_10rem] order-[revert] gap-2"] {
-                    form["hx-post" = "/line_items/delete",
+                    form[action = "/line_items/delete",
+                         method = "post",
+                         "hx-post" = "/line_items/delete",
                         "hx-target" = {format!("#{}", line_item.dom_id())},
                         "hx-swap" = "delete"] {
 
@@ -34,6 +56,7 @@ markup::define! {
                         button[class = "button button-light", "type" = "submit"] {"Delete"}
                     }
                     a[class = "button button-light",
+                        href = {format!("/line_items/edit/{}", line_item.id())},
                         "hx-get" = {format!("/line_items/edit/{}", line_item.id())},
                         "hx-target" = {format!("#{}", line_item.dom_id())},
                         "hx-trigger" = "click"] { "Edit" }
@@ -42,9 +65,13 @@ markup::define! {
         }
     }
 
-    EditForm<'a>(line_item: &'a LineItemPresenter, quote: &'a QuotePresenter) {
-        div[id = &line_item.dom_id()] {
+    EditForm<'a>(line_item: &'a LineItemPresenter, quote: &'a QuotePresenter, can_view_margins: bool) {
+        div[id = &line_item.dom_id(), class = "sortable-item"] {
+            // Keeps the item in the order posted when the others are dragged while it is edited
+            input[name = "ids", "type" = "hidden", value = &line_item.id()] {}
             form[id = &line_item.dom_id(),
+                action = "/line_items/update",
+                method = "post",
                 "hx-post" = "/line_items/update",
                 "hx-target" = {format!("#{}", line_item.dom_id())},
                 "hx-swap" = "outerHTML",
@@ -66,7 +93,8 @@ markup::define! {
                     "type" = "hidden",
                     value = &line_item.line_item_date_id] {}
 
-                div[class = "flex-1 font-bold mb-0"] {
+                div[class = "flex-[0_0_3rem] mb-0"] {}
+                div[class = "relative flex-1 font-bold mb-0"] {
                     input[id = "line_item_name",
                         name = "name",
                         class = "form-input",
@@ -74,18 +102,29 @@ markup::define! {
                         placeholder = "Name of your item",
                         required,
                         "type" = "text",
-                        value = &line_item.name] {}
+                        value = &line_item.name,
+                        "hx-get" = "/catalog_items/search",
+                        "hx-trigger" = "input changed delay:300ms",
+                        "hx-target" = "next .catalog-suggestions",
+                        "hx-swap" = "innerHTML"] {}
+                    div[class = "catalog-suggestions"] {}
                 }
-                div[class = "block flex-[0_0_7rem] mb-0"] {
+                div[class = "flex gap-1 flex-[0_0_11rem] mb-0"] {
                     input[id = "line_item_quantity",
                         name = "quantity",
                         class = "form-input",
                         placeholder = "1",
                         required,
                         "type" = "number",
-                        min = "1",
-                        step = "1",
+                        min = "0",
+                        step = step(),
                         value = &line_item.quantity] {}
+                    label[class = "visually-hidden", "for" = "line_item_unit"] { "Unit" }
+                    select[id = "line_item_unit",
+                        name = "unit",
+                        class = "form-input"] {
+                        @UnitOptions { selected: &line_item.unit }
+                    }
                 }
                 div[class = "block flex-[0_0_9rem] mb-0"] {
                     input[id = "line_item_price",
@@ -97,14 +136,28 @@ markup::define! {
                         min = "0.01",
                         step = "0.01",
                         value = &line_item.unit_price.to_string()] {}
+                    @if *can_view_margins {
+                        label[class = "visually-hidden", "for" = "line_item_cost_price"] { "Cost price" }
+                        input[id = "line_item_cost_price",
+                            name = "cost_price",
+                            class = "form-input mt-1",
+                            placeholder = "Cost (internal)",
+                            title = "Internal cost price, never shown to customers",
+                            "type" = "number",
+                            min = "0",
+                            step = "0.01",
+                            value = &line_item.cost_price.to_string()] {}
+                    }
                 }
                 div[class = "basis-full order-2 m-w-100 font-normal text-[0.875rem] text-[hsl(0,1%,44%)] mb-0"] {
+                    @ResourceSelect { selected: &line_item.resource_id }
                     textarea[id = "line_item_description",
                         name = "description",
                         class = "resize-none form-input",
                         placeholder = "Description (optional)"] { @line_item.description }
                 }
                 a[class = "button button-light",
+                    href = {format!("/line_items/{}", line_item.id())},
                     "hx-get" = {format!("/line_items/{}", line_item.id())},
                     "hx-target" = {format!("#{}", line_item.dom_id())},
                     "hx-trigger" = "click",
@@ -118,19 +171,25 @@ markup::define! {
         }
     }
 
-    EditFormWithErrors<'a, 'r>(form: &'a Form<Contextual<'r, EditLineItemForm>>) {
+    EditFormWithErrors<'a, 'r>(form: &'a Form<Contextual<'r, EditLineItemForm>>, can_view_margins: bool) {
         @let context = &form.context;
         @let id = context.field_value("id").unwrap_or("");
         @let quote_id = context.field_value("quote_id").unwrap_or("");
         @let line_item_date_id = context.field_value("line_item_date_id").unwrap_or("");
         @let name = context.field_value("name").unwrap_or("");
         @let quantity = context.field_value("quantity").unwrap_or("");
+        @let unit = context.field_value("unit").unwrap_or(DEFAULT_UNIT);
         @let unit_price = context.field_value("unit_price").unwrap_or("");
+        @let cost_price = context.field_value("cost_price").unwrap_or("");
+        @let resource_id = context.field_value("resource_id").unwrap_or("");
         @let description = context.field_value("description").unwrap_or("");
         @let dom_id = format!("line_item_{}", id);
 
-        div[id = &dom_id] {
+        div[id = &dom_id, class = "sortable-item"] {
+            input[name = "ids", "type" = "hidden", value = id] {}
             form[id = &dom_id,
+                action = "/line_items/update",
+                method = "post",
                 "hx-post" = "/line_items/update",
                 "hx-target" = {format!("#{}", dom_id)},
                 "hx-swap" = "outerHTML",
@@ -158,24 +217,36 @@ markup::define! {
                     name = "line_item_date_id",
                     "type" = "hidden",
                     value = &line_item_date_id] {}
-                div[class = "flex-1 font-bold mb-0"] {
+                div[class = "flex-[0_0_3rem] mb-0"] {}
+                div[class = "relative flex-1 font-bold mb-0"] {
                     input[id = "line_item_name",
                         name = "name",
                         class = css_for_field(form, "name", "form-input", "border-primary"),
                         autofocus = "autofocus",
                         placeholder = "Name of your item",
                         "type" = "text",
-                        value = &name] {}
+                        value = &name,
+                        "hx-get" = "/catalog_items/search",
+                        "hx-trigger" = "input changed delay:300ms",
+                        "hx-target" = "next .catalog-suggestions",
+                        "hx-swap" = "innerHTML"] {}
+                    div[class = "catalog-suggestions"] {}
                 }
-                div[class = "block flex-[0_0_7rem] mb-0"] {
+                div[class = "flex gap-1 flex-[0_0_11rem] mb-0"] {
                     input[id = "line_item_quantity",
                         name = "quantity",
                         class = css_for_field(form, "quantity", "form-input", "border-primary"),
                         placeholder = "1",
                         "type" = "number",
-                        min = "1",
-                        step = "1",
+                        min = "0",
+                        step = step(),
                         value = &quantity] {}
+                    label[class = "visually-hidden", "for" = "line_item_unit"] { "Unit" }
+                    select[id = "line_item_unit",
+                        name = "unit",
+                        class = css_for_field(form, "unit", "form-input", "border-primary")] {
+                        @UnitOptions { selected: &unit }
+                    }
                 }
                 div[class = "block flex-[0_0_9rem] mb-0"] {
                     input[id = "line_item_price",
@@ -186,14 +257,28 @@ markup::define! {
                         min = "0.01",
                         step = "0.01",
                         value = &unit_price] {}
+                    @if *can_view_margins {
+                        label[class = "visually-hidden", "for" = "line_item_cost_price"] { "Cost price" }
+                        input[id = "line_item_cost_price",
+                            name = "cost_price",
+                            class = css_for_field(form, "cost_price", "form-input mt-1", "border-primary"),
+                            placeholder = "Cost (internal)",
+                            title = "Internal cost price, never shown to customers",
+                            "type" = "number",
+                            min = "0",
+                            step = "0.01",
+                            value = &cost_price] {}
+                    }
                 }
                 div[class = "basis-full order-2 m-w-100 font-normal text-[0.875rem] text-[hsl(0,1%,44%)] mb-0"] {
+                    @ResourceSelect { selected: &resource_id }
                     textarea[id = "line_item_description",
                         name = "description",
                         class = css_for_field(form, "description", "resize-none form-input", "border-primary"),
                         placeholder = "Description (optional)"] { @description }
                 }
                 a[class = "button button-light",
+                    href = {format!("/line_items/{}", id)},
                     "hx-get" = {format!("/line_items/{}", id)},
                     "hx-target" = {format!("#{}", dom_id)},
                     "hx-trigger" = "click",
@@ -208,10 +293,13 @@ markup::define! {
     }
 
     NewForm<'a>(line_item: &'a LineItemPresenter,
-                quote: &'a QuotePresenter) {
+                quote: &'a QuotePresenter,
+                can_view_margins: bool) {
         div[id = &line_item.dom_id()] {
             @let line_item_new_dom_id = format!("#line_item_date_{}_line_items", line_item.line_item_date_id);
             form[id = "form_new",
+                action = "/line_items/create",
+                method = "post",
                 "hx-post" = "/line_items/create",
                 "hx-target" = line_item_new_dom_id,
                 "hx-swap" = "beforeend",
@@ -228,24 +316,36 @@ markup::define! {
                     name = "line_item_date_id",
                     "type" = "hidden",
                     value = &line_item.line_item_date_id] {}
-                div[class = "flex-1 font-bold mb-0"] {
+                div[class = "flex-[0_0_3rem] mb-0"] {}
+                div[class = "relative flex-1 font-bold mb-0"] {
                     input[id = "line_item_name",
                         name = "name",
                         class = "form-input",
                         autofocus = "autofocus",
                         placeholder = "Name of your item",
                         "type" = "text",
-                        value = &line_item.name] {}
+                        value = &line_item.name,
+                        "hx-get" = "/catalog_items/search",
+                        "hx-trigger" = "input changed delay:300ms",
+                        "hx-target" = "next .catalog-suggestions",
+                        "hx-swap" = "innerHTML"] {}
+                    div[class = "catalog-suggestions"] {}
                 }
-                div[class = "block flex-[0_0_7rem] mb-0"] {
+                div[class = "flex gap-1 flex-[0_0_11rem] mb-0"] {
                     input[id = "line_item_quantity",
                         name = "quantity",
                         class = "form-input",
                         placeholder = "1",
                         "type" = "number",
-                        min = "1",
-                        step = "1",
+                        min = "0",
+                        step = step(),
                         value = &line_item.quantity] {}
+                    label[class = "visually-hidden", "for" = "line_item_unit"] { "Unit" }
+                    select[id = "line_item_unit",
+                        name = "unit",
+                        class = "form-input"] {
+                        @UnitOptions { selected: &line_item.unit }
+                    }
                 }
                 div[class = "block flex-[0_0_9rem] mb-0"] {
                     input[id = "line_item_price",
@@ -256,8 +356,21 @@ markup::define! {
                         min = "0.01",
                         step = "0.01",
                         value = &line_item.unit_price.to_string()] {}
+                    @if *can_view_margins {
+                        label[class = "visually-hidden", "for" = "line_item_cost_price"] { "Cost price" }
+                        input[id = "line_item_cost_price",
+                            name = "cost_price",
+                            class = "form-input mt-1",
+                            placeholder = "Cost (internal)",
+                            title = "Internal cost price, never shown to customers",
+                            "type" = "number",
+                            min = "0",
+                            step = "0.01",
+                            value = &line_item.cost_price.to_string()] {}
+                    }
                 }
                 div[class = "basis-full order-2 m-w-100 font-normal text-[0.875rem] text-[hsl(0,1%,44%)] mb-0"] {
+                    @ResourceSelect { selected: &line_item.resource_id }
                     textarea[id = "line_item_description",
                         name = "description",
                         class = "resize-none form-input",
@@ -274,18 +387,23 @@ markup::define! {
         }
     }
 
-    NewFormWithErrors<'a, 'r>(form: &'a Form<Contextual<'r, NewLineItemForm>>) {
+    NewFormWithErrors<'a, 'r>(form: &'a Form<Contextual<'r, NewLineItemForm>>, can_view_margins: bool) {
         @let context = &form.context;
         @let quote_id = context.field_value("quote_id").unwrap_or("");
         @let line_item_date_id = context.field_value("line_item_date_id").unwrap_or("");
         @let name = context.field_value("name").unwrap_or("");
         @let quantity = context.field_value("quantity").unwrap_or("");
+        @let unit = context.field_value("unit").unwrap_or(DEFAULT_UNIT);
         @let unit_price = context.field_value("unit_price").unwrap_or("");
+        @let cost_price = context.field_value("cost_price").unwrap_or("");
+        @let resource_id = context.field_value("resource_id").unwrap_or("");
         @let description = context.field_value("description").unwrap_or("");
 
         div[id = "line_item_new"] {
             @let line_item_new_dom_id = format!("#line_item_date_{}_line_items", line_item_date_id);
             form[id = "form_new",
+                action = "/line_items/create",
+                method = "post",
                 "hx-post" = "/line_items/create",
                 "hx-target" = line_item_new_dom_id,
                 "hx-swap" = "beforeend",
@@ -309,7 +427,8 @@ markup::define! {
                     name = "line_item_date_id",
                     "type" = "hidden",
                     value = &line_item_date_id] {}
-                div[class = "flex-1 font-bold mb-0"] {
+                div[class = "flex-[0_0_3rem] mb-0"] {}
+                div[class = "relative flex-1 font-bold mb-0"] {
                     input[id = "line_item_name",
                         name = "name",
                         class = css_for_field(form, "name", "form-input", "border-primary"),
@@ -317,18 +436,29 @@ markup::define! {
                         placeholder = "Name of your item",
                         required,
                         "type" = "text",
-                        value = &name] {}
+                        value = &name,
+                        "hx-get" = "/catalog_items/search",
+                        "hx-trigger" = "input changed delay:300ms",
+                        "hx-target" = "next .catalog-suggestions",
+                        "hx-swap" = "innerHTML"] {}
+                    div[class = "catalog-suggestions"] {}
                 }
-                div[class = "block flex-[0_0_7rem] mb-0"] {
+                div[class = "flex gap-1 flex-[0_0_11rem] mb-0"] {
                     input[id = "line_item_quantity",
                         name = "quantity",
                         class = css_for_field(form, "quantity", "form-input", "border-primary"),
                         placeholder = "1",
                         required,
                         "type" = "number",
-                        min = "1",
-                        step = "1",
+                        min = "0",
+                        step = step(),
                         value = &quantity] {}
+                    label[class = "visually-hidden", "for" = "line_item_unit"] { "Unit" }
+                    select[id = "line_item_unit",
+                        name = "unit",
+                        class = css_for_field(form, "unit", "form-input", "border-primary")] {
+                        @UnitOptions { selected: &unit }
+                    }
                 }
                 div[class = "block flex-[0_0_9rem] mb-0"] {
                     input[id = "line_item_price",
@@ -340,8 +470,21 @@ markup::define! {
                         min = "0.01",
                         step = "0.01",
                         value = &unit_price] {}
+                    @if *can_view_margins {
+                        label[class = "visually-hidden", "for" = "line_item_cost_price"] { "Cost price" }
+                        input[id = "line_item_cost_price",
+                            name = "cost_price",
+                            class = css_for_field(form, "cost_price", "form-input mt-1", "border-primary"),
+                            placeholder = "Cost (internal)",
+                            title = "Internal cost price, never shown to customers",
+                            "type" = "number",
+                            min = "0",
+                            step = "0.01",
+                            value = &cost_price] {}
+                    }
                 }
                 div[class = "basis-full order-2 m-w-100 font-normal text-[0.875rem] text-[hsl(0,1%,44%)] mb-0"] {
+                    @ResourceSelect { selected: &resource_id }
                     textarea[id = "line_item_description",
                         name = "description",
                         class = css_for_field(form, "description", "resize-none form-input", "border-primary"),
@@ -358,22 +501,233 @@ markup::define! {
         }
     }
 
-    Create<'a>(line_item: &'a LineItemPresenter, quote: &'a QuotePresenter, message: &'a str) {
+    UnitOptions<'a>(selected: &'a str) {
+        @for unit in UNITS.iter() {
+            option[value = unit, selected = unit == selected] { @unit }
+        }
+    }
+
+    // Options for picking a line item of a quote, or none for the whole quote
+    LineItemOptions<'a>(line_items: &'a Vec<LineItemPresenter>, selected: &'a str) {
+        option[value = ""] { "Whole quote" }
+        @for line_item in *line_items {
+            @let id = line_item.id();
+            option[value = &id, selected = id == *selected] { @line_item.name }
+        }
+    }
+
+    BulkToolbar<'a>(line_item_date: &'a LineItemDatePresenter) {
+        @let line_items_dom_id = format!("line_item_date_{}_line_items", line_item_date.id());
+        @let selected = format!("#{} input[name='selected']:checked", line_items_dom_id);
+        // The checkboxes of the line items are part of the form through their `form` attribute
+        form[id = &line_item_date.bulk_dom_id(),
+            action = "/line_items/bulk",
+            method = "post",
+            "hx-post" = "/line_items/bulk",
+            "hx-swap" = "none",
+            class = "flex flex-wrap items-center justify-end gap-2 mb-3",
+            autocomplete = "off",
+            novalidate,
+            "accept-charset" = "UTF-8"] {
+
+            input[name = "line_item_date_id", "type" = "hidden", value = &line_item_date.id()] {}
+            label[class = "visually-hidden", "for" = {format!("bulk_action_{}", line_item_date.id())}] { "Action" }
+            select[id = {format!("bulk_action_{}", line_item_date.id())},
+                name = "action",
+                class = "form-input [flex:0_0_13rem]"] {
+                option[value = BULK_QUANTITY] { "Set quantity to" }
+                option[value = BULK_PRICE] { "Change prices by (%)" }
+                option[value = BULK_DELETE] { "Delete" }
+                option[value = BULK_MOVE] { "Move to another date" }
+            }
+            label[class = "visually-hidden", "for" = {format!("bulk_value_{}", line_item_date.id())}] { "Value" }
+            input[id = {format!("bulk_value_{}", line_item_date.id())},
+                name = "value",
+                class = "form-input [flex:0_0_8rem]",
+                "type" = "number",
+                step = "any"] {}
+            input[name = "commit",
+                "type" = "submit",
+                value = "Apply to selected",
+                class = "button button-light",
+                "hx-confirm" = "Apply to the selected items?"] {}
+            a[class = "button button-light",
+                href = {format!("/line_items/transfer/{}", line_item_date.id())},
+                "hx-get" = {format!("/line_items/transfer/{}", line_item_date.id())},
+                "hx-include" = &selected,
+                "hx-target" = {format!("#{}", line_item_date.transfer_dom_id())},
+                "hx-trigger" = "click",
+                "hx-swap" = "innerHTML"] { "Copy or move selected" }
+        }
+    }
+
+    // Asks where to move the selected items, then moves them with the other bulk actions
+    BulkMoveForm<'a>(line_item_date: &'a LineItemDatePresenter,
+                     selected: &'a Vec<String>,
+                     options: &'a Vec<LineItemDateOption>) {
+        @let form_id = format!("form_bulk_move_{}", line_item_date.id());
+        form[id = &form_id,
+            action = "/line_items/bulk",
+            method = "post",
+            "hx-post" = "/line_items/bulk",
+            "hx-swap" = "none",
+            class = "flex flex-wrap items-center gap-2 mb-3 p-2 rounded-md bg-light",
+            autocomplete = "off",
+            novalidate,
+            "accept-charset" = "UTF-8"] {
+
+            input[name = "line_item_date_id", "type" = "hidden", value = &line_item_date.id()] {}
+            input[name = "action", "type" = "hidden", value = BULK_MOVE] {}
+            @for id in *selected {
+                input[name = "selected", "type" = "hidden", value = id] {}
+            }
+            span {
+                @if selected.len() == 1 { "Move the selected item to" } else { {format!("Move the {} selected items to", selected.len())} }
+            }
+            label[class = "visually-hidden", "for" = {format!("bulk_target_{}", line_item_date.id())}] { "Date" }
+            select[id = {format!("bulk_target_{}", line_item_date.id())},
+                name = "target_id",
+                class = "form-input [flex:1]"] {
+                @for date_option in *options {
+                    @if date_option.id != line_item_date.id() {
+                        option[value = &date_option.id] { @date_option.label() }
+                    }
+                }
+            }
+            a[class = "button button-light",
+                "_" = {format!("on click remove #{form_id}")}] { "Cancel" }
+            input[name = "commit",
+                "type" = "submit",
+                value = "Move",
+                class = "button button-secondary",
+                "_" = "on click add { pointer-events: none }"] {}
+        }
+    }
+
+    // Every line item of the date again after a bulk action
+    Bulk<'a>(line_item_date: &'a LineItemDatePresenter,
+             line_items: &'a Vec<LineItemPresenter>,
+             quote: &'a QuotePresenter,
+             message: &'a str) {
+        div[id = format!("line_item_date_{}_line_items", line_item_date.id()), "hx-swap-oob" = "innerHTML"] {
+            @for line_item in *line_items {
+                @LineItem { line_item }
+            }
+        }
+        @Flash { message }
+        @SwapSubtotal { line_item_date }
+        @SwapFooter { quote }
+    }
+
+    TransferForm<'a>(line_item_date: &'a LineItemDatePresenter,
+                     ids: &'a Vec<String>,
+                     options: &'a Vec<LineItemDateOption>) {
+        @let form_id = format!("form_transfer_{}", line_item_date.id());
+        @if ids.is_empty() {
+            div[id = &form_id, class = "flex items-center justify-between gap-2 mb-3 p-2 rounded-md bg-light"] {
+                span { "Select the items to copy or move first." }
+                a[class = "button button-light",
+                    "_" = {format!("on click remove #{form_id}")}] { "Close" }
+            }
+        } else {
+            form[id = &form_id,
+                action = "/line_items/transfer",
+                method = "post",
+                "hx-post" = "/line_items/transfer",
+                "hx-swap" = "none",
+                class = "flex flex-wrap items-center gap-2 mb-3 p-2 rounded-md bg-light",
+                autocomplete = "off",
+                novalidate,
+                "accept-charset" = "UTF-8"] {
+
+                @for id in *ids {
+                    input[name = "ids", "type" = "hidden", value = id] {}
+                }
+                label[class = "flex items-center gap-1"] {
+                    input[name = "action", "type" = "radio", value = TRANSFER_COPY, checked] {}
+                    "Copy"
+                }
+                label[class = "flex items-center gap-1"] {
+                    input[name = "action", "type" = "radio", value = TRANSFER_MOVE] {}
+                    "Move"
+                }
+                span {
+                    @if ids.len() == 1 { "the selected item to" } else { {format!("the {} selected items to", ids.len())} }
+                }
+                label[class = "visually-hidden", "for" = {format!("transfer_date_{}", line_item_date.id())}] { "Date" }
+                select[id = {format!("transfer_date_{}", line_item_date.id())},
+                    name = "line_item_date_id",
+                    class = "form-input [flex:1]"] {
+                    @for date_option in *options {
+                        option[value = &date_option.id, selected = date_option.id == line_item_date.id()] {
+                            @date_option.label()
+                        }
+                    }
+                }
+                a[class = "button button-light",
+                    "_" = {format!("on click remove #{form_id}")}] { "Cancel" }
+                input[name = "commit",
+                    "type" = "submit",
+                    value = "Confirm",
+                    class = "button button-secondary",
+                    "_" = "on click add { pointer-events: none }"] {}
+            }
+        }
+    }
+
+    // Line items copied or moved from the dates of `sources`, appended to `target` when it is on the page
+    Transfer<'a>(moved: &'a Vec<String>,
+                 line_items: &'a Vec<LineItemPresenter>,
+                 target: &'a Option<LineItemDatePresenter>,
+                 sources: &'a Vec<LineItemDatePresenter>,
+                 quote: &'a QuotePresenter,
+                 message: &'a str) {
+        @for dom_id in *moved {
+            div[id = dom_id, "hx-swap-oob" = "delete"] {}
+        }
+        @if let Some(target) = target {
+            div[id = format!("line_item_date_{}_line_items", target.id()), "hx-swap-oob" = "beforeend"] {
+                @for line_item in *line_items {
+                    @LineItem { line_item }
+                }
+            }
+            @SwapSubtotal { line_item_date: target }
+        }
+        @for line_item_date in *sources {
+            div[id = &line_item_date.transfer_dom_id(), "hx-swap-oob" = "innerHTML"] {}
+            @SwapSubtotal { line_item_date }
+        }
+        @Flash { message }
+        @SwapFooter { quote }
+    }
+
+    Create<'a>(line_item: &'a LineItemPresenter,
+               line_item_date: &'a LineItemDatePresenter,
+               quote: &'a QuotePresenter,
+               message: &'a str) {
         @let line_item_new_dom_id = format!("line_item_date_{}_line_item_new", line_item.line_item_date_id);
         @LineItem{ line_item }
         div[id = &line_item_new_dom_id, "hx-swap-oob"="innerHTML"]{}
         @Flash{ message }
+        @SwapSubtotal{ line_item_date }
         @SwapFooter{ quote }
     }
 
-    Update<'a>(line_item: &'a LineItemPresenter, quote: &'a QuotePresenter, message: &'a str) {
+    Update<'a>(line_item: &'a LineItemPresenter,
+               line_item_date: &'a LineItemDatePresenter,
+               quote: &'a QuotePresenter,
+               message: &'a str) {
         @LineItem{ line_item }
         @Flash{ message }
+        @SwapSubtotal{ line_item_date }
         @SwapFooter{ quote }
     }
 
-    Destroy<'a>(quote: &'a QuotePresenter, message: &'a str) {
+    Destroy<'a>(line_item_date: &'a LineItemDatePresenter,
+                quote: &'a QuotePresenter,
+                message: &'a str) {
         @Flash{ message }
+        @SwapSubtotal{ line_item_date }
         @SwapFooter{ quote }
     }
 }
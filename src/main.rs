@@ -7,19 +7,35 @@
 #[macro_use]
 extern crate rocket;
 
+pub mod acceptances;
 mod assets;
+pub mod attachments;
+pub mod audit_entries;
+pub mod calendar;
+pub mod catalog_items;
+pub mod comments;
+mod config;
 mod currency;
+pub mod emails;
 mod error;
 mod forms;
 pub mod layout;
 pub mod line_item_dates;
 pub mod line_items;
+mod quantity;
 pub mod quotes;
+pub mod resources;
 mod rocket_ext;
+pub mod schedule;
 mod schema;
+pub mod share_links;
+pub mod templates;
 mod time;
+pub mod users;
+pub mod webhooks;
 
 use diesel::sqlite::SqliteConnection;
+use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
 use rocket::response::Redirect;
 use rocket::{Build, Rocket, fairing::AdHoc};
 use rocket_sync_db_pools::database;
@@ -29,9 +45,12 @@ struct Db(SqliteConnection);
 
 pub(crate) type Result<T = ()> = std::result::Result<T, error::AppError>;
 
+const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
+
 #[launch]
 fn rocket() -> _ {
     rocket::build()
+        .attach(config::stage())
         .attach(AdHoc::on_ignite("Diesel SQLite Stage", |rocket| async {
             rocket
                 .attach(Db::fairing())
@@ -41,6 +60,19 @@ fn rocket() -> _ {
         .attach(quotes::controller::stage())
         .attach(line_item_dates::controller::stage())
         .attach(line_items::controller::stage())
+        .attach(catalog_items::controller::stage())
+        .attach(resources::controller::stage())
+        .attach(users::controller::stage())
+        .attach(audit_entries::controller::stage())
+        .attach(comments::controller::stage())
+        .attach(attachments::controller::stage())
+        .attach(share_links::controller::stage())
+        .attach(acceptances::controller::stage())
+        .attach(emails::controller::stage())
+        .attach(templates::controller::stage())
+        .attach(webhooks::controller::stage())
+        .attach(calendar::controller::stage())
+        .attach(schedule::controller::stage())
         .attach(assets::stage())
 }
 
@@ -50,10 +82,6 @@ fn index() -> Redirect {
 }
 
 async fn run_migrations(rocket: Rocket<Build>) -> Rocket<Build> {
-    use diesel_migrations::{EmbeddedMigrations, MigrationHarness, embed_migrations};
-
-    const MIGRATIONS: EmbeddedMigrations = embed_migrations!();
-
     Db::get_one(&rocket)
         .await
         .expect("failure obtaining database connection")
@@ -65,3 +93,13 @@ async fn run_migrations(rocket: Rocket<Build>) -> Rocket<Build> {
 
     rocket
 }
+
+// Fresh in-memory database with every migration applied, for tests of queries
+#[cfg(test)]
+fn test_connection() -> SqliteConnection {
+    use diesel::Connection;
+
+    let mut connection = SqliteConnection::establish(":memory:").unwrap();
+    connection.run_pending_migrations(MIGRATIONS).unwrap();
+    connection
+}
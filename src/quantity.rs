@@ -0,0 +1,58 @@
+use crate::config;
+use regex::Regex;
+
+pub(crate) static UNITS: [&str; 5] = ["pcs", "hours", "days", "m²", "kg"];
+pub(crate) static DEFAULT_UNIT: &str = "pcs";
+
+// Built from the configured precision when used, rather than frozen by whoever reads it first
+pub(crate) fn quantity_regex() -> Regex {
+    let pattern = match precision() {
+        0 => String::from(r"^\d+$"),
+        precision => format!(r"^\d+(\.\d{{1,{}}})?$", precision),
+    };
+    Regex::new(&pattern).unwrap()
+}
+
+pub(crate) fn precision() -> usize {
+    config::get().quantity_precision
+}
+
+// Step for `<input type="number">`, e.g. "0.01" for a precision of 2
+pub(crate) fn step() -> String {
+    match precision() {
+        0 => String::from("1"),
+        precision => format!("0.{}1", "0".repeat(precision - 1)),
+    }
+}
+
+pub(crate) fn parse_quantity<S: AsRef<str>>(text: S) -> f64 {
+    let quantity = text.as_ref().parse::<f64>().unwrap_or(0f64);
+    let factor = 10f64.powi(precision() as i32);
+    (quantity * factor).round() / factor
+}
+
+pub(crate) fn format_quantity(quantity: f64) -> String {
+    let text = format!("{:.*}", precision(), quantity);
+    if text.contains('.') {
+        text.trim_end_matches('0').trim_end_matches('.').to_string()
+    } else {
+        text
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    #[test]
+    fn format_quantity() {
+        assert_eq!("2.5", super::format_quantity(2.5));
+        assert_eq!("12.75", super::format_quantity(12.75));
+        assert_eq!("3", super::format_quantity(3.0));
+        assert_eq!("0.33", super::format_quantity(1.0 / 3.0));
+    }
+
+    #[test]
+    fn parse_quantity() {
+        assert_eq!(2.5, super::parse_quantity("2.50"));
+        assert_eq!(0.0, super::parse_quantity("abc"));
+    }
+}
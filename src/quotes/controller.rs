@@ -1,5 +1,5 @@
 use crate::{
-    Db, Result,
+    Db, Result, config,
     layout::{Flash, Layout},
     line_item_dates::{self, model::LineItemDatePresenter},
     line_items::{self, model::LineItemPresenter},
@@ -8,30 +8,35 @@ use crate::{
         model::{DeleteForm, EditQuoteForm, NewQuoteForm, QuotePresenter},
         view::*,
     },
-    rocket_ext::HtmxResponder,
+    rocket_ext::{AcceptJson, HtmxRequest, HtmxResponder},
+    templates,
+    users::model::CurrentUser,
 };
 use itertools::Itertools;
 use rocket::{
     fairing::AdHoc,
     form::{Contextual, Form},
-    http::Header,
-    response::content::RawHtml,
+    http::{Header, Status},
+    response::{Redirect, content::RawHtml},
+    serde::json::json,
 };
 
 pub(crate) fn stage() -> AdHoc {
     AdHoc::on_ignite("Quote Stage", |rocket| async {
         rocket.mount(
             "/quotes",
-            routes![index, quote, show, new, create, edit, update, delete],
+            routes![
+                index, quote, show, margins, new, create, edit, update, delete
+            ],
         )
     })
 }
 
 #[get("/")]
-async fn index(db: Db) -> Result<RawHtml<String>> {
+async fn index(db: Db, accept: AcceptJson) -> Result<HtmxResponder> {
     let quotes = db
         .run(move |conn| {
-            let records = quotes::query::all(conn)?
+            let records = quotes::query::all_with_totals(conn)?
                 .into_iter()
                 .map(|record| record.into())
                 .collect::<Vec<QuotePresenter>>();
@@ -39,6 +44,10 @@ async fn index(db: Db) -> Result<RawHtml<String>> {
         })
         .await?;
 
+    if accept.0 {
+        return HtmxResponder::json(&quotes);
+    }
+
     let template = Layout {
         head: markup::new! {
             title { "Quotes" }
@@ -46,11 +55,11 @@ async fn index(db: Db) -> Result<RawHtml<String>> {
         body: Index { quotes },
     };
 
-    Ok(RawHtml(template.to_string()))
+    Ok(HtmxResponder::Ok(template.to_string()))
 }
 
 #[get("/<id>")]
-async fn quote(db: Db, id: String) -> Result<RawHtml<String>> {
+async fn quote(db: Db, accept: AcceptJson, htmx: HtmxRequest, id: String) -> Result<HtmxResponder> {
     let quote = db
         .run(move |conn| {
             let quote = quotes::query::read(conn, &id)?;
@@ -58,14 +67,17 @@ async fn quote(db: Db, id: String) -> Result<RawHtml<String>> {
         })
         .await?;
 
-    let quote = Quote {
-        quote: &quote.into(),
-    };
-    Ok(RawHtml(quote.to_string()))
+    let quote: QuotePresenter = quote.into();
+    if accept.0 {
+        return HtmxResponder::json(&quote);
+    }
+
+    let template = Quote { quote: &quote };
+    Ok(HtmxResponder::Ok(htmx.render("Quote", template)))
 }
 
 #[get("/show/<id>")]
-async fn show(db: Db, id: String) -> Result<RawHtml<String>> {
+async fn show(db: Db, accept: AcceptJson, id: String) -> Result<HtmxResponder> {
     let quote = db
         .run(move |conn| {
             let quote = quotes::query::read(conn, &id)?;
@@ -97,40 +109,94 @@ async fn show(db: Db, id: String) -> Result<RawHtml<String>> {
         })
         .await?;
 
+    let quote: QuotePresenter = quote.into();
+    if accept.0 {
+        return HtmxResponder::json(&json!({
+            "quote": quote,
+            "line_item_dates": line_item_dates,
+            "line_items": line_items,
+        }));
+    }
+
     let quote_name = quote.name.clone();
     let template = Layout {
         head: markup::new! {
             title { {format!("Quote {quote_name}")} }
         },
         body: Show {
-            quote: &quote.into(),
+            quote: &quote,
             line_item_dates: &line_item_dates,
             line_items: &line_items,
         },
     };
 
-    Ok(RawHtml(template.to_string()))
+    Ok(HtmxResponder::Ok(template.to_string()))
+}
+
+#[get("/margins/<id>")]
+async fn margins(
+    db: Db,
+    htmx: HtmxRequest,
+    user: CurrentUser,
+    id: String,
+) -> Result<RawHtml<String>> {
+    if !user.0.can_view_margins() {
+        return Ok(RawHtml(String::new()));
+    }
+
+    let margins = db
+        .run(move |conn| {
+            let margins = quotes::query::margins(conn, &id)?;
+            Result::Ok(margins)
+        })
+        .await?;
+
+    let template = Margins {
+        margins: &margins,
+        threshold: config::get().margin_warning_threshold,
+    };
+    Ok(RawHtml(htmx.render("Margins", template)))
 }
 
 #[get("/new")]
-async fn new() -> RawHtml<String> {
-    RawHtml(NewForm {}.to_string())
+async fn new(db: Db, htmx: HtmxRequest) -> Result<RawHtml<String>> {
+    let templates = templates::controller::all(&db).await?;
+    let template = NewForm {
+        templates: &templates,
+    };
+    Ok(RawHtml(htmx.render("New quote", template)))
 }
 
 #[post("/create", data = "<form>")]
-async fn create(db: Db, form: Form<Contextual<'_, NewQuoteForm>>) -> Result<HtmxResponder> {
+async fn create(
+    db: Db,
+    user: CurrentUser,
+    accept: AcceptJson,
+    htmx: HtmxRequest,
+    form: Form<Contextual<'_, NewQuoteForm>>,
+) -> Result<HtmxResponder> {
     match form.value {
         Some(ref quote_form) => {
             let quote_form = quote_form.clone();
             let quote = db
                 .run(move |conn| {
-                    let record = quotes::query::insert(conn, &quote_form)?;
-                    Result::Ok(record)
+                    let record = quotes::query::insert(conn, &user.0, &quote_form)?;
+                    // Read back with the total of the line items a template may have added
+                    quotes::query::read(conn, &record.id)
                 })
                 .await?;
 
+            let quote: QuotePresenter = quote.into();
+            if accept.0 {
+                return HtmxResponder::json(&quote);
+            }
+
+            if !htmx.0 {
+                return Ok(HtmxResponder::Redirect(Redirect::to("/quotes")));
+            }
+
             let content = Create {
-                quote: &quote.into(),
+                quote: &quote,
                 message: "Quote was successfully created.",
             }
             .to_string();
@@ -138,7 +204,19 @@ async fn create(db: Db, form: Form<Contextual<'_, NewQuoteForm>>) -> Result<Htmx
             Ok(HtmxResponder::Ok(content))
         }
         None => {
-            let template = NewFormWithErrors { form: &form };
+            if accept.0 {
+                return Ok(HtmxResponder::invalid(&form.context));
+            }
+
+            let templates = templates::controller::all(&db).await?;
+            let template = NewFormWithErrors {
+                form: &form,
+                templates: &templates,
+            };
+            if !htmx.0 {
+                return Ok(HtmxResponder::Ok(htmx.render("New quote", template)));
+            }
+
             let content = template.to_string();
             Ok(HtmxResponder::Retarget {
                 content,
@@ -150,7 +228,7 @@ async fn create(db: Db, form: Form<Contextual<'_, NewQuoteForm>>) -> Result<Htmx
 }
 
 #[get("/edit/<id>")]
-async fn edit(db: Db, id: String) -> Result<RawHtml<String>> {
+async fn edit(db: Db, accept: AcceptJson, htmx: HtmxRequest, id: String) -> Result<HtmxResponder> {
     let quote = db
         .run(move |conn| {
             let quote = quotes::query::read(conn, &id)?;
@@ -158,54 +236,85 @@ async fn edit(db: Db, id: String) -> Result<RawHtml<String>> {
         })
         .await?;
 
-    Ok(RawHtml(
-        EditForm {
-            quote: &quote.into(),
-        }
-        .to_string(),
-    ))
+    let quote: QuotePresenter = quote.into();
+    if accept.0 {
+        return HtmxResponder::json(&quote);
+    }
+
+    let template = EditForm { quote: &quote };
+    Ok(HtmxResponder::Ok(htmx.render("Edit quote", template)))
 }
 
 #[post("/update", data = "<form>")]
-async fn update(db: Db, form: Form<Contextual<'_, EditQuoteForm>>) -> Result<RawHtml<String>> {
+async fn update(
+    db: Db,
+    user: CurrentUser,
+    accept: AcceptJson,
+    htmx: HtmxRequest,
+    form: Form<Contextual<'_, EditQuoteForm>>,
+) -> Result<HtmxResponder> {
     match form.value {
         Some(ref quote_form) => {
             let quote_form = quote_form.clone();
             let quote = db
                 .run(move |conn| {
-                    let record = quotes::query::update(conn, &quote_form)?;
+                    let record = quotes::query::update(conn, &user.0, &quote_form)?;
                     Result::Ok(record)
                 })
                 .await?;
 
-            Ok(RawHtml(
-                Update {
-                    quote: &quote.into(),
-                    message: "Quote was successfully updated.",
-                }
-                .to_string(),
-            ))
+            let quote: QuotePresenter = quote.into();
+            if accept.0 {
+                return HtmxResponder::json(&quote);
+            }
+
+            if !htmx.0 {
+                return Ok(HtmxResponder::Redirect(Redirect::to("/quotes")));
+            }
+
+            let content = Update {
+                quote: &quote,
+                message: "Quote was successfully updated.",
+            }
+            .to_string();
+            Ok(HtmxResponder::Ok(content))
         }
         None => {
+            if accept.0 {
+                return Ok(HtmxResponder::invalid(&form.context));
+            }
+
             let template = EditFormWithErrors { form: &form };
-            let html = template.to_string();
-            Ok(RawHtml(html))
+            Ok(HtmxResponder::Ok(htmx.render("Edit quote", template)))
         }
     }
 }
 
 #[post("/delete", data = "<form>")]
-async fn delete(db: Db, form: Form<DeleteForm>) -> Result<RawHtml<String>> {
+async fn delete(
+    db: Db,
+    user: CurrentUser,
+    accept: AcceptJson,
+    htmx: HtmxRequest,
+    form: Form<DeleteForm>,
+) -> Result<HtmxResponder> {
     db.run(move |conn| {
-        quotes::query::delete(conn, &form.id)?;
+        quotes::query::delete(conn, &user.0, &form.id)?;
         Result::Ok(())
     })
     .await?;
 
-    Ok(RawHtml(
-        Flash {
-            message: "Quote was successfully destroyed.",
-        }
-        .to_string(),
-    ))
+    if accept.0 {
+        return Ok(HtmxResponder::Empty(Status::NoContent));
+    }
+
+    if !htmx.0 {
+        return Ok(HtmxResponder::Redirect(Redirect::to("/quotes")));
+    }
+
+    let content = Flash {
+        message: "Quote was successfully destroyed.",
+    }
+    .to_string();
+    Ok(HtmxResponder::Ok(content))
 }
@@ -1,10 +1,17 @@
-use crate::schema::quotes;
+use crate::{
+    audit_entries::model::Audited, forms::validate_start_date, schema::quotes, time::parse_date,
+};
 use currency_rs::Currency;
 use diesel::prelude::*;
 use diesel::sql_types::*;
-use time::OffsetDateTime;
+use rocket::serde::Serialize;
+use time::{Date, OffsetDateTime};
 use ulid::Ulid;
 
+pub(crate) const STATUS_DRAFT: &str = "draft";
+pub(crate) const STATUS_ACCEPTED: &str = "accepted";
+pub(crate) const STATUS_REJECTED: &str = "rejected";
+
 #[derive(Debug, QueryableByName)]
 pub struct QuoteWithTotal {
     #[diesel(sql_type = Text)]
@@ -17,15 +24,103 @@ pub struct QuoteWithTotal {
     pub created_at: OffsetDateTime,
     #[diesel(sql_type = TimestamptzSqlite)]
     pub updated_at: OffsetDateTime,
+    #[diesel(sql_type = Text)]
+    pub status: String,
+}
+
+#[derive(Debug, QueryableByName)]
+pub struct LineItemMargin {
+    #[diesel(sql_type = Text)]
+    pub line_item_date_id: String,
+    #[diesel(sql_type = diesel::sql_types::Date)]
+    pub date: Date,
+    #[diesel(sql_type = Text)]
+    pub name: String,
+    #[diesel(sql_type = Double)]
+    pub revenue: f64,
+    #[diesel(sql_type = Double)]
+    pub cost: f64,
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Margin {
+    pub revenue: f64,
+    pub cost: f64,
+}
+
+impl Margin {
+    pub fn profit(&self) -> Currency {
+        Currency::new_float(self.revenue - self.cost, None)
+    }
+
+    // Profit as a percentage of revenue
+    pub fn margin(&self) -> Option<f64> {
+        (self.revenue != 0f64).then(|| (self.revenue - self.cost) / self.revenue * 100f64)
+    }
+
+    // Profit as a percentage of cost
+    pub fn markup(&self) -> Option<f64> {
+        (self.cost != 0f64).then(|| (self.revenue - self.cost) / self.cost * 100f64)
+    }
+
+    pub fn margin_text(&self) -> String {
+        percent_text(self.margin())
+    }
+
+    pub fn markup_text(&self) -> String {
+        percent_text(self.markup())
+    }
+
+    pub fn is_below(&self, threshold: f64) -> bool {
+        self.margin().is_some_and(|margin| margin < threshold)
+    }
+
+    pub(crate) fn add(&mut self, other: Margin) {
+        self.revenue += other.revenue;
+        self.cost += other.cost;
+    }
+}
+
+fn percent_text(percent: Option<f64>) -> String {
+    match percent {
+        Some(percent) => format!("{percent:.1}%"),
+        None => String::from("—"),
+    }
 }
 
-#[derive(Debug, Insertable, Queryable)]
+#[derive(Debug)]
+pub struct LineItemDateMargins {
+    pub line_item_date_id: String,
+    pub date: Date,
+    pub total: Margin,
+    pub line_items: Vec<(String, Margin)>,
+}
+
+#[derive(Debug, Default)]
+pub struct QuoteMargins {
+    pub total: Margin,
+    pub line_item_dates: Vec<LineItemDateMargins>,
+}
+
+#[derive(Debug, Insertable, Queryable, Serialize)]
 #[diesel(table_name = quotes)]
+#[serde(crate = "rocket::serde")]
 pub struct Quote {
     pub id: String,
     pub name: String,
+    #[serde(skip)]
     pub created_at: OffsetDateTime,
+    #[serde(skip)]
     pub updated_at: OffsetDateTime,
+    pub status: String,
+}
+
+impl Audited for Quote {
+    const ENTITY: &'static str = "quote";
+
+    fn entity_id(&self) -> &str {
+        &self.id
+    }
 }
 
 impl From<&NewQuoteForm> for Quote {
@@ -35,6 +130,7 @@ impl From<&NewQuoteForm> for Quote {
             name: value.name.clone(),
             created_at: OffsetDateTime::now_utc(),
             updated_at: OffsetDateTime::now_utc(),
+            status: String::from(STATUS_DRAFT),
         }
     }
 }
@@ -46,6 +142,7 @@ impl From<&EditQuoteForm> for Quote {
             name: value.name.clone(),
             created_at: OffsetDateTime::now_utc(),
             updated_at: OffsetDateTime::now_utc(),
+            status: String::from(STATUS_DRAFT),
         }
     }
 }
@@ -54,6 +151,18 @@ impl From<&EditQuoteForm> for Quote {
 pub struct NewQuoteForm {
     #[field(validate = len(1..).or_else(msg!("Please enter a name")))]
     pub(crate) name: String,
+    pub(crate) template_id: Option<String>,
+    #[field(validate = validate_start_date(&self.template_id))]
+    pub(crate) start_date: Option<String>,
+}
+
+impl NewQuoteForm {
+    // Template to fill the new quote with and the date of its first day
+    pub(crate) fn template(&self) -> Option<(&str, Date)> {
+        let template_id = self.template_id.as_deref().filter(|id| !id.is_empty())?;
+        let start_date = self.start_date.as_deref()?;
+        Some((template_id, parse_date(start_date)))
+    }
 }
 
 #[derive(Clone, Debug, FromForm)]
@@ -64,11 +173,14 @@ pub struct EditQuoteForm {
     pub(crate) name: String,
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, Serialize)]
+#[serde(crate = "rocket::serde")]
 pub struct QuotePresenter {
     pub id: Option<String>,
     pub name: String,
+    #[serde(serialize_with = "crate::currency::serialize")]
     pub total: Currency,
+    pub status: String,
 }
 
 impl QuotePresenter {
@@ -82,6 +194,19 @@ impl QuotePresenter {
     pub fn dom_id(&self) -> String {
         format!("quote_{}", self.id())
     }
+
+    // Accepted and declined quotes are kept as the customer saw them
+    pub fn is_locked(&self) -> bool {
+        self.status != STATUS_DRAFT
+    }
+
+    pub fn status_name(&self) -> &str {
+        match self.status.as_str() {
+            STATUS_ACCEPTED => "Accepted",
+            STATUS_REJECTED => "Declined",
+            _ => "Draft",
+        }
+    }
 }
 
 impl Default for QuotePresenter {
@@ -90,6 +215,7 @@ impl Default for QuotePresenter {
             id: None,
             name: String::from(""),
             total: Currency::new_float(0f64, None),
+            status: String::from(STATUS_DRAFT),
         }
     }
 }
@@ -100,6 +226,7 @@ impl From<Quote> for QuotePresenter {
             id: Some(value.id),
             name: value.name,
             total: Currency::new_float(0f64, None),
+            status: value.status,
         }
     }
 }
@@ -110,6 +237,7 @@ impl From<QuoteWithTotal> for QuotePresenter {
             id: Some(value.id),
             name: value.name,
             total: value.total,
+            status: value.status,
         }
     }
 }
@@ -120,6 +248,7 @@ impl From<NewQuoteForm> for QuotePresenter {
             id: None,
             name: value.name,
             total: Currency::new_float(0f64, None),
+            status: String::from(STATUS_DRAFT),
         }
     }
 }
@@ -130,6 +259,7 @@ impl From<EditQuoteForm> for QuotePresenter {
             id: Some(value.id),
             name: value.name,
             total: Currency::new_float(0f64, None),
+            status: String::from(STATUS_DRAFT),
         }
     }
 }
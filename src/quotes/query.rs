@@ -1,7 +1,26 @@
 use crate::{
-    Result, line_item_dates,
-    quotes::model::{EditQuoteForm, NewQuoteForm, Quote, QuoteWithTotal},
+    Result, acceptances, attachments,
+    audit_entries::{
+        self,
+        model::{ACTION_CREATE, ACTION_DELETE, ACTION_UPDATE, AuditEntry},
+    },
+    comments, emails,
+    error::Locked,
+    line_item_dates,
+    line_items::model::LineItemPresenter,
+    quotes::model::{
+        EditQuoteForm, LineItemDateMargins, LineItemMargin, Margin, NewQuoteForm, Quote,
+        QuoteMargins, QuoteWithTotal, STATUS_DRAFT,
+    },
     schema::quotes,
+    share_links, templates,
+    users::model::User,
+    webhooks::{
+        self,
+        model::{
+            LINE_ITEM_CREATED, LINE_ITEM_DATE_CREATED, QUOTE_CREATED, QUOTE_DELETED, QUOTE_UPDATED,
+        },
+    },
 };
 use diesel::prelude::*;
 
@@ -10,6 +29,28 @@ pub(crate) fn all(connection: &mut SqliteConnection) -> Result<Vec<Quote>> {
     Ok(records)
 }
 
+// Every quote with the total of its line items, for listings and JSON clients
+pub(crate) fn all_with_totals(connection: &mut SqliteConnection) -> Result<Vec<QuoteWithTotal>> {
+    // language=SQL
+    let sql = r#"
+      select
+        q.id,
+        q.name,
+        (select
+            coalesce(sum(quantity * li.unit_price), 0)
+          from line_items li
+            inner join line_item_dates lid on li.line_item_date_id = lid.id
+          where lid.quote_id = q.id) as total,
+        q.created_at,
+        q.updated_at,
+        q.status
+      from quotes q
+      order by q.id
+    "#;
+    let records = diesel::dsl::sql_query(sql).load(connection)?;
+    Ok(records)
+}
+
 pub(crate) fn read<S: AsRef<str>>(
     connection: &mut SqliteConnection,
     id: S,
@@ -32,7 +73,8 @@ fn read_from_connection<S: AsRef<str>>(
             inner join line_item_dates lid on li.line_item_date_id = lid.id
           where lid.quote_id = q.id) as total,
         q.created_at,
-        q.updated_at
+        q.updated_at,
+        q.status
       from quotes q
       where q.id = ?
     "#;
@@ -42,6 +84,18 @@ fn read_from_connection<S: AsRef<str>>(
     Ok(record)
 }
 
+// Fails with `Locked` once the customer has accepted or declined the quote
+pub(crate) fn ensure_editable<S: AsRef<str>>(connection: &mut SqliteConnection, id: S) -> Result {
+    let status: String = quotes::table
+        .find(id.as_ref())
+        .select(quotes::status)
+        .get_result(connection)?;
+    if status != STATUS_DRAFT {
+        return Err(Locked.into());
+    }
+    Ok(())
+}
+
 pub(crate) fn from_line_item_date_id<S: AsRef<str>>(
     connection: &mut SqliteConnection,
     id: S,
@@ -57,7 +111,8 @@ pub(crate) fn from_line_item_date_id<S: AsRef<str>>(
             inner join line_item_dates lid2 on li.line_item_date_id = lid2.id
           where lid2.quote_id = q.id) as total,
         q.created_at,
-        q.updated_at
+        q.updated_at,
+        q.status
       from line_item_dates lid
         inner join quotes q on lid.quote_id = q.id
       where lid.id = ?
@@ -68,47 +123,187 @@ pub(crate) fn from_line_item_date_id<S: AsRef<str>>(
     Ok(record)
 }
 
-pub(crate) fn insert(connection: &mut SqliteConnection, form: &NewQuoteForm) -> Result<Quote> {
+// Revenue and internal cost per line item, summed per line item date and for the whole quote
+pub(crate) fn margins<S: AsRef<str>>(
+    connection: &mut SqliteConnection,
+    id: S,
+) -> Result<QuoteMargins> {
+    // language=SQL
+    let sql = r#"
+      select
+        lid.id as line_item_date_id,
+        lid."date",
+        li.name,
+        li.quantity * li.unit_price as revenue,
+        li.quantity * li.cost_price as cost
+      from line_items li
+        inner join line_item_dates lid on li.line_item_date_id = lid.id
+      where lid.quote_id = ?
+      order by lid."date", lid.start_time, li.position, li.created_at
+    "#;
+    let records: Vec<LineItemMargin> = diesel::dsl::sql_query(sql)
+        .bind::<diesel::sql_types::Text, _>(id.as_ref())
+        .load(connection)?;
+
+    let mut margins = QuoteMargins::default();
+    for record in records {
+        let margin = Margin {
+            revenue: record.revenue,
+            cost: record.cost,
+        };
+        margins.total.add(margin);
+        match margins.line_item_dates.last_mut() {
+            Some(line_item_date)
+                if line_item_date.line_item_date_id == record.line_item_date_id =>
+            {
+                line_item_date.total.add(margin);
+                line_item_date.line_items.push((record.name, margin));
+            }
+            _ => margins.line_item_dates.push(LineItemDateMargins {
+                line_item_date_id: record.line_item_date_id,
+                date: record.date,
+                total: margin,
+                line_items: vec![(record.name, margin)],
+            }),
+        }
+    }
+
+    Ok(margins)
+}
+
+pub(crate) fn insert(
+    connection: &mut SqliteConnection,
+    actor: &User,
+    form: &NewQuoteForm,
+) -> Result<Quote> {
     let record: Quote = form.into();
 
-    diesel::dsl::insert_into(quotes::table)
-        .values(&record)
-        .execute(connection)?;
+    connection.transaction::<_, crate::error::AppError, _>(|tx| {
+        diesel::dsl::insert_into(quotes::table)
+            .values(&record)
+            .execute(tx)?;
+
+        let entry = AuditEntry::new(actor, ACTION_CREATE, &record.id, None, Some(&record))?;
+        audit_entries::query::insert(tx, &entry)?;
+        webhooks::query::enqueue(tx, QUOTE_CREATED, &record.id, &record)?;
+
+        let Some((template_id, start)) = form.template() else {
+            return Ok(());
+        };
+        let (dates, items) = templates::query::instantiate(tx, template_id, &record.id, start)?;
+        for date in &dates {
+            let entry = AuditEntry::new(actor, ACTION_CREATE, &record.id, None, Some(date))?;
+            audit_entries::query::insert(tx, &entry)?;
+            webhooks::query::enqueue(tx, LINE_ITEM_DATE_CREATED, &record.id, date)?;
+        }
+        for item in &items {
+            let entry = AuditEntry::new(actor, ACTION_CREATE, &record.id, None, Some(item))?;
+            audit_entries::query::insert(tx, &entry)?;
+            webhooks::query::enqueue(
+                tx,
+                LINE_ITEM_CREATED,
+                &record.id,
+                &LineItemPresenter::from(item),
+            )?;
+        }
+        Ok(())
+    })?;
 
     Ok(record)
 }
 
 pub(crate) fn update(
     connection: &mut SqliteConnection,
+    actor: &User,
     form: &EditQuoteForm,
 ) -> Result<QuoteWithTotal> {
     let record: Quote = form.into();
 
-    diesel::dsl::update(quotes::table)
-        .set((
-            quotes::name.eq(&record.name),
-            quotes::updated_at.eq(&record.updated_at),
-        ))
-        .filter(quotes::id.eq(&record.id))
-        .execute(connection)?;
+    connection.transaction::<_, crate::error::AppError, _>(|tx| {
+        ensure_editable(tx, &record.id)?;
+        let before: Quote = quotes::table.find(&record.id).get_result(tx)?;
+
+        diesel::dsl::update(quotes::table)
+            .set((
+                quotes::name.eq(&record.name),
+                quotes::updated_at.eq(&record.updated_at),
+            ))
+            .filter(quotes::id.eq(&record.id))
+            .execute(tx)?;
+
+        let entry = AuditEntry::new(
+            actor,
+            ACTION_UPDATE,
+            &record.id,
+            Some(&before),
+            Some(&record),
+        )?;
+        audit_entries::query::insert(tx, &entry)?;
+        webhooks::query::enqueue(tx, QUOTE_UPDATED, &record.id, &record)
+    })?;
 
     read_from_connection(connection, &record.id)
 }
 
 pub(crate) fn delete<S: AsRef<str>>(
     connection: &mut SqliteConnection,
+    actor: &User,
     id: S,
 ) -> Result<QuoteWithTotal> {
     let record = read_from_connection(connection, &id)?;
 
-    _ = connection.transaction::<_, _, _>(|tx| {
+    let attachment_ids = connection.transaction::<_, crate::error::AppError, _>(|tx| {
+        ensure_editable(tx, &id)?;
+        let before: Quote = quotes::table.find(id.as_ref()).get_result(tx)?;
+
+        let attachment_ids = attachments::query::delete_all_for_quote(tx, &id)?;
+        comments::query::delete_all_for_quote(tx, &id)?;
+        acceptances::query::delete_all_for_quote(tx, &id)?;
+        emails::query::delete_all_for_quote(tx, &id)?;
+        share_links::query::delete_all_for_quote(tx, &id)?;
         line_item_dates::query::delete_all_for_quote(tx, &id)?;
 
         _ = diesel::dsl::delete(quotes::table)
             .filter(quotes::id.eq(&id.as_ref()))
             .execute(tx)?;
 
-        Ok::<(), crate::error::AppError>(())
-    });
+        let entry = AuditEntry::new(actor, ACTION_DELETE, &before.id, Some(&before), None)?;
+        audit_entries::query::insert(tx, &entry)?;
+        webhooks::query::enqueue(tx, QUOTE_DELETED, &before.id, &before)?;
+
+        Ok(attachment_ids)
+    })?;
+
+    // The rows are gone, a file left behind is only wasted space
+    for attachment_id in attachment_ids {
+        _ = std::fs::remove_file(attachments::model::path(&attachment_id));
+    }
     Ok(record)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        quotes::model::{STATUS_ACCEPTED, STATUS_REJECTED},
+        schema::quotes,
+    };
+    use diesel::prelude::*;
+
+    // One of the quotes added by the migrations
+    const QUOTE_ID: &str = "01HE2X4FKPDTVHHB6C2HZD5Z53";
+
+    #[test]
+    fn ensure_editable() {
+        let mut connection = crate::test_connection();
+        assert!(super::ensure_editable(&mut connection, QUOTE_ID).is_ok());
+
+        for status in [STATUS_ACCEPTED, STATUS_REJECTED] {
+            diesel::update(quotes::table.find(QUOTE_ID))
+                .set(quotes::status.eq(status))
+                .execute(&mut connection)
+                .unwrap();
+            let error = super::ensure_editable(&mut connection, QUOTE_ID).unwrap_err();
+            assert!(format!("{error:?}").contains("can no longer be changed"));
+        }
+    }
+}
@@ -1,9 +1,21 @@
 use crate::{
+    acceptances::view::AcceptanceLoader,
+    attachments::view::AttachmentsLoader,
+    audit_entries::view::ActivityLoader,
+    comments::view::ThreadLoader,
+    emails::view::EmailsLoader,
     forms::css_for_field,
     layout::Flash,
-    line_item_dates::{model::LineItemDatePresenter, view::LineItemDate},
+    line_item_dates::{
+        model::LineItemDatePresenter,
+        view::{DateNav, LineItemDate},
+    },
     line_items::model::LineItemPresenter,
-    quotes::model::{EditQuoteForm, NewQuoteForm, QuotePresenter},
+    quotes::model::{EditQuoteForm, NewQuoteForm, QuoteMargins, QuotePresenter},
+    rocket_ext::QUOTE_CHANGED,
+    share_links::view::SharingLoader,
+    templates::{model::TemplatePresenter, view::SaveForm},
+    time::long_form,
 };
 use rocket::form::{Contextual, Form};
 use std::collections::HashMap;
@@ -14,6 +26,7 @@ markup::define! {
             div[id = "header", class = "flex flex-wrap gap-3 justify-between mt-4 mb-8"] {
                 h1[class = "text-header text-[2rem]/[1.1] box-border m-0 p-0 font-bold"] {"Quotes"}
                 a[class = "button button-prime",
+                    href = "/quotes/new",
                     "hx-get" = "/quotes/new",
                     "hx-target" = "#quote_new",
                     "hx-trigger" = "click",
@@ -28,6 +41,7 @@ markup::define! {
                         "You don't have any quotes yet!"
                     }
                     a[class = "button button-prime",
+                        href = "/quotes/new",
                         "hx-get" = "/quotes/new",
                         "hx-target" = "quote_new",
                         "hx-trigger" = "click"] { "Add quote" }
@@ -46,20 +60,27 @@ markup::define! {
                     "hx-boost" = "true",
                     "hx-push-url" = "true",
                     "hx-history" = "false"] { @quote.name }
-                div[class = "flex flex-auto grow-0 shrink-0 self-start gap-2"] {
-                    form["hx-post" = "/quotes/delete",
-                         "hx-target" = {format!("#{}", quote.dom_id())},
-                         "hx-swap" = "delete"] {
-                        input[id = "quote_id",
-                            name = "id",
-                            "type" = "hidden",
-                            value = &quote.id()] {}
-                        button[class = "button button-light", "type" = "submit"] {"Delete"}
+                @if quote.is_locked() {
+                    span[class = "text-[0.875rem] font-bold"] { @quote.status_name() }
+                } else {
+                    div[class = "flex flex-auto grow-0 shrink-0 self-start gap-2"] {
+                        form[action = "/quotes/delete",
+                             method = "post",
+                             "hx-post" = "/quotes/delete",
+                             "hx-target" = {format!("#{}", quote.dom_id())},
+                             "hx-swap" = "delete"] {
+                            input[id = "quote_id",
+                                name = "id",
+                                "type" = "hidden",
+                                value = &quote.id()] {}
+                            button[class = "button button-light", "type" = "submit"] {"Delete"}
+                        }
+                        a[class = "button button-light",
+                            href = {format!("/quotes/edit/{}", quote.id())},
+                            "hx-get" = {format!("/quotes/edit/{}", quote.id())},
+                            "hx-target" = {format!("#{}", quote.dom_id())},
+                            "hx-trigger" = "click"] { "Edit" }
                     }
-                    a[class = "button button-light",
-                        "hx-get" = {format!("/quotes/edit/{}", quote.id())},
-                        "hx-target" = {format!("#{}", quote.dom_id())},
-                        "hx-trigger" = "click"] { "Edit" }
                 }
             }
         }
@@ -78,14 +99,43 @@ markup::define! {
                     @quote.name
                 }
 
-                a[class = "button button-prime",
-                    "hx-get" = {format!("/line_item_dates/new/{}", quote.id())},
-                    "hx-target" = "#line_item_date_new",
-                    "hx-trigger" = "click",
-                    "hx-swap" = "innerHTML"] { "New date" }
+                div[class = "flex gap-2"] {
+                    @if !line_item_dates.is_empty() {
+                        a[class = "button button-light",
+                            href = {format!("/quotes/{}/calendar.ics", quote.id())}] { "Export to calendar" }
+                    }
+                    @if !quote.is_locked() {
+                        @if !line_item_dates.is_empty() {
+                            a[class = "button button-light",
+                                href = {format!("/line_item_dates/reschedule/{}", quote.id())},
+                                "hx-get" = {format!("/line_item_dates/reschedule/{}", quote.id())},
+                                "hx-target" = "#line_item_date_reschedule",
+                                "hx-trigger" = "click",
+                                "hx-swap" = "outerHTML"] { "Reschedule" }
+                        }
+                        a[class = "button button-light",
+                            href = {format!("/line_item_dates/repeat/{}", quote.id())},
+                            "hx-get" = {format!("/line_item_dates/repeat/{}", quote.id())},
+                            "hx-target" = "#line_item_date_recurrence",
+                            "hx-trigger" = "click",
+                            "hx-swap" = "outerHTML"] { "Repeat" }
+                        a[class = "button button-prime",
+                            href = {format!("/line_item_dates/new/{}", quote.id())},
+                            "hx-get" = {format!("/line_item_dates/new/{}", quote.id())},
+                            "hx-target" = "#line_item_date_new",
+                            "hx-trigger" = "click",
+                            "hx-swap" = "innerHTML"] { "New date" }
+                    }
+                }
+            }
+            @if quote.is_locked() {
+                @AcceptanceLoader { quote_id: &quote.id() }
             }
             div[id = "line_item_date_new"] {}
+            div[id = "line_item_date_reschedule"] {}
+            div[id = "line_item_date_recurrence"] {}
 
+            @DateNav { line_item_dates }
             div[id = "line_item_dates"] {
                 @for line_item_date in *line_item_dates {
                     @let empty = Vec::new();
@@ -93,6 +143,13 @@ markup::define! {
                     @LineItemDate { line_item_date, line_items }
                 }
             }
+
+            @SaveForm { quote }
+            @SharingLoader { quote_id: &quote.id() }
+            @EmailsLoader { quote_id: &quote.id() }
+            @AttachmentsLoader { quote_id: &quote.id() }
+            @ThreadLoader { quote_id: &quote.id() }
+            @ActivityLoader { quote_id: &quote.id(), oob: false }
         }
 
         @InitialFooter { quote }
@@ -101,6 +158,8 @@ markup::define! {
     EditForm<'a>(quote: &'a QuotePresenter) {
         div[id = &quote.dom_id()] {
             form[id = format!("form_{}", quote.id()),
+                action = "/quotes/update",
+                method = "post",
                 "hx-post" = "/quotes/update",
                 "hx-target" = {format!("#{}", quote.dom_id())},
                 "hx-swap" = "outerHTML",
@@ -124,6 +183,7 @@ markup::define! {
                         value = &quote.name] {}
                 }
                 a[class = "button button-light",
+                    href = {format!("/quotes/{}", quote.id())},
                     "hx-get" = {format!("/quotes/{}", quote.id())},
                     "hx-target" = {format!("#{}", quote.dom_id())},
                     "hx-trigger" = "click"] { "Cancel" }
@@ -142,6 +202,8 @@ markup::define! {
         @let dom_id = format!("quote_{}", id);
         div[id = &dom_id] {
             form[id = format!("form_{}", id),
+                action = "/quotes/update",
+                method = "post",
                 "hx-post" = "/quotes/update",
                 "hx-target" = {format!("#{}", dom_id)},
                 "hx-swap" = "outerHTML",
@@ -172,6 +234,7 @@ markup::define! {
                         value = &name] {}
                 }
                 a[class = "button button-light",
+                    href = {format!("/quotes/{}", id)},
                     "hx-get" = {format!("/quotes/{}", id)},
                     "hx-target" = {format!("#{}", dom_id)},
                     "hx-trigger" = "click"] { "Cancel" }
@@ -184,9 +247,11 @@ markup::define! {
         }
     }
 
-    NewForm() {
+    NewForm<'a>(templates: &'a Vec<TemplatePresenter>) {
         div[id = "quote_new"] {
             form[id = "form_new",
+                action = "/quotes/create",
+                method = "post",
                 "hx-post" = "/quotes/create",
                 "hx-target" = "#quotes_empty",
                 "hx-swap" = "afterend",
@@ -204,6 +269,7 @@ markup::define! {
                         placeholder = "Name of your quote",
                         "type" = "text"] {}
                 }
+                @TemplateFields { templates, template_id: "", start_date: "" }
                 a[class = "button button-light",
                     "_" = "on click remove #form_new"] { "Cancel" }
                 input[name = "commit",
@@ -215,10 +281,14 @@ markup::define! {
         }
     }
 
-    NewFormWithErrors<'a, 'r>(form: &'a Form<Contextual<'r, NewQuoteForm>>) {
+    NewFormWithErrors<'a, 'r>(form: &'a Form<Contextual<'r, NewQuoteForm>>, templates: &'a Vec<TemplatePresenter>) {
         @let name = form.context.field_value("name").unwrap_or("");
+        @let template_id = form.context.field_value("template_id").unwrap_or("");
+        @let start_date = form.context.field_value("start_date").unwrap_or("");
         div[id = "quote_new"] {
             form[id = "form_new",
+                action = "/quotes/create",
+                method = "post",
                 "hx-post" = "/quotes/create",
                 "hx-target" = "#quotes_empty",
                 "hx-swap" = "afterend",
@@ -244,6 +314,7 @@ markup::define! {
                         "type" = "text",
                         value = &name] {}
                 }
+                @TemplateFields { templates, template_id, start_date }
                 a[class = "button button-light",
                     "_" = "on click remove #form_new"] { "Cancel" }
                 input[name = "commit",
@@ -255,18 +326,87 @@ markup::define! {
         }
     }
 
+    // Optional template to start the new quote from, with the date its day 1 falls on
+    TemplateFields<'a>(templates: &'a Vec<TemplatePresenter>, template_id: &'a str, start_date: &'a str) {
+        @if !templates.is_empty() {
+            div[class = "flex flex-wrap gap-3 w-full"] {
+                label[class = "visually-hidden", "for" = "quote_template_id"] { "Template" }
+                select[id = "quote_template_id", name = "template_id", class = "form-input [flex:1]"] {
+                    option[value = ""] { "Blank quote" }
+                    @for template in *templates {
+                        option[value = &template.id, selected = *template_id == template.id] {
+                            @template.name " (" @template.description() ")"
+                        }
+                    }
+                }
+                label[class = "visually-hidden", "for" = "quote_start_date"] { "Start date" }
+                input[id = "quote_start_date",
+                    name = "start_date",
+                    class = "form-input [flex:0_0_12rem]",
+                    "type" = "date",
+                    title = "Date of day 1 of the template",
+                    value = start_date] {}
+            }
+        }
+    }
+
     Footer<'a>(quote: &'a QuotePresenter) {
-        footer[class = "fixed bottom-0 w-full py-4 text-[1.25rem] font-bold bg-white shadow-[2px_4px_10px_hsl(0,0%,0%,0.1)]"] {
-            div[class = "flex items-center justify-between w-full px-4 mx-auto max-w-[60rem]"] {
-                div { "Total:" }
-                div { @quote.total.format() }
+        div[class = "flex items-center justify-between w-full px-4 mx-auto max-w-[60rem]"] {
+            div { "Total:" }
+            div { @quote.total.format() }
+        }
+    }
+
+    Margins<'a>(margins: &'a QuoteMargins, threshold: f64) {
+        @let total = &margins.total;
+        div {
+            div[class = "flex flex-wrap items-center gap-4"] {
+                span { "Margin: " @total.margin_text() }
+                span { "Markup: " @total.markup_text() }
+                span { "Profit: " @total.profit().format() }
+                @if total.is_below(*threshold) {
+                    span[class = "font-bold text-primary"] { {format!("Margin is below {threshold}%")} }
+                }
+                a[class = "ml-auto cursor-pointer",
+                    "_" = "on click toggle .hidden on #quote_margins_details"] { "Details" }
+            }
+            div[id = "quote_margins_details", class = "hidden max-h-[50vh] overflow-y-auto mt-2 text-[0.875rem]"] {
+                @for line_item_date in &margins.line_item_dates {
+                    div[class = "flex gap-4 mt-2 font-bold"] {
+                        div[class = "flex-1"] { @long_form(line_item_date.date) }
+                        div[class = "flex-[0_0_7rem]"] { @line_item_date.total.margin_text() }
+                        div[class = "flex-[0_0_7rem]"] { @line_item_date.total.markup_text() }
+                        div[class = "flex-[0_0_9rem]"] { @line_item_date.total.profit().format() }
+                    }
+                    @for (name, margin) in &line_item_date.line_items {
+                        div[class = {if margin.is_below(*threshold) { "flex gap-4 text-primary" } else { "flex gap-4" }}] {
+                            div[class = "flex-1 pl-4"] { @name }
+                            div[class = "flex-[0_0_7rem]"] { @margin.margin_text() }
+                            div[class = "flex-[0_0_7rem]"] { @margin.markup_text() }
+                            div[class = "flex-[0_0_9rem]"] { @margin.profit().format() }
+                        }
+                    }
+                }
             }
         }
     }
 
+    // Only the total is swapped with the line items, the margins and capacity warnings reload on their own
     InitialFooter<'a>(quote: &'a QuotePresenter) {
-        div[id = "quote_total_footer"] {
-            @Footer{ quote }
+        footer[class = "fixed bottom-0 w-full py-4 text-[1.25rem] font-bold bg-white shadow-[2px_4px_10px_hsl(0,0%,0%,0.1)]"] {
+            div[id = "quote_resource_conflicts",
+                class = "w-full px-4 mx-auto max-w-[60rem] text-[1rem] font-normal",
+                "hx-get" = {format!("/resources/conflicts/{}", quote.id())},
+                "hx-trigger" = {format!("load, {QUOTE_CHANGED} from:body")},
+                "hx-swap" = "innerHTML"] {}
+            div[id = "quote_total_footer"] {
+                @Footer{ quote }
+            }
+            div[id = "quote_margins",
+                class = "w-full px-4 mx-auto max-w-[60rem] text-[1rem] font-normal",
+                "hx-get" = {format!("/quotes/margins/{}", quote.id())},
+                "hx-trigger" = {format!("load, {QUOTE_CHANGED} from:body")},
+                "hx-swap" = "innerHTML"] {}
         }
     }
 
@@ -274,6 +414,7 @@ markup::define! {
         div[id = "quote_total_footer", "hx-swap-oob" = "true"] {
             @Footer{ quote }
         }
+        @ActivityLoader { quote_id: &quote.id(), oob: true }
     }
 
     Create<'a>(quote: &'a QuotePresenter, message: &'a str) {
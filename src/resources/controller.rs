@@ -0,0 +1,121 @@
+use crate::{
+    Db, Result,
+    layout::Layout,
+    resources::{
+        self,
+        model::{DeleteForm, EditResourceForm, ResourceForm, ResourcePresenter},
+        view::*,
+    },
+    rocket_ext::HtmxResponder,
+};
+use rocket::{
+    fairing::AdHoc,
+    form::{Contextual, Form},
+    response::{Redirect, content::RawHtml},
+};
+
+pub(crate) fn stage() -> AdHoc {
+    AdHoc::on_ignite("Resource Stage", |rocket| async {
+        rocket.mount(
+            "/resources",
+            routes![index, create, update, delete, options, conflicts],
+        )
+    })
+}
+
+#[get("/")]
+async fn index(db: Db) -> Result<RawHtml<String>> {
+    let content = render(&db, Vec::new()).await?;
+    Ok(RawHtml(content))
+}
+
+#[post("/create", data = "<form>")]
+async fn create(db: Db, form: Form<Contextual<'_, ResourceForm>>) -> Result<HtmxResponder> {
+    let Some(resource_form) = form.value.clone() else {
+        let content = render(&db, errors(&form.context)).await?;
+        return Ok(HtmxResponder::Ok(content));
+    };
+
+    db.run(move |conn| resources::query::insert(conn, &resource_form))
+        .await?;
+
+    Ok(HtmxResponder::Redirect(Redirect::to(uri!("/resources"))))
+}
+
+#[post("/update", data = "<form>")]
+async fn update(db: Db, form: Form<Contextual<'_, EditResourceForm>>) -> Result<HtmxResponder> {
+    let Some(resource_form) = form.value.clone() else {
+        let content = render(&db, errors(&form.context)).await?;
+        return Ok(HtmxResponder::Ok(content));
+    };
+
+    db.run(move |conn| resources::query::update(conn, &resource_form))
+        .await?;
+
+    Ok(HtmxResponder::Redirect(Redirect::to(uri!("/resources"))))
+}
+
+#[post("/delete", data = "<form>")]
+async fn delete(db: Db, form: Form<DeleteForm>) -> Result<Redirect> {
+    let id = form.into_inner().id;
+    db.run(move |conn| resources::query::delete(conn, &id))
+        .await?;
+
+    Ok(Redirect::to(uri!("/resources")))
+}
+
+// Options of the resource select of the line item forms
+#[get("/options?<selected>")]
+async fn options(db: Db, selected: Option<&str>) -> Result<RawHtml<String>> {
+    let resources = all(&db).await?;
+    let template = Options {
+        resources: &resources,
+        selected: selected.unwrap_or(""),
+    };
+    Ok(RawHtml(template.to_string()))
+}
+
+// Warnings about the resources of a quote booked beyond their capacity, loaded in its footer
+#[get("/conflicts/<quote_id>")]
+async fn conflicts(db: Db, quote_id: String) -> Result<RawHtml<String>> {
+    let conflicts = db
+        .run(move |conn| resources::query::conflicts(conn, &quote_id))
+        .await?;
+    let template = Conflicts {
+        conflicts: &conflicts,
+    };
+    Ok(RawHtml(template.to_string()))
+}
+
+fn errors(context: &rocket::form::Context<'_>) -> Vec<String> {
+    context
+        .errors()
+        .map(|error| error.to_string())
+        .collect::<Vec<String>>()
+}
+
+async fn all(db: &Db) -> Result<Vec<ResourcePresenter>> {
+    db.run(move |conn| {
+        let records = resources::query::all(conn)?
+            .into_iter()
+            .map(|record| record.into())
+            .collect::<Vec<ResourcePresenter>>();
+        Result::Ok(records)
+    })
+    .await
+}
+
+async fn render(db: &Db, errors: Vec<String>) -> Result<String> {
+    let resources = all(db).await?;
+
+    let template = Layout {
+        head: markup::new! {
+            title { "Resources" }
+        },
+        body: Index {
+            resources: &resources,
+            errors: &errors,
+        },
+    };
+    Ok(template.to_string())
+}
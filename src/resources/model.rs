@@ -0,0 +1,98 @@
+use crate::{
+    forms::validate_quantity,
+    quantity::{format_quantity, parse_quantity},
+    schema::resources,
+    time::long_form,
+};
+use diesel::prelude::*;
+use diesel::sql_types::*;
+use time::{Date, OffsetDateTime};
+use ulid::Ulid;
+
+// Items or staff available in limited numbers, booked by the line items using them
+#[derive(Debug, Insertable, Queryable, Selectable)]
+#[diesel(table_name = resources)]
+pub(crate) struct Resource {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) capacity: f64,
+    pub(crate) created_at: OffsetDateTime,
+    pub(crate) updated_at: OffsetDateTime,
+}
+
+impl From<&ResourceForm> for Resource {
+    fn from(value: &ResourceForm) -> Self {
+        Resource {
+            id: Ulid::new().to_string(),
+            name: value.name.trim().to_string(),
+            capacity: parse_quantity(&value.capacity),
+            created_at: OffsetDateTime::now_utc(),
+            updated_at: OffsetDateTime::now_utc(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, FromForm)]
+pub struct ResourceForm {
+    #[field(validate = len(1..).or_else(msg!("Please enter a name")))]
+    pub(crate) name: String,
+    #[field(validate = validate_quantity())]
+    pub(crate) capacity: String,
+}
+
+#[derive(Clone, Debug, FromForm)]
+pub struct EditResourceForm {
+    #[field(validate = len(1..))]
+    pub(crate) id: String,
+    #[field(validate = len(1..).or_else(msg!("Please enter a name")))]
+    pub(crate) name: String,
+    #[field(validate = validate_quantity())]
+    pub(crate) capacity: String,
+}
+
+#[derive(Debug, FromForm)]
+pub(crate) struct DeleteForm {
+    pub(crate) id: String,
+}
+
+#[derive(Debug)]
+pub struct ResourcePresenter {
+    pub id: String,
+    pub name: String,
+    pub capacity: String,
+}
+
+impl From<Resource> for ResourcePresenter {
+    fn from(value: Resource) -> Self {
+        ResourcePresenter {
+            id: value.id,
+            name: value.name,
+            capacity: format_quantity(value.capacity),
+        }
+    }
+}
+
+// A resource booked beyond its capacity on a date of a quote, counting the line items of all quotes
+#[derive(Debug, QueryableByName)]
+pub struct ResourceConflict {
+    #[diesel(sql_type = diesel::sql_types::Date)]
+    pub date: Date,
+    #[diesel(sql_type = Text)]
+    pub name: String,
+    #[diesel(sql_type = Double)]
+    pub capacity: f64,
+    #[diesel(sql_type = Double)]
+    pub booked: f64,
+}
+
+impl ResourceConflict {
+    pub fn message(&self) -> String {
+        format!(
+            "{} on {}: {} booked for a capacity of {}",
+            self.name,
+            long_form(self.date),
+            format_quantity(self.booked),
+            format_quantity(self.capacity)
+        )
+    }
+}
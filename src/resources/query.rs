@@ -0,0 +1,180 @@
+use crate::{
+    Result,
+    quantity::parse_quantity,
+    quotes::model::STATUS_REJECTED,
+    resources::model::{EditResourceForm, Resource, ResourceConflict, ResourceForm},
+    schema::{line_items, resources, template_line_items},
+};
+use diesel::prelude::*;
+use time::OffsetDateTime;
+
+pub(crate) fn all(connection: &mut SqliteConnection) -> Result<Vec<Resource>> {
+    let records = resources::table
+        .order_by(resources::name)
+        .get_results(connection)?;
+    Ok(records)
+}
+
+pub(crate) fn insert(connection: &mut SqliteConnection, form: &ResourceForm) -> Result<Resource> {
+    let record: Resource = form.into();
+
+    diesel::dsl::insert_into(resources::table)
+        .values(&record)
+        .execute(connection)?;
+
+    Ok(record)
+}
+
+pub(crate) fn update(connection: &mut SqliteConnection, form: &EditResourceForm) -> Result {
+    diesel::dsl::update(resources::table)
+        .set((
+            resources::name.eq(form.name.trim()),
+            resources::capacity.eq(parse_quantity(&form.capacity)),
+            resources::updated_at.eq(OffsetDateTime::now_utc()),
+        ))
+        .filter(resources::id.eq(&form.id))
+        .execute(connection)?;
+    Ok(())
+}
+
+// Line items and templates using the resource are kept, without a resource
+pub(crate) fn delete<S: AsRef<str>>(connection: &mut SqliteConnection, id: S) -> Result {
+    connection.transaction::<_, crate::error::AppError, _>(|tx| {
+        diesel::dsl::update(line_items::table)
+            .set(line_items::resource_id.eq(None::<String>))
+            .filter(line_items::resource_id.eq(id.as_ref()))
+            .execute(tx)?;
+        diesel::dsl::update(template_line_items::table)
+            .set(template_line_items::resource_id.eq(None::<String>))
+            .filter(template_line_items::resource_id.eq(id.as_ref()))
+            .execute(tx)?;
+        diesel::dsl::delete(resources::table.filter(resources::id.eq(id.as_ref()))).execute(tx)?;
+        Ok(())
+    })
+}
+
+// Resources used by a quote whose quantities booked by every quote but the declined ones exceed
+// their capacity, on any date of the quote. Time slots of a same day all count against the day.
+pub(crate) fn conflicts<S: AsRef<str>>(
+    connection: &mut SqliteConnection,
+    quote_id: S,
+) -> Result<Vec<ResourceConflict>> {
+    // language=SQL
+    let sql = r#"
+      select
+        lid."date",
+        r.name,
+        r.capacity,
+        sum(li.quantity) as booked
+      from line_items li
+        inner join line_item_dates lid on li.line_item_date_id = lid.id
+        inner join quotes q on lid.quote_id = q.id
+        inner join resources r on li.resource_id = r.id
+      where q.status != ?
+        and exists (
+          select 1
+          from line_items qli
+            inner join line_item_dates qlid on qli.line_item_date_id = qlid.id
+          where qlid.quote_id = ?
+            and qlid."date" = lid."date"
+            and qli.resource_id = r.id)
+      group by lid."date", r.id, r.name, r.capacity
+      having sum(li.quantity) > r.capacity
+      order by lid."date", r.name
+    "#;
+    let records = diesel::dsl::sql_query(sql)
+        .bind::<diesel::sql_types::Text, _>(STATUS_REJECTED)
+        .bind::<diesel::sql_types::Text, _>(quote_id.as_ref())
+        .load(connection)?;
+    Ok(records)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{
+        quotes::model::STATUS_REJECTED, resources::model::ResourceForm, schema::quotes,
+        time::parse_date,
+    };
+    use diesel::prelude::*;
+    use diesel::sql_types::{Double, Text};
+    use time::Date;
+
+    // Two of the quotes added by the migrations
+    const WEDDING: &str = "01HE2X4FKPDTVHHB6C2HZD5Z53";
+    const GALA: &str = "01HE2X51WKBYSJ7ZPETRB9STCQ";
+
+    // Books `quantity` of the resource on a new date of the quote
+    fn book(
+        connection: &mut SqliteConnection,
+        quote_id: &str,
+        date: &str,
+        resource_id: &str,
+        quantity: f64,
+    ) {
+        let line_item_date_id = format!("{quote_id}_{date}");
+        // language=SQL
+        let sql = r#"
+          insert into line_item_dates (id, quote_id, "date", created_at, updated_at)
+          values (?, ?, ?, strftime('%Y-%m-%dT%H:%M:%fZ'), strftime('%Y-%m-%dT%H:%M:%fZ'))
+        "#;
+        diesel::dsl::sql_query(sql)
+            .bind::<Text, _>(&line_item_date_id)
+            .bind::<Text, _>(quote_id)
+            .bind::<Text, _>(date)
+            .execute(connection)
+            .unwrap();
+        // language=SQL
+        let sql = r#"
+          insert into line_items
+            (id, line_item_date_id, name, quantity, unit_price, created_at, updated_at, resource_id)
+          values
+            (?, ?, 'Tent', ?, 100, strftime('%Y-%m-%dT%H:%M:%fZ'), strftime('%Y-%m-%dT%H:%M:%fZ'), ?)
+        "#;
+        diesel::dsl::sql_query(sql)
+            .bind::<Text, _>(format!("{line_item_date_id}_item"))
+            .bind::<Text, _>(&line_item_date_id)
+            .bind::<Double, _>(quantity)
+            .bind::<Text, _>(resource_id)
+            .execute(connection)
+            .unwrap();
+    }
+
+    #[test]
+    fn conflicts() {
+        let mut connection = crate::test_connection();
+        let form = ResourceForm {
+            name: String::from("Tent"),
+            capacity: String::from("10"),
+        };
+        let tent = super::insert(&mut connection, &form).unwrap();
+        book(&mut connection, WEDDING, "2024-03-01", &tent.id, 6.0);
+        book(&mut connection, GALA, "2024-03-01", &tent.id, 5.0);
+        book(&mut connection, GALA, "2024-03-02", &tent.id, 12.0);
+
+        // Only the days the quote itself books the resource on are reported
+        let conflicts = super::conflicts(&mut connection, WEDDING).unwrap();
+        assert_eq!(1, conflicts.len());
+        assert_eq!(parse_date("2024-03-01"), conflicts[0].date);
+        assert_eq!((10.0, 11.0), (conflicts[0].capacity, conflicts[0].booked));
+
+        let conflicts = super::conflicts(&mut connection, GALA).unwrap();
+        assert_eq!(
+            vec![parse_date("2024-03-01"), parse_date("2024-03-02")],
+            conflicts
+                .iter()
+                .map(|conflict| conflict.date)
+                .collect::<Vec<Date>>()
+        );
+
+        // Declined quotes book nothing
+        diesel::update(quotes::table.find(GALA))
+            .set(quotes::status.eq(STATUS_REJECTED))
+            .execute(&mut connection)
+            .unwrap();
+        assert!(
+            super::conflicts(&mut connection, WEDDING)
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
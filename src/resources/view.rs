@@ -0,0 +1,113 @@
+use crate::{
+    quantity::step,
+    resources::model::{ResourceConflict, ResourcePresenter},
+};
+
+markup::define! {
+    Index<'a>(resources: &'a Vec<ResourcePresenter>, errors: &'a Vec<String>) {
+        main[id = "container", class = "w-full px-4 py-0 mx-auto my-0 max-w-[60rem]"] {
+            div[id = "header", class = "flex flex-wrap gap-3 justify-between mt-4 mb-8"] {
+                h1[class = "text-header text-[2rem]/[1.1] box-border m-0 p-0 font-bold"] {"Resources"}
+            }
+
+            @if !errors.is_empty() {
+                div[class = "w-full text-primary bg-primary-bg p-2 mb-4 rounded-md"] {
+                    @for message in *errors {
+                        p { @message }
+                    }
+                }
+            }
+
+            @for resource in *resources {
+                div[class = "flex flex-wrap items-center gap-2 bg-white rounded-md mb-4 px-4 py-2 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]"] {
+                    form[action = "/resources/update",
+                        method = "post",
+                        class = "flex flex-1 flex-wrap items-center gap-2",
+                        autocomplete = "off",
+                        novalidate,
+                        "accept-charset" = "UTF-8"] {
+                        input[name = "id", "type" = "hidden", value = &resource.id] {}
+                        label[class = "visually-hidden", "for" = {format!("resource_{}_name", resource.id)}] { "Name" }
+                        input[id = {format!("resource_{}_name", resource.id)},
+                            name = "name",
+                            class = "form-input flex-1",
+                            required,
+                            "type" = "text",
+                            value = &resource.name] {}
+                        label["for" = {format!("resource_{}_capacity", resource.id)}] { "Per day" }
+                        input[id = {format!("resource_{}_capacity", resource.id)},
+                            name = "capacity",
+                            class = "form-input [flex:0_0_8rem]",
+                            required,
+                            "type" = "number",
+                            min = "0",
+                            step = step(),
+                            value = &resource.capacity] {}
+                        button[class = "button button-light", "type" = "submit"] { "Save" }
+                    }
+                    form[action = "/resources/delete", method = "post"] {
+                        input[name = "id", "type" = "hidden", value = &resource.id] {}
+                        button[class = "button button-light", "type" = "submit"] { "Delete" }
+                    }
+                }
+            }
+
+            form[action = "/resources/create",
+                method = "post",
+                class = "flex flex-col gap-3 bg-white rounded-md mb-4 p-4 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]",
+                autocomplete = "off",
+                novalidate,
+                "accept-charset" = "UTF-8"] {
+
+                h2[class = "text-[1.5rem] font-bold"] { "New resource" }
+
+                label["for" = "resource_name"] { "Name" }
+                input[id = "resource_name",
+                    name = "name",
+                    class = "form-input",
+                    placeholder = "Folding chairs",
+                    "type" = "text"] {}
+                label["for" = "resource_capacity"] { "Capacity per day, across all quotes" }
+                input[id = "resource_capacity",
+                    name = "capacity",
+                    class = "form-input",
+                    placeholder = "100",
+                    "type" = "number",
+                    min = "0",
+                    step = step()] {}
+                div[class = "flex justify-end"] {
+                    input[name = "commit",
+                        "type" = "submit",
+                        value = "Add resource",
+                        class = "button button-secondary"] {}
+                }
+            }
+        }
+    }
+
+    Options<'a>(resources: &'a Vec<ResourcePresenter>, selected: &'a str) {
+        option[value = ""] { "No resource" }
+        @for resource in *resources {
+            option[value = &resource.id, selected = resource.id == *selected] { @resource.name }
+        }
+    }
+
+    ResourceSelect<'a>(selected: &'a str) {
+        label[class = "visually-hidden", "for" = "line_item_resource_id"] { "Resource" }
+        select[id = "line_item_resource_id",
+            name = "resource_id",
+            class = "form-input mb-1",
+            title = "Resource booked by the quantity of the item",
+            "hx-get" = {format!("/resources/options?selected={selected}")},
+            "hx-trigger" = "load",
+            "hx-swap" = "innerHTML"] {
+            option[value = *selected] { "Resource" }
+        }
+    }
+
+    Conflicts<'a>(conflicts: &'a Vec<ResourceConflict>) {
+        @for conflict in *conflicts {
+            p[class = "font-bold text-primary"] { "Over capacity: " @conflict.message() }
+        }
+    }
+}
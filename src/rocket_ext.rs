@@ -1,12 +1,139 @@
-use rocket::http::Header;
+use crate::layout::{Layout, Page};
+use rocket::{
+    Request,
+    form::Context,
+    http::{Header, Status},
+    request::{FromRequest, Outcome},
+    response::Redirect,
+    serde::{
+        Serialize,
+        json::{self, Json, Value},
+    },
+};
+use std::convert::Infallible;
+
+// Event sent with the responses changing the line items or dates of a quote, for the elements
+// computed from them, like its margins, to reload
+pub(crate) const QUOTE_CHANGED: &str = "quoteChanged";
+
+pub(crate) fn quote_changed() -> Header<'static> {
+    Header::new("HX-Trigger", QUOTE_CHANGED)
+}
 
 #[derive(Responder)]
-#[response(status = 200, content_type = "html")]
 pub(crate) enum HtmxResponder {
+    #[response(status = 200, content_type = "html")]
     Ok(String),
+    #[response(status = 200, content_type = "html")]
     Retarget {
         content: String,
         retarget: Header<'static>,
         reswap: Header<'static>,
     },
+    #[response(status = 200, content_type = "html")]
+    Changed {
+        content: String,
+        trigger: Header<'static>,
+    },
+    #[response(status = 200, content_type = "html")]
+    RetargetChanged {
+        content: String,
+        retarget: Header<'static>,
+        reswap: Header<'static>,
+        trigger: Header<'static>,
+    },
+    #[response(status = 200, content_type = "html")]
+    Refresh {
+        content: String,
+        refresh: Header<'static>,
+    },
+    Redirect(Redirect),
+    Json(Json<Value>),
+    #[response(status = 422)]
+    Invalid(Json<Value>),
+    Empty(Status),
+}
+
+impl HtmxResponder {
+    pub(crate) fn json<T: Serialize>(value: &T) -> crate::Result<HtmxResponder> {
+        Ok(HtmxResponder::Json(Json(json::to_value(value)?)))
+    }
+
+    // Validation errors of a submitted form as `{"errors": [{"field": ..., "message": ...}]}`
+    pub(crate) fn invalid(context: &Context<'_>) -> HtmxResponder {
+        let errors = context
+            .errors()
+            .map(|error| {
+                json::json!({
+                    "field": error.name.as_ref().map(|name| name.to_string()),
+                    "message": error.to_string(),
+                })
+            })
+            .collect::<Vec<Value>>();
+        HtmxResponder::Invalid(Json(json::json!({ "errors": errors })))
+    }
+}
+
+// Whether the request comes from htmx (`HX-Request: true`) rather than plain browser navigation
+pub(crate) struct HtmxRequest(pub(crate) bool);
+
+impl HtmxRequest {
+    // The fragment as is for htmx, wrapped in the layout otherwise
+    pub(crate) fn render<Content: markup::Render>(&self, title: &str, content: Content) -> String {
+        if self.0 {
+            return content.to_string();
+        }
+
+        Layout {
+            head: markup::new! {
+                title { @title }
+            },
+            body: Page { content },
+        }
+        .to_string()
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for HtmxRequest {
+    type Error = Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(HtmxRequest(
+            request.headers().get_one("HX-Request") == Some("true"),
+        ))
+    }
+}
+
+// Whether the client asks for JSON (`Accept: application/json`) rather than markup
+pub(crate) struct AcceptJson(pub(crate) bool);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AcceptJson {
+    type Error = Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(AcceptJson(
+            request
+                .accept()
+                .is_some_and(|accept| accept.preferred().media_type().is_json()),
+        ))
+    }
+}
+
+// The `User-Agent` header of the request, if any
+pub(crate) struct UserAgent(pub(crate) Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for UserAgent {
+    type Error = Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(UserAgent(
+            request
+                .headers()
+                .get_one("User-Agent")
+                .map(|user_agent| user_agent.to_string()),
+        ))
+    }
 }
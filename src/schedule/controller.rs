@@ -0,0 +1,67 @@
+use crate::{
+    Db, Result,
+    layout::Layout,
+    schedule::{
+        self,
+        model::{Schedule, by_quote},
+        view::*,
+    },
+};
+use rocket::{
+    fairing::AdHoc,
+    http::{ContentType, Header},
+    response::content::RawHtml,
+};
+use time::OffsetDateTime;
+
+pub(crate) fn stage() -> AdHoc {
+    AdHoc::on_ignite("Schedule Stage", |rocket| async {
+        rocket.mount("/schedule", routes![index, export])
+    })
+}
+
+#[derive(Responder)]
+struct Csv {
+    content: String,
+    content_type: ContentType,
+    disposition: Header<'static>,
+}
+
+#[get("/?<from>&<to>")]
+async fn index(db: Db, from: Option<&str>, to: Option<&str>) -> Result<RawHtml<String>> {
+    let schedule = Schedule::new(from, to, OffsetDateTime::now_utc().date());
+    let (first, last) = (schedule.from, schedule.to);
+    let items = db
+        .run(move |conn| schedule::query::items_between(conn, first, last))
+        .await?;
+    let quotes = by_quote(items);
+
+    let template = Layout {
+        head: markup::new! {
+            title { "Schedule" }
+        },
+        body: Index {
+            schedule: &schedule,
+            quotes: &quotes,
+        },
+    };
+    Ok(RawHtml(template.to_string()))
+}
+
+#[get("/export.csv?<from>&<to>")]
+async fn export(db: Db, from: Option<&str>, to: Option<&str>) -> Result<Csv> {
+    let schedule = Schedule::new(from, to, OffsetDateTime::now_utc().date());
+    let (first, last) = (schedule.from, schedule.to);
+    let items = db
+        .run(move |conn| schedule::query::items_between(conn, first, last))
+        .await?;
+
+    Ok(Csv {
+        content: schedule::model::csv(&items),
+        content_type: ContentType::CSV,
+        disposition: Header::new(
+            "Content-Disposition",
+            format!("attachment; filename=\"{}\"", schedule.file_name()),
+        ),
+    })
+}
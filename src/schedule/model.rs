@@ -0,0 +1,248 @@
+use crate::{
+    quantity::format_quantity,
+    quotes::model::{STATUS_ACCEPTED, STATUS_DRAFT, STATUS_REJECTED},
+    time::{DATE_FORMAT, long_form, short_form},
+};
+use diesel::prelude::*;
+use diesel::sql_types::*;
+use time::Date;
+
+// Quantity of an item needed by a quote over the dates of the report
+#[derive(Debug, QueryableByName)]
+pub struct ScheduleItem {
+    #[diesel(sql_type = Text)]
+    pub quote_id: String,
+    #[diesel(sql_type = Text)]
+    pub quote_name: String,
+    #[diesel(sql_type = Text)]
+    pub quote_status: String,
+    #[diesel(sql_type = Text)]
+    pub name: String,
+    #[diesel(sql_type = Text)]
+    pub unit: String,
+    #[diesel(sql_type = Double)]
+    pub quantity: f64,
+    #[diesel(sql_type = diesel::sql_types::Date)]
+    pub first_date: Date,
+    #[diesel(sql_type = diesel::sql_types::Date)]
+    pub last_date: Date,
+}
+
+impl ScheduleItem {
+    pub fn quantity(&self) -> String {
+        format_quantity(self.quantity)
+    }
+
+    pub fn status_name(&self) -> &str {
+        match self.quote_status.as_str() {
+            STATUS_ACCEPTED => "Accepted",
+            STATUS_REJECTED => "Declined",
+            _ => "Draft",
+        }
+    }
+
+    pub fn dates(&self) -> String {
+        dates(self.first_date, self.last_date)
+    }
+}
+
+// The items of a quote, in the order of the report
+#[derive(Debug)]
+pub struct ScheduleQuote {
+    pub items: Vec<ScheduleItem>,
+}
+
+impl ScheduleQuote {
+    fn first(&self) -> &ScheduleItem {
+        &self.items[0]
+    }
+
+    pub fn href(&self) -> String {
+        format!("/quotes/show/{}", self.first().quote_id)
+    }
+
+    pub fn name(&self) -> &str {
+        &self.first().quote_name
+    }
+
+    pub fn status_name(&self) -> &str {
+        self.first().status_name()
+    }
+
+    // Quotes not accepted yet are not a commitment
+    pub fn is_tentative(&self) -> bool {
+        self.first().quote_status == STATUS_DRAFT
+    }
+
+    fn range(&self) -> (Date, Date) {
+        let first = self.items.iter().map(|item| item.first_date).min().unwrap();
+        let last = self.items.iter().map(|item| item.last_date).max().unwrap();
+        (first, last)
+    }
+
+    pub fn dates(&self) -> String {
+        let (first, last) = self.range();
+        dates(first, last)
+    }
+
+    // The dates of each item are then worth showing
+    pub fn is_several_days(&self) -> bool {
+        let (first, last) = self.range();
+        first != last
+    }
+}
+
+fn dates(first: Date, last: Date) -> String {
+    if first == last {
+        long_form(first)
+    } else {
+        format!("{} – {}", long_form(first), long_form(last))
+    }
+}
+
+// Items come ordered by quote, so that each quote is a run of consecutive items
+pub(crate) fn by_quote(items: Vec<ScheduleItem>) -> Vec<ScheduleQuote> {
+    let mut quotes: Vec<ScheduleQuote> = Vec::new();
+    for item in items {
+        match quotes.last_mut() {
+            Some(quote) if quote.first().quote_id == item.quote_id => quote.items.push(item),
+            _ => quotes.push(ScheduleQuote { items: vec![item] }),
+        }
+    }
+    quotes
+}
+
+// A single day or a range of days, both ends included
+#[derive(Debug)]
+pub struct Schedule {
+    pub from: Date,
+    pub to: Date,
+}
+
+impl Schedule {
+    pub(crate) fn new(from: Option<&str>, to: Option<&str>, today: Date) -> Self {
+        let from = from
+            .and_then(|date| Date::parse(date, DATE_FORMAT).ok())
+            .unwrap_or(today);
+        let to = to
+            .and_then(|date| Date::parse(date, DATE_FORMAT).ok())
+            .filter(|to| *to >= from)
+            .unwrap_or(from);
+        Schedule { from, to }
+    }
+
+    pub fn title(&self) -> String {
+        format!("Schedule for {}", dates(self.from, self.to))
+    }
+
+    pub fn from_form(&self) -> String {
+        short_form(self.from)
+    }
+
+    pub fn to_form(&self) -> String {
+        short_form(self.to)
+    }
+
+    pub fn csv_href(&self) -> String {
+        format!(
+            "/schedule/export.csv?from={}&to={}",
+            self.from_form(),
+            self.to_form()
+        )
+    }
+
+    pub(crate) fn file_name(&self) -> String {
+        if self.from == self.to {
+            format!("schedule-{}.csv", short_form(self.from))
+        } else {
+            format!(
+                "schedule-{}-{}.csv",
+                short_form(self.from),
+                short_form(self.to)
+            )
+        }
+    }
+}
+
+// Fields with a separator, a quote or a line break are quoted, with their quotes doubled
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\r', '\n']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+// One line per item, with CRLF line endings as spreadsheets expect
+pub(crate) fn csv(items: &[ScheduleItem]) -> String {
+    let mut lines = vec![String::from(
+        "Quote,Status,First date,Last date,Item,Quantity,Unit",
+    )];
+    for item in items {
+        let fields = [
+            csv_field(&item.quote_name),
+            csv_field(item.status_name()),
+            short_form(item.first_date),
+            short_form(item.last_date),
+            csv_field(&item.name),
+            item.quantity(),
+            csv_field(&item.unit),
+        ];
+        lines.push(fields.join(","));
+    }
+    let mut content = lines.join("\r\n");
+    content.push_str("\r\n");
+    content
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use time::macros::date;
+
+    fn item(quote_id: &str, name: &str) -> ScheduleItem {
+        ScheduleItem {
+            quote_id: quote_id.to_string(),
+            quote_name: String::from("Smith, \"Gala\" dinner"),
+            quote_status: String::from(STATUS_ACCEPTED),
+            name: name.to_string(),
+            unit: String::from("pcs"),
+            quantity: 120.0,
+            first_date: date!(2026 - 10 - 19),
+            last_date: date!(2026 - 10 - 20),
+        }
+    }
+
+    #[test]
+    fn schedules() {
+        let today = date!(2026 - 10 - 19);
+        let schedule = Schedule::new(None, None, today);
+        assert_eq!((schedule.from, schedule.to), (today, today));
+        assert_eq!(schedule.file_name(), "schedule-2026-10-19.csv");
+
+        let schedule = Schedule::new(Some("2026-10-20"), Some("2026-10-25"), today);
+        assert_eq!(schedule.from, date!(2026 - 10 - 20));
+        assert_eq!(schedule.to, date!(2026 - 10 - 25));
+
+        // An end before the start falls back to the start
+        let schedule = Schedule::new(Some("2026-10-20"), Some("2026-10-01"), today);
+        assert_eq!(schedule.to, date!(2026 - 10 - 20));
+    }
+
+    #[test]
+    fn exports() {
+        let quotes = by_quote(vec![
+            item("a", "Chairs"),
+            item("a", "Tables"),
+            item("b", "Chairs"),
+        ]);
+        assert_eq!(quotes.len(), 2);
+        assert_eq!(quotes[0].items.len(), 2);
+
+        assert_eq!(
+            csv(&quotes[1].items),
+            "Quote,Status,First date,Last date,Item,Quantity,Unit\r\n\
+             \"Smith, \"\"Gala\"\" dinner\",Accepted,2026-10-19,2026-10-20,Chairs,120,pcs\r\n"
+        );
+    }
+}
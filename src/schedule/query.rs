@@ -0,0 +1,37 @@
+use crate::{Result, quotes::model::STATUS_REJECTED, schedule::model::ScheduleItem};
+use diesel::prelude::*;
+use time::Date;
+
+// Line items of every quote but the declined ones from `first` to `last`, their quantities summed
+// per item and unit, grouped by quote
+pub(crate) fn items_between(
+    connection: &mut SqliteConnection,
+    first: Date,
+    last: Date,
+) -> Result<Vec<ScheduleItem>> {
+    // language=SQL
+    let sql = r#"
+      select
+        q.id as quote_id,
+        q.name as quote_name,
+        q.status as quote_status,
+        li.name,
+        li.unit,
+        sum(li.quantity) as quantity,
+        min(lid."date") as first_date,
+        max(lid."date") as last_date
+      from line_items li
+        inner join line_item_dates lid on li.line_item_date_id = lid.id
+        inner join quotes q on lid.quote_id = q.id
+      where lid."date" between ? and ?
+        and q.status != ?
+      group by q.id, q.name, q.status, li.name, li.unit
+      order by q.name, q.id, li.name, li.unit
+    "#;
+    let records = diesel::dsl::sql_query(sql)
+        .bind::<diesel::sql_types::Date, _>(first)
+        .bind::<diesel::sql_types::Date, _>(last)
+        .bind::<diesel::sql_types::Text, _>(STATUS_REJECTED)
+        .load(connection)?;
+    Ok(records)
+}
@@ -0,0 +1,61 @@
+use crate::schedule::model::{Schedule, ScheduleQuote};
+
+markup::define! {
+    Index<'a>(schedule: &'a Schedule, quotes: &'a Vec<ScheduleQuote>) {
+        main[id = "container", class = "w-full px-4 py-0 mx-auto my-0 max-w-[60rem]"] {
+            div[id = "header", class = "flex flex-wrap gap-3 justify-between items-center mt-4 mb-8"] {
+                h1[class = "text-header text-[2rem]/[1.1] box-border m-0 p-0 font-bold"] { @schedule.title() }
+                div[class = "flex flex-wrap gap-2 print:hidden"] {
+                    button[class = "button button-light", "type" = "button", "_" = "on click call window.print()"] { "Print" }
+                    a[class = "button button-light", href = schedule.csv_href()] { "Export to CSV" }
+                }
+            }
+
+            form[class = "flex flex-wrap gap-2 items-end mb-8 print:hidden", action = "/schedule", method = "get"] {
+                div {
+                    label[class = "block", "for" = "schedule_from"] { "From" }
+                    input[id = "schedule_from", name = "from", class = "form-input", required, "type" = "date", value = schedule.from_form()] {}
+                }
+                div {
+                    label[class = "block", "for" = "schedule_to"] { "To" }
+                    input[id = "schedule_to", name = "to", class = "form-input", required, "type" = "date", value = schedule.to_form()] {}
+                }
+                input[class = "button button-secondary", "type" = "submit", value = "Show"] {}
+            }
+
+            @if quotes.is_empty() {
+                p[class = "text-muted"] { "Nothing is scheduled on these dates." }
+            }
+            @for quote in *quotes {
+                section[class = "mb-6 break-inside-avoid"] {
+                    div[class = "flex flex-wrap gap-2 justify-between items-baseline mb-2"] {
+                        h2[class = "text-header text-[1.25rem] font-bold m-0"] {
+                            a[href = quote.href()] { @quote.name() }
+                        }
+                        div[class = "text-[0.875rem] text-muted"] {
+                            @quote.dates() " · " @quote.status_name()
+                        }
+                    }
+                    div[class = {if quote.is_tentative() {
+                        "rounded-md border border-dashed border-[hsl(0,6%,80%)]"
+                    } else {
+                        "rounded-md bg-white shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]"
+                    }}] {
+                        @for item in &quote.items {
+                            div[class = "flex gap-2 px-4 py-2 border-b last:border-b-0 border-[hsl(0,6%,93%)]"] {
+                                div[class = "flex-1 font-bold"] { @item.name }
+                                div[class = "flex-[0_0_11rem]"] { @item.quantity() " " @item.unit }
+                                @if quote.is_several_days() {
+                                    div[class = "flex-[0_0_14rem] text-[0.875rem] text-muted"] { @item.dates() }
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+            @if !quotes.is_empty() {
+                p[class = "mt-2 text-[0.875rem] text-muted print:hidden"] { "Items of quotes not accepted yet have a dashed border." }
+            }
+        }
+    }
+}
@@ -1,5 +1,99 @@
 // @generated automatically by Diesel CLI.
 
+diesel::table! {
+    acceptances (id) {
+        id -> Text,
+        quote_id -> Text,
+        share_link_id -> Text,
+        decision -> Text,
+        signer_name -> Text,
+        signature -> Nullable<Text>,
+        comment -> Nullable<Text>,
+        snapshot -> Text,
+        ip_address -> Nullable<Text>,
+        created_at -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    attachments (id) {
+        id -> Text,
+        quote_id -> Text,
+        line_item_id -> Nullable<Text>,
+        user_id -> Text,
+        file_name -> Text,
+        content_type -> Text,
+        size -> Integer,
+        created_at -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    audit_entries (id) {
+        id -> Text,
+        quote_id -> Text,
+        actor_id -> Text,
+        actor_name -> Text,
+        entity -> Text,
+        entity_id -> Text,
+        action -> Text,
+        before_values -> Nullable<Text>,
+        after_values -> Nullable<Text>,
+        created_at -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    use currency_rs::diesel2::sqlite::sql_types::Currency;
+    use diesel::sql_types::*;
+
+    catalog_items (id) {
+        id -> Text,
+        sku -> Text,
+        name -> Text,
+        description -> Nullable<Text>,
+        unit -> Text,
+        unit_price -> Currency,
+        active -> Bool,
+        created_at -> TimestamptzSqlite,
+        updated_at -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    comment_mentions (comment_id, user_id) {
+        comment_id -> Text,
+        user_id -> Text,
+        notified_at -> Nullable<TimestamptzSqlite>,
+    }
+}
+
+diesel::table! {
+    comments (id) {
+        id -> Text,
+        quote_id -> Text,
+        line_item_id -> Nullable<Text>,
+        user_id -> Text,
+        body -> Text,
+        created_at -> TimestamptzSqlite,
+        updated_at -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    emails (id) {
+        id -> Text,
+        quote_id -> Text,
+        user_id -> Text,
+        recipient -> Text,
+        subject -> Text,
+        body -> Text,
+        status -> Text,
+        error -> Nullable<Text>,
+        created_at -> TimestamptzSqlite,
+    }
+}
+
 diesel::table! {
     line_item_dates (id) {
         id -> Text,
@@ -7,6 +101,9 @@ diesel::table! {
         date -> Date,
         created_at -> TimestamptzSqlite,
         updated_at -> TimestamptzSqlite,
+        start_time -> Nullable<Time>,
+        end_time -> Nullable<Time>,
+        timezone -> Nullable<Text>,
     }
 }
 
@@ -19,10 +116,14 @@ diesel::table! {
         line_item_date_id -> Text,
         name -> Text,
         description -> Nullable<Text>,
-        quantity -> Integer,
+        quantity -> Double,
         unit_price -> Currency,
         created_at -> TimestamptzSqlite,
         updated_at -> TimestamptzSqlite,
+        position -> Integer,
+        unit -> Text,
+        cost_price -> Currency,
+        resource_id -> Nullable<Text>,
     }
 }
 
@@ -32,10 +133,151 @@ diesel::table! {
         name -> Text,
         created_at -> TimestamptzSqlite,
         updated_at -> TimestamptzSqlite,
+        status -> Text,
+    }
+}
+
+diesel::table! {
+    resources (id) {
+        id -> Text,
+        name -> Text,
+        capacity -> Double,
+        created_at -> TimestamptzSqlite,
+        updated_at -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    share_link_views (id) {
+        id -> Text,
+        share_link_id -> Text,
+        user_agent -> Nullable<Text>,
+        viewed_at -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    share_links (id) {
+        id -> Text,
+        quote_id -> Text,
+        token -> Text,
+        user_id -> Text,
+        created_at -> TimestamptzSqlite,
+        revoked_at -> Nullable<TimestamptzSqlite>,
+    }
+}
+
+diesel::table! {
+    template_dates (id) {
+        id -> Text,
+        template_id -> Text,
+        day -> Integer,
+        start_time -> Nullable<Time>,
+        end_time -> Nullable<Time>,
+        timezone -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    template_line_items (id) {
+        id -> Text,
+        template_date_id -> Text,
+        name -> Text,
+        description -> Nullable<Text>,
+        quantity -> Double,
+        unit -> Text,
+        unit_price -> Currency,
+        cost_price -> Currency,
+        position -> Integer,
+        resource_id -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    templates (id) {
+        id -> Text,
+        name -> Text,
+        created_at -> TimestamptzSqlite,
+        updated_at -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    users (id) {
+        id -> Text,
+        username -> Text,
+        name -> Text,
+        role -> Text,
+        created_at -> TimestamptzSqlite,
+        updated_at -> TimestamptzSqlite,
+    }
+}
+
+diesel::table! {
+    webhook_deliveries (id) {
+        id -> Text,
+        subscription_id -> Text,
+        event -> Text,
+        payload -> Text,
+        status -> Text,
+        attempts -> Integer,
+        next_attempt_at -> TimestamptzSqlite,
+        response_status -> Nullable<Integer>,
+        last_error -> Nullable<Text>,
+        created_at -> TimestamptzSqlite,
+        delivered_at -> Nullable<TimestamptzSqlite>,
+    }
+}
+
+diesel::table! {
+    webhook_subscriptions (id) {
+        id -> Text,
+        url -> Text,
+        secret -> Text,
+        events -> Text,
+        active -> Bool,
+        created_at -> TimestamptzSqlite,
+        updated_at -> TimestamptzSqlite,
     }
 }
 
+diesel::joinable!(acceptances -> quotes (quote_id));
+diesel::joinable!(acceptances -> share_links (share_link_id));
+diesel::joinable!(attachments -> quotes (quote_id));
+diesel::joinable!(attachments -> users (user_id));
+diesel::joinable!(comment_mentions -> comments (comment_id));
+diesel::joinable!(comment_mentions -> users (user_id));
+diesel::joinable!(comments -> quotes (quote_id));
+diesel::joinable!(comments -> users (user_id));
+diesel::joinable!(emails -> quotes (quote_id));
+diesel::joinable!(emails -> users (user_id));
 diesel::joinable!(line_item_dates -> quotes (quote_id));
 diesel::joinable!(line_items -> line_item_dates (line_item_date_id));
+diesel::joinable!(share_link_views -> share_links (share_link_id));
+diesel::joinable!(share_links -> quotes (quote_id));
+diesel::joinable!(share_links -> users (user_id));
+diesel::joinable!(template_dates -> templates (template_id));
+diesel::joinable!(template_line_items -> template_dates (template_date_id));
+diesel::joinable!(webhook_deliveries -> webhook_subscriptions (subscription_id));
 
-diesel::allow_tables_to_appear_in_same_query!(line_item_dates, line_items, quotes);
+diesel::allow_tables_to_appear_in_same_query!(
+    acceptances,
+    attachments,
+    audit_entries,
+    catalog_items,
+    comment_mentions,
+    comments,
+    emails,
+    line_item_dates,
+    line_items,
+    quotes,
+    resources,
+    share_link_views,
+    share_links,
+    template_dates,
+    template_line_items,
+    templates,
+    users,
+    webhook_deliveries,
+    webhook_subscriptions
+);
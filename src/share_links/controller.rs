@@ -0,0 +1,205 @@
+use crate::{
+    Db, Result,
+    acceptances::{
+        self,
+        model::{AcceptanceForm, AcceptancePresenter},
+        view::Answer,
+    },
+    layout::CustomerLayout,
+    line_item_dates::{self, model::LineItemDatePresenter},
+    line_items::{self, model::LineItemPresenter},
+    quotes::{self, model::QuotePresenter},
+    rocket_ext::{HtmxRequest, HtmxResponder, UserAgent},
+    share_links::{
+        self,
+        model::{ShareForm, ShareLink, ShareLinkPresenter, ShareLinkView, ShareLinkViewPresenter},
+        view::*,
+    },
+    users::model::CurrentUser,
+};
+use itertools::Itertools;
+use rocket::{
+    fairing::AdHoc,
+    form::{Contextual, Form},
+    response::Redirect,
+};
+use std::net::IpAddr;
+
+pub(crate) fn stage() -> AdHoc {
+    AdHoc::on_ignite("Share Link Stage", |rocket| async {
+        rocket
+            .mount("/share_links", routes![quote, create, revoke])
+            .mount("/shared", routes![shared, answer])
+    })
+}
+
+#[get("/quote/<quote_id>")]
+async fn quote(db: Db, htmx: HtmxRequest, quote_id: String) -> Result<HtmxResponder> {
+    let content = render(&db, &htmx, quote_id).await?;
+    Ok(HtmxResponder::Ok(content))
+}
+
+#[post("/create", data = "<form>")]
+async fn create(
+    db: Db,
+    user: CurrentUser,
+    htmx: HtmxRequest,
+    form: Form<ShareForm>,
+) -> Result<HtmxResponder> {
+    let form = form.into_inner();
+    let record = ShareLink::new(&user.0.id, &form.quote_id);
+    db.run(move |conn| share_links::query::insert(conn, &record))
+        .await?;
+
+    respond(&db, &htmx, form.quote_id).await
+}
+
+#[post("/revoke", data = "<form>")]
+async fn revoke(db: Db, htmx: HtmxRequest, form: Form<ShareForm>) -> Result<HtmxResponder> {
+    let quote_id = form.into_inner().quote_id;
+    let id = quote_id.clone();
+    db.run(move |conn| share_links::query::revoke(conn, &id))
+        .await?;
+
+    respond(&db, &htmx, quote_id).await
+}
+
+// Public page opened by customers, every visit is recorded for the sales team
+#[get("/<token>")]
+async fn shared(db: Db, user_agent: UserAgent, token: String) -> Result<Option<HtmxResponder>> {
+    let Some(share_link) = active(&db, token).await? else {
+        return Ok(None);
+    };
+
+    let view = ShareLinkView::new(&share_link.id, user_agent.0);
+    db.run(move |conn| share_links::query::insert_view(conn, &view))
+        .await?;
+
+    let content = page(&db, share_link, AcceptanceForm::default(), Vec::new()).await?;
+    Ok(Some(HtmxResponder::Ok(content)))
+}
+
+// The customer accepts or declines the quote they were shown
+#[post("/<token>", data = "<form>")]
+async fn answer(
+    db: Db,
+    ip_address: Option<IpAddr>,
+    token: String,
+    form: Form<Contextual<'_, AcceptanceForm>>,
+) -> Result<Option<HtmxResponder>> {
+    let Some(share_link) = active(&db, token).await? else {
+        return Ok(None);
+    };
+
+    let Some(acceptance_form) = form.value.clone() else {
+        let values = AcceptanceForm {
+            signer_name: form
+                .context
+                .field_value("signer_name")
+                .unwrap_or("")
+                .to_string(),
+            comment: form.context.field_value("comment").map(String::from),
+            ..Default::default()
+        };
+        let errors = form
+            .context
+            .errors()
+            .map(|error| error.to_string())
+            .collect::<Vec<String>>();
+        let content = page(&db, share_link, values, errors).await?;
+        return Ok(Some(HtmxResponder::Ok(content)));
+    };
+
+    let uri = format!("/shared/{}", share_link.token);
+    db.run(move |conn| acceptances::query::insert(conn, &share_link, &acceptance_form, ip_address))
+        .await?;
+
+    Ok(Some(HtmxResponder::Redirect(Redirect::to(uri))))
+}
+
+async fn active(db: &Db, token: String) -> Result<Option<ShareLink>> {
+    db.run(move |conn| share_links::query::active_for_token(conn, &token))
+        .await
+}
+
+// Quote as the customer sees it, followed by their answer or the form to give it
+async fn page(
+    db: &Db,
+    share_link: ShareLink,
+    form: AcceptanceForm,
+    errors: Vec<String>,
+) -> Result<String> {
+    let quote_id = share_link.quote_id;
+    let (quote, line_item_dates, line_items, acceptance) = db
+        .run(move |conn| {
+            let quote = quotes::query::read(conn, &quote_id)?;
+            let line_item_dates = line_item_dates::query::all(conn, &quote_id)?
+                .into_iter()
+                .map(|record| record.into())
+                .collect::<Vec<LineItemDatePresenter>>();
+            let line_items = line_items::query::all_for_quote(conn, &quote_id)?
+                .into_iter()
+                .map(|record| record.into())
+                .collect::<Vec<LineItemPresenter>>()
+                .into_iter()
+                .into_group_map_by(|line_item| line_item.line_item_date_id.clone());
+            let acceptance =
+                acceptances::query::for_quote(conn, &quote_id)?.map(AcceptancePresenter::from);
+            Result::Ok((quote, line_item_dates, line_items, acceptance))
+        })
+        .await?;
+
+    let quote: QuotePresenter = quote.into();
+    let quote_name = quote.name.clone();
+    let action = format!("/shared/{}", share_link.token);
+    let template = CustomerLayout {
+        head: markup::new! {
+            title { {format!("Quote {quote_name}")} }
+        },
+        body: Shared {
+            quote: &quote,
+            line_item_dates: &line_item_dates,
+            line_items: &line_items,
+            answer: Answer {
+                action: &action,
+                acceptance: &acceptance,
+                signer_name: &form.signer_name,
+                comment: form.comment.as_deref().unwrap_or(""),
+                errors: &errors,
+            },
+        },
+    };
+    Ok(template.to_string())
+}
+
+async fn respond(db: &Db, htmx: &HtmxRequest, quote_id: String) -> Result<HtmxResponder> {
+    if !htmx.0 {
+        let uri = format!("/quotes/show/{}", quote_id);
+        return Ok(HtmxResponder::Redirect(Redirect::to(uri)));
+    }
+
+    let content = render(db, htmx, quote_id).await?;
+    Ok(HtmxResponder::Ok(content))
+}
+
+async fn render(db: &Db, htmx: &HtmxRequest, quote_id: String) -> Result<String> {
+    let id = quote_id.clone();
+    let (share_link, views) = db
+        .run(move |conn| {
+            let share_link =
+                share_links::query::active_for_quote(conn, &id)?.map(ShareLinkPresenter::from);
+            let views = share_links::query::views_for_quote(conn, &id)?
+                .into_iter()
+                .map(|record| record.into())
+                .collect::<Vec<ShareLinkViewPresenter>>();
+            Result::Ok((share_link, views))
+        })
+        .await?;
+
+    let template = Sharing {
+        quote_id: &quote_id,
+        share_link: &share_link,
+        views: &views,
+    };
+    Ok(htmx.render("Sharing", template))
+}
@@ -0,0 +1,92 @@
+use crate::{
+    schema::{share_link_views, share_links},
+    time::timestamp,
+};
+use diesel::prelude::*;
+use time::OffsetDateTime;
+use ulid::Ulid;
+
+// 160 random bits, the timestamp part of the ulids is left out as it could be guessed
+pub(crate) fn token() -> String {
+    format!("{:020x}{:020x}", Ulid::new().random(), Ulid::new().random())
+}
+
+#[derive(Debug, Insertable, Queryable, Selectable)]
+#[diesel(table_name = share_links)]
+pub(crate) struct ShareLink {
+    pub(crate) id: String,
+    pub(crate) quote_id: String,
+    pub(crate) token: String,
+    pub(crate) user_id: String,
+    pub(crate) created_at: OffsetDateTime,
+    pub(crate) revoked_at: Option<OffsetDateTime>,
+}
+
+impl ShareLink {
+    pub(crate) fn new(user_id: &str, quote_id: &str) -> ShareLink {
+        ShareLink {
+            id: Ulid::new().to_string(),
+            quote_id: quote_id.to_string(),
+            token: token(),
+            user_id: user_id.to_string(),
+            created_at: OffsetDateTime::now_utc(),
+            revoked_at: None,
+        }
+    }
+}
+
+#[derive(Debug, Insertable, Queryable, Selectable)]
+#[diesel(table_name = share_link_views)]
+pub(crate) struct ShareLinkView {
+    pub(crate) id: String,
+    pub(crate) share_link_id: String,
+    pub(crate) user_agent: Option<String>,
+    pub(crate) viewed_at: OffsetDateTime,
+}
+
+impl ShareLinkView {
+    pub(crate) fn new(share_link_id: &str, user_agent: Option<String>) -> ShareLinkView {
+        ShareLinkView {
+            id: Ulid::new().to_string(),
+            share_link_id: share_link_id.to_string(),
+            user_agent,
+            viewed_at: OffsetDateTime::now_utc(),
+        }
+    }
+}
+
+#[derive(Debug, FromForm)]
+pub(crate) struct ShareForm {
+    #[field(validate = len(1..))]
+    pub(crate) quote_id: String,
+}
+
+#[derive(Debug)]
+pub struct ShareLinkPresenter {
+    pub path: String,
+    pub created_at: String,
+}
+
+impl From<ShareLink> for ShareLinkPresenter {
+    fn from(value: ShareLink) -> Self {
+        ShareLinkPresenter {
+            path: format!("/shared/{}", value.token),
+            created_at: timestamp(value.created_at),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct ShareLinkViewPresenter {
+    pub user_agent: String,
+    pub viewed_at: String,
+}
+
+impl From<ShareLinkView> for ShareLinkViewPresenter {
+    fn from(value: ShareLinkView) -> Self {
+        ShareLinkViewPresenter {
+            user_agent: value.user_agent.unwrap_or(String::from("Unknown browser")),
+            viewed_at: timestamp(value.viewed_at),
+        }
+    }
+}
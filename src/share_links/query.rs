@@ -0,0 +1,112 @@
+use crate::{
+    Result,
+    schema::{share_link_views, share_links},
+    share_links::model::{ShareLink, ShareLinkView},
+};
+use diesel::prelude::*;
+use time::OffsetDateTime;
+
+// The link customers can currently open, a quote has at most one
+pub(crate) fn active_for_quote<S: AsRef<str>>(
+    connection: &mut SqliteConnection,
+    quote_id: S,
+) -> Result<Option<ShareLink>> {
+    let record = share_links::table
+        .filter(share_links::quote_id.eq(quote_id.as_ref()))
+        .filter(share_links::revoked_at.is_null())
+        .first(connection)
+        .optional()?;
+    Ok(record)
+}
+
+pub(crate) fn active_for_token<S: AsRef<str>>(
+    connection: &mut SqliteConnection,
+    token: S,
+) -> Result<Option<ShareLink>> {
+    let record = share_links::table
+        .filter(share_links::token.eq(token.as_ref()))
+        .filter(share_links::revoked_at.is_null())
+        .first(connection)
+        .optional()?;
+    Ok(record)
+}
+
+// Views of every link the quote has had, including revoked ones, the latest first
+pub(crate) fn views_for_quote<S: AsRef<str>>(
+    connection: &mut SqliteConnection,
+    quote_id: S,
+) -> Result<Vec<ShareLinkView>> {
+    let records = share_link_views::table
+        .inner_join(share_links::table)
+        .filter(share_links::quote_id.eq(quote_id.as_ref()))
+        .order_by((
+            share_link_views::viewed_at.desc(),
+            share_link_views::id.desc(),
+        ))
+        .select(ShareLinkView::as_select())
+        .get_results(connection)?;
+    Ok(records)
+}
+
+// Replaces the active link of the quote, the old one stops working
+pub(crate) fn insert(connection: &mut SqliteConnection, record: &ShareLink) -> Result {
+    connection.transaction::<_, crate::error::AppError, _>(|tx| {
+        revoke(tx, &record.quote_id)?;
+
+        diesel::dsl::insert_into(share_links::table)
+            .values(record)
+            .execute(tx)?;
+
+        Ok(())
+    })
+}
+
+// The active link of the quote, a new one is created when there is none
+pub(crate) fn active_or_insert<S: AsRef<str>>(
+    connection: &mut SqliteConnection,
+    user_id: &str,
+    quote_id: S,
+) -> Result<ShareLink> {
+    if let Some(record) = active_for_quote(connection, &quote_id)? {
+        return Ok(record);
+    }
+
+    let record = ShareLink::new(user_id, quote_id.as_ref());
+    insert(connection, &record)?;
+    Ok(record)
+}
+
+pub(crate) fn revoke<S: AsRef<str>>(connection: &mut SqliteConnection, quote_id: S) -> Result {
+    _ = diesel::dsl::update(share_links::table)
+        .filter(share_links::quote_id.eq(quote_id.as_ref()))
+        .filter(share_links::revoked_at.is_null())
+        .set(share_links::revoked_at.eq(Some(OffsetDateTime::now_utc())))
+        .execute(connection)?;
+    Ok(())
+}
+
+pub(crate) fn insert_view(connection: &mut SqliteConnection, record: &ShareLinkView) -> Result {
+    diesel::dsl::insert_into(share_link_views::table)
+        .values(record)
+        .execute(connection)?;
+    Ok(())
+}
+
+pub(crate) fn delete_all_for_quote<S: AsRef<str>>(
+    tx: &mut SqliteConnection,
+    quote_id: S,
+) -> Result {
+    let ids = share_links::table
+        .select(share_links::id)
+        .filter(share_links::quote_id.eq(quote_id.as_ref()));
+
+    _ = diesel::dsl::delete(share_link_views::table)
+        .filter(share_link_views::share_link_id.eq_any(ids))
+        .execute(tx)?;
+
+    _ = diesel::dsl::delete(share_links::table)
+        .filter(share_links::quote_id.eq(quote_id.as_ref()))
+        .execute(tx)?;
+
+    Ok(())
+}
@@ -0,0 +1,130 @@
+use crate::{
+    line_item_dates::model::LineItemDatePresenter,
+    line_items::model::LineItemPresenter,
+    quotes::model::QuotePresenter,
+    share_links::model::{ShareLinkPresenter, ShareLinkViewPresenter},
+};
+use std::collections::HashMap;
+
+markup::define! {
+    // Placeholder loading the share link of a quote
+    SharingLoader<'a>(quote_id: &'a str) {
+        div[id = "quote_sharing",
+            "hx-get" = {format!("/share_links/quote/{quote_id}")},
+            "hx-trigger" = "load",
+            "hx-swap" = "outerHTML"] {}
+    }
+
+    Sharing<'a>(quote_id: &'a str,
+                share_link: &'a Option<ShareLinkPresenter>,
+                views: &'a Vec<ShareLinkViewPresenter>) {
+        div[id = "quote_sharing", class = "mt-12"] {
+            h2[class = "text-[1.5rem] font-bold mb-2"] { "Sharing" }
+            div[class = "bg-white rounded-md p-4 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]"] {
+                div[class = "flex flex-wrap items-center gap-2"] {
+                    @if let Some(share_link) = share_link {
+                        a[class = "font-bold underline break-all",
+                            href = &share_link.path,
+                            target = "_blank"] { @share_link.path }
+                        span[class = "text-[0.75rem] opacity-70"] { "Created " @share_link.created_at }
+                    } else {
+                        span { "Customers can only see this quote through a share link." }
+                    }
+                    div[class = "flex gap-2 ml-auto"] {
+                        @if share_link.is_some() {
+                            form[action = "/share_links/revoke",
+                                method = "post",
+                                "hx-post" = "/share_links/revoke",
+                                "hx-target" = "#quote_sharing",
+                                "hx-swap" = "outerHTML"] {
+                                input[name = "quote_id", "type" = "hidden", value = quote_id] {}
+                                button[class = "button button-light", "type" = "submit"] { "Revoke" }
+                            }
+                        }
+                        form[action = "/share_links/create",
+                            method = "post",
+                            "hx-post" = "/share_links/create",
+                            "hx-target" = "#quote_sharing",
+                            "hx-swap" = "outerHTML"] {
+                            input[name = "quote_id", "type" = "hidden", value = quote_id] {}
+                            button[class = "button button-secondary", "type" = "submit"] {
+                                @if share_link.is_some() { "New link" } else { "Create link" }
+                            }
+                        }
+                    }
+                }
+
+                @if !views.is_empty() {
+                    h3[class = "font-bold mt-4 mb-2"] { "Viewed by the customer" }
+                    ul[class = "flex flex-col gap-1 text-[0.875rem]"] {
+                        @for view in *views {
+                            li {
+                                span[class = "font-bold"] { @view.viewed_at }
+                                " · "
+                                span[class = "opacity-70"] { @view.user_agent }
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    // Read only version of the quote for customers
+    Shared<'a, Answer: markup::Render>(quote: &'a QuotePresenter,
+               line_item_dates: &'a Vec<LineItemDatePresenter>,
+               line_items: &'a HashMap<String, Vec<LineItemPresenter>>,
+               answer: Answer) {
+        main[id = "container", class = "w-full px-4 py-0 mb-16 mx-auto my-0 max-w-[60rem]"] {
+            h1[class = "text-header text-[2rem]/[1.1] mt-4 mb-8 p-0 font-bold"] {
+                @quote.name
+            }
+
+            @for line_item_date in *line_item_dates {
+                @let empty = Vec::new();
+                @let line_items = line_items.get(&line_item_date.id()).unwrap_or(&empty);
+                div[class = "mt-8 mb-1.5"] {
+                    div[class= "flex items-center justify-between gap-2"] {
+                        h2[class = "text-[1.5rem] font-bold"] {
+                            @line_item_date.date_long_form()
+                            @if let Some(time_range) = line_item_date.time_range() {
+                                span[class = "ml-2 font-normal text-muted"] { @time_range }
+                            }
+                        }
+                        div[class = "font-bold"] { @line_item_date.subtotal.format() }
+                    }
+                    div[class = "bg-white rounded-md mt-2 p-4 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]"] {
+                        div[class = "flex flex-wrap items-start bg-light gap-2 mb-3 p-2 rounded-md"] {
+                            div[class = "flex-1 font-bold text-[0.875rem] tracking-[1px] uppercase"] { "Article" }
+                            div[class = "display-[revert] flex-[0_0_11rem] font-bold text-[0.875rem] tracking-[1px] uppercase"] { "Quantity" }
+                            div[class = "display-[revert] flex-[0_0_9rem] font-bold text-[0.875rem] tracking-[1px] uppercase"] { "Price" }
+                        }
+                        @for line_item in line_items {
+                            div[class = "flex flex-wrap items-start bg-white gap-2 mb-3 p-2 rounded-md"] {
+                                div[class = "flex-1 font-bold mb-0"] {
+                                    @line_item.name
+                                    div[class = "basis-full m-w-100 font-normal text-[0.875rem] text-[hsl(0,1%,44%)]"] {
+                                        @line_item.description
+                                    }
+                                }
+                                div[class = "display-[revert] flex-[0_0_11rem] mb-0"] {
+                                    @line_item.quantity " " @line_item.unit
+                                }
+                                div[class = "display-[revert] flex-[0_0_9rem] mb-0"] {
+                                    @line_item.unit_price.format()
+                                }
+                            }
+                        }
+                    }
+                }
+            }
+
+            div[class = "flex items-center justify-between mt-8 py-4 text-[1.25rem] font-bold border-t-2 border-[hsl(0,6%,93%)]"] {
+                div { "Total:" }
+                div { @quote.total.format() }
+            }
+
+            @answer
+        }
+    }
+}
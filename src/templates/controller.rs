@@ -0,0 +1,97 @@
+use crate::{
+    Db, Result,
+    layout::{Flash, Layout},
+    rocket_ext::{HtmxRequest, HtmxResponder},
+    templates::{
+        self,
+        model::{DeleteForm, TemplateForm, TemplatePresenter},
+        view::*,
+    },
+};
+use rocket::{
+    fairing::AdHoc,
+    form::{Contextual, Form},
+    response::{Redirect, content::RawHtml},
+};
+
+pub(crate) fn stage() -> AdHoc {
+    AdHoc::on_ignite("Template Stage", |rocket| async {
+        rocket.mount("/templates", routes![index, create, delete])
+    })
+}
+
+#[get("/")]
+async fn index(db: Db) -> Result<RawHtml<String>> {
+    let templates = all(&db).await?;
+
+    let template = Layout {
+        head: markup::new! {
+            title { "Templates" }
+        },
+        body: Index {
+            templates: &templates,
+        },
+    };
+    Ok(RawHtml(template.to_string()))
+}
+
+#[post("/create", data = "<form>")]
+async fn create(
+    db: Db,
+    htmx: HtmxRequest,
+    form: Form<Contextual<'_, TemplateForm>>,
+) -> Result<HtmxResponder> {
+    let Some(template_form) = form.value.clone() else {
+        let message = form
+            .context
+            .errors()
+            .map(|error| error.to_string())
+            .collect::<Vec<String>>()
+            .join(" ");
+        return Ok(HtmxResponder::Ok(Flash { message: &message }.to_string()));
+    };
+
+    let quote_id = template_form.quote_id.clone();
+    db.run(move |conn| templates::query::insert(conn, &template_form))
+        .await?;
+
+    if !htmx.0 {
+        return Ok(HtmxResponder::Redirect(Redirect::to(format!(
+            "/quotes/show/{quote_id}"
+        ))));
+    }
+
+    let content = Flash {
+        message: "Template was successfully saved.",
+    }
+    .to_string();
+    Ok(HtmxResponder::Ok(content))
+}
+
+#[post("/delete", data = "<form>")]
+async fn delete(db: Db, htmx: HtmxRequest, form: Form<DeleteForm>) -> Result<HtmxResponder> {
+    let id = form.into_inner().id;
+    db.run(move |conn| templates::query::delete(conn, &id))
+        .await?;
+
+    if !htmx.0 {
+        return Ok(HtmxResponder::Redirect(Redirect::to(uri!("/templates"))));
+    }
+
+    let content = Flash {
+        message: "Template was successfully destroyed.",
+    }
+    .to_string();
+    Ok(HtmxResponder::Ok(content))
+}
+
+pub(crate) async fn all(db: &Db) -> Result<Vec<TemplatePresenter>> {
+    db.run(move |conn| {
+        let records = templates::query::all(conn)?
+            .into_iter()
+            .map(|record| record.into())
+            .collect::<Vec<TemplatePresenter>>();
+        Result::Ok(records)
+    })
+    .await
+}
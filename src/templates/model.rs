@@ -0,0 +1,197 @@
+use crate::{
+    line_item_dates::model::LineItemDate,
+    line_items::model::LineItem,
+    schema::{template_dates, template_line_items, templates},
+};
+use currency_rs::Currency;
+use diesel::prelude::*;
+use diesel::sql_types::*;
+use time::{Date, Duration, OffsetDateTime, Time};
+use ulid::Ulid;
+
+// Day 1 is the first date of the quote, or the start date of the quote created from the template
+pub(crate) fn day_of(first: Date, date: Date) -> i32 {
+    (date - first).whole_days() as i32 + 1
+}
+
+pub(crate) fn date_of(start: Date, day: i32) -> Date {
+    start + Duration::days(i64::from(day - 1))
+}
+
+#[derive(Debug, Insertable, Queryable)]
+pub(crate) struct Template {
+    pub(crate) id: String,
+    pub(crate) name: String,
+    pub(crate) created_at: OffsetDateTime,
+    pub(crate) updated_at: OffsetDateTime,
+}
+
+impl Template {
+    pub(crate) fn new(name: &str) -> Self {
+        Template {
+            id: Ulid::new().to_string(),
+            name: name.trim().to_string(),
+            created_at: OffsetDateTime::now_utc(),
+            updated_at: OffsetDateTime::now_utc(),
+        }
+    }
+}
+
+#[derive(Debug, Insertable, Queryable)]
+pub(crate) struct TemplateDate {
+    pub(crate) id: String,
+    pub(crate) template_id: String,
+    pub(crate) day: i32,
+    pub(crate) start_time: Option<Time>,
+    pub(crate) end_time: Option<Time>,
+    pub(crate) timezone: Option<String>,
+}
+
+impl TemplateDate {
+    pub(crate) fn new(template_id: &str, first: Date, line_item_date: &LineItemDate) -> Self {
+        TemplateDate {
+            id: Ulid::new().to_string(),
+            template_id: template_id.to_string(),
+            day: day_of(first, line_item_date.date),
+            start_time: line_item_date.start_time,
+            end_time: line_item_date.end_time,
+            timezone: line_item_date.timezone.clone(),
+        }
+    }
+
+    pub(crate) fn to_line_item_date(&self, quote_id: &str, start: Date) -> LineItemDate {
+        LineItemDate {
+            id: Ulid::new().to_string(),
+            quote_id: quote_id.to_string(),
+            date: date_of(start, self.day),
+            created_at: OffsetDateTime::now_utc(),
+            updated_at: OffsetDateTime::now_utc(),
+            start_time: self.start_time,
+            end_time: self.end_time,
+            timezone: self.timezone.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Insertable, Queryable)]
+pub(crate) struct TemplateLineItem {
+    pub(crate) id: String,
+    pub(crate) template_date_id: String,
+    pub(crate) name: String,
+    pub(crate) description: Option<String>,
+    pub(crate) quantity: f64,
+    pub(crate) unit: String,
+    pub(crate) unit_price: Currency,
+    pub(crate) cost_price: Currency,
+    pub(crate) position: i32,
+    pub(crate) resource_id: Option<String>,
+}
+
+impl TemplateLineItem {
+    pub(crate) fn new(template_date_id: &str, line_item: &LineItem) -> Self {
+        TemplateLineItem {
+            id: Ulid::new().to_string(),
+            template_date_id: template_date_id.to_string(),
+            name: line_item.name.clone(),
+            description: line_item.description.clone(),
+            quantity: line_item.quantity,
+            unit: line_item.unit.clone(),
+            unit_price: line_item.unit_price.clone(),
+            cost_price: line_item.cost_price.clone(),
+            position: line_item.position,
+            resource_id: line_item.resource_id.clone(),
+        }
+    }
+
+    pub(crate) fn to_line_item(&self, line_item_date_id: &str) -> LineItem {
+        LineItem {
+            id: Ulid::new().to_string(),
+            line_item_date_id: line_item_date_id.to_string(),
+            name: self.name.clone(),
+            description: self.description.clone(),
+            quantity: self.quantity,
+            unit_price: self.unit_price.clone(),
+            created_at: OffsetDateTime::now_utc(),
+            updated_at: OffsetDateTime::now_utc(),
+            position: self.position,
+            unit: self.unit.clone(),
+            cost_price: self.cost_price.clone(),
+            resource_id: self.resource_id.clone(),
+        }
+    }
+}
+
+#[derive(Debug, QueryableByName)]
+pub struct TemplateSummary {
+    #[diesel(sql_type = Text)]
+    pub id: String,
+    #[diesel(sql_type = Text)]
+    pub name: String,
+    #[diesel(sql_type = BigInt)]
+    pub days: i64,
+    #[diesel(sql_type = BigInt)]
+    pub line_items: i64,
+}
+
+#[derive(Clone, Debug, FromForm)]
+pub(crate) struct TemplateForm {
+    #[field(validate = len(1..))]
+    pub(crate) quote_id: String,
+    #[field(validate = len(1..).or_else(msg!("Please enter a name for the template")))]
+    pub(crate) name: String,
+}
+
+#[derive(Debug, FromForm)]
+pub(crate) struct DeleteForm {
+    pub(crate) id: String,
+}
+
+#[derive(Debug)]
+pub struct TemplatePresenter {
+    pub id: String,
+    pub name: String,
+    pub days: i64,
+    pub line_items: i64,
+}
+
+impl TemplatePresenter {
+    pub fn description(&self) -> String {
+        let days = if self.days == 1 { "day" } else { "days" };
+        let line_items = if self.line_items == 1 {
+            "line item"
+        } else {
+            "line items"
+        };
+        format!("{} {days}, {} {line_items}", self.days, self.line_items)
+    }
+}
+
+impl From<TemplateSummary> for TemplatePresenter {
+    fn from(value: TemplateSummary) -> Self {
+        TemplatePresenter {
+            id: value.id,
+            name: value.name,
+            days: value.days,
+            line_items: value.line_items,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{date_of, day_of};
+    use time::{Date, Month};
+
+    #[test]
+    fn relative_days() {
+        let first = Date::from_calendar_date(2023, Month::February, 27).unwrap();
+        let date = Date::from_calendar_date(2023, Month::March, 1).unwrap();
+        assert_eq!(1, day_of(first, first));
+        assert_eq!(3, day_of(first, date));
+
+        let start = Date::from_calendar_date(2024, Month::February, 27).unwrap();
+        let expected = Date::from_calendar_date(2024, Month::February, 29).unwrap();
+        assert_eq!(start, date_of(start, 1));
+        assert_eq!(expected, date_of(start, 3));
+    }
+}
@@ -0,0 +1,128 @@
+use crate::{
+    Result,
+    line_item_dates::model::LineItemDate,
+    line_items::model::LineItem,
+    schema::{line_item_dates, line_items, template_dates, template_line_items, templates},
+    templates::model::{Template, TemplateDate, TemplateForm, TemplateLineItem, TemplateSummary},
+};
+use diesel::prelude::*;
+use time::Date;
+
+pub(crate) fn all(connection: &mut SqliteConnection) -> Result<Vec<TemplateSummary>> {
+    // language=SQL
+    let sql = r#"
+      select
+        t.id,
+        t.name,
+        (select count(*) from template_dates td where td.template_id = t.id) as days,
+        (select
+            count(*)
+          from template_line_items tli
+            inner join template_dates td on tli.template_date_id = td.id
+          where td.template_id = t.id) as line_items
+      from templates t
+      order by t.name, t.id
+    "#;
+    let records = diesel::dsl::sql_query(sql).load(connection)?;
+    Ok(records)
+}
+
+// Copies the dates and line items of a quote, the first date of the quote becoming day 1
+pub(crate) fn insert(connection: &mut SqliteConnection, form: &TemplateForm) -> Result<Template> {
+    let record = Template::new(&form.name);
+
+    connection.transaction::<_, crate::error::AppError, _>(|tx| {
+        diesel::dsl::insert_into(templates::table)
+            .values(&record)
+            .execute(tx)?;
+
+        let dates: Vec<LineItemDate> = line_item_dates::table
+            .filter(line_item_dates::quote_id.eq(&form.quote_id))
+            .order_by((line_item_dates::date, line_item_dates::start_time))
+            .load(tx)?;
+        let Some(first) = dates.first().map(|date| date.date) else {
+            return Ok(());
+        };
+
+        for date in &dates {
+            let template_date = TemplateDate::new(&record.id, first, date);
+            diesel::dsl::insert_into(template_dates::table)
+                .values(&template_date)
+                .execute(tx)?;
+
+            let items: Vec<LineItem> = line_items::table
+                .filter(line_items::line_item_date_id.eq(&date.id))
+                .order_by((line_items::position, line_items::created_at))
+                .load(tx)?;
+            let items = items
+                .iter()
+                .map(|item| TemplateLineItem::new(&template_date.id, item))
+                .collect::<Vec<TemplateLineItem>>();
+            diesel::dsl::insert_into(template_line_items::table)
+                .values(&items)
+                .execute(tx)?;
+        }
+        Ok(())
+    })?;
+
+    Ok(record)
+}
+
+// Inserts the dates and line items of a template into a quote, day 1 falling on the start date.
+// Meant to be called from the transaction creating the quote, which audits what is returned.
+pub(crate) fn instantiate(
+    tx: &mut SqliteConnection,
+    id: &str,
+    quote_id: &str,
+    start: Date,
+) -> Result<(Vec<LineItemDate>, Vec<LineItem>)> {
+    let template_dates: Vec<TemplateDate> = template_dates::table
+        .filter(template_dates::template_id.eq(id))
+        .order_by((template_dates::day, template_dates::start_time))
+        .load(tx)?;
+
+    let mut dates = Vec::with_capacity(template_dates.len());
+    let mut items = Vec::new();
+    for template_date in template_dates {
+        let date = template_date.to_line_item_date(quote_id, start);
+        diesel::dsl::insert_into(line_item_dates::table)
+            .values(&date)
+            .execute(tx)?;
+
+        let template_items: Vec<TemplateLineItem> = template_line_items::table
+            .filter(template_line_items::template_date_id.eq(&template_date.id))
+            .order_by(template_line_items::position)
+            .load(tx)?;
+        let date_items = template_items
+            .iter()
+            .map(|item| item.to_line_item(&date.id))
+            .collect::<Vec<LineItem>>();
+        diesel::dsl::insert_into(line_items::table)
+            .values(&date_items)
+            .execute(tx)?;
+
+        dates.push(date);
+        items.extend(date_items);
+    }
+
+    Ok((dates, items))
+}
+
+pub(crate) fn delete<S: AsRef<str>>(connection: &mut SqliteConnection, id: S) -> Result {
+    connection.transaction::<_, crate::error::AppError, _>(|tx| {
+        let date_ids = template_dates::table
+            .filter(template_dates::template_id.eq(id.as_ref()))
+            .select(template_dates::id);
+        diesel::dsl::delete(
+            template_line_items::table
+                .filter(template_line_items::template_date_id.eq_any(date_ids)),
+        )
+        .execute(tx)?;
+        diesel::dsl::delete(
+            template_dates::table.filter(template_dates::template_id.eq(id.as_ref())),
+        )
+        .execute(tx)?;
+        diesel::dsl::delete(templates::table.filter(templates::id.eq(id.as_ref()))).execute(tx)?;
+        Ok(())
+    })
+}
@@ -0,0 +1,62 @@
+use crate::{quotes::model::QuotePresenter, templates::model::TemplatePresenter};
+
+markup::define! {
+    Index<'a>(templates: &'a Vec<TemplatePresenter>) {
+        main[id = "container", class = "w-full px-4 py-0 mx-auto my-0 max-w-[60rem]"] {
+            div[id = "header", class = "flex flex-wrap gap-3 justify-between mt-4 mb-8"] {
+                h1[class = "text-header text-[2rem]/[1.1] box-border m-0 p-0 font-bold"] {"Templates"}
+            }
+
+            div[id = "templates"] {
+                div[id = "templates_empty", class = "p-4 border-2 border-[hsl(0,6%,93%)] border-dashed text-center hidden only:[display:revert]"] {
+                    p[class = "[font-size:1.125rem] text-header mb-2 font-bold"] {
+                        "You don't have any templates yet!"
+                    }
+                    p { "Save a quote as a template from its page to reuse its dates and line items." }
+                }
+                @for template in *templates {
+                    div[id = format!("template_{}", template.id),
+                        class = "flex justify-between items-center gap-3 bg-white rounded-md mb-4 px-4 py-2 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]"] {
+                        div {
+                            div[class = "font-bold"] { @template.name }
+                            div[class = "text-[0.875rem] text-muted"] { @template.description() }
+                        }
+                        form[action = "/templates/delete",
+                             method = "post",
+                             "hx-post" = "/templates/delete",
+                             "hx-target" = format!("#template_{}", template.id),
+                             "hx-swap" = "delete"] {
+                            input[name = "id", "type" = "hidden", value = &template.id] {}
+                            button[class = "button button-light", "type" = "submit"] { "Delete" }
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    SaveForm<'a>(quote: &'a QuotePresenter) {
+        form[id = "quote_template",
+            action = "/templates/create",
+            method = "post",
+            "hx-post" = "/templates/create",
+            "hx-swap" = "none",
+            class = "flex flex-wrap justify-end items-center gap-3 mb-8",
+            autocomplete = "off",
+            novalidate,
+            "accept-charset" = "UTF-8"] {
+            input[name = "quote_id", "type" = "hidden", value = &quote.id()] {}
+            label[class = "visually-hidden", "for" = "template_name"] { "Template name" }
+            input[id = "template_name",
+                name = "name",
+                class = "form-input [flex:0_1_20rem]",
+                placeholder = "Name of the template",
+                "type" = "text",
+                value = &quote.name] {}
+            input[name = "commit",
+                "type" = "submit",
+                value = "Save as template",
+                class = "button button-light"] {}
+        }
+    }
+}
@@ -1,10 +1,19 @@
 use once_cell::sync::Lazy;
 use regex::Regex;
-use time::{Date, format_description::FormatItem, macros::format_description};
+use rocket::serde::Serializer;
+use time::{
+    Date, OffsetDateTime, Time, format_description::FormatItem, macros::format_description,
+};
 
 pub(crate) static DATE_FORMAT: &[FormatItem<'_>] = format_description!("[year]-[month]-[day]");
 pub(crate) static DATE_REGEX: Lazy<Regex> =
     Lazy::new(|| Regex::new(r"[0-9]{4}-[0-9]{2}-[0-9]{2}$").unwrap());
+pub(crate) static TIME_FORMAT: &[FormatItem<'_>] = format_description!("[hour]:[minute]");
+pub(crate) static TIME_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[0-9]{2}:[0-9]{2}$").unwrap());
+// Names of the tz database, e.g. `Europe/Paris` or `America/Argentina/Salta`, and `UTC`
+pub(crate) static TIMEZONE_REGEX: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^(UTC|[A-Z][A-Za-z_]+(/[A-Za-z0-9_+-]+){1,2})$").unwrap());
 
 pub(crate) fn long_form(date: Date) -> String {
     let mut result = String::with_capacity(18);
@@ -22,10 +31,55 @@ pub(crate) fn short_form(date: Date) -> String {
         .unwrap()
 }
 
+// Date and time of day in UTC, e.g. `2023-04-02 14:05 UTC`
+pub(crate) fn timestamp(moment: OffsetDateTime) -> String {
+    moment
+        .format(&format_description!(
+            "[year]-[month]-[day] [hour]:[minute] UTC"
+        ))
+        .unwrap()
+}
+
 pub(crate) fn parse_date<S: AsRef<str>>(text: S) -> Date {
     Date::parse(text.as_ref(), DATE_FORMAT).unwrap()
 }
 
+// Time of day as entered in forms, e.g. `09:30`
+pub(crate) fn time_form(time: Time) -> String {
+    time.format(TIME_FORMAT).unwrap()
+}
+
+// Empty form fields are no time
+pub(crate) fn parse_optional_time(text: &Option<String>) -> Option<Time> {
+    text.as_deref()
+        .filter(|text| !text.is_empty())
+        .map(|text| Time::parse(text, TIME_FORMAT).unwrap())
+}
+
+pub(crate) fn serialize<S: Serializer>(date: &Date, serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&short_form(*date))
+}
+
+pub(crate) fn serialize_optional<S: Serializer>(
+    date: &Option<Date>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match date {
+        Some(date) => serializer.serialize_some(&short_form(*date)),
+        None => serializer.serialize_none(),
+    }
+}
+
+pub(crate) fn serialize_optional_time<S: Serializer>(
+    time: &Option<Time>,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    match time {
+        Some(time) => serializer.serialize_some(&time_form(*time)),
+        None => serializer.serialize_none(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use time::{Date, Month};
@@ -0,0 +1,55 @@
+use crate::{
+    Db, Result, config,
+    rocket_ext::HtmxResponder,
+    users::{
+        self,
+        model::{CurrentUser, SwitchUserForm, USER_COOKIE, UserPresenter},
+        view::*,
+    },
+};
+use rocket::{
+    fairing::AdHoc,
+    form::Form,
+    http::{Cookie, CookieJar, Header},
+    response::content::RawHtml,
+};
+
+pub(crate) fn stage() -> AdHoc {
+    AdHoc::on_ignite("User Stage", |rocket| async {
+        if config::get().user_switcher {
+            rocket.mount("/users", routes![current, switch])
+        } else {
+            rocket.mount("/users", routes![current])
+        }
+    })
+}
+
+#[get("/current")]
+async fn current(db: Db, user: CurrentUser) -> Result<RawHtml<String>> {
+    let users = db
+        .run(move |conn| {
+            let records = users::query::all(conn)?
+                .into_iter()
+                .map(|record| record.into())
+                .collect::<Vec<UserPresenter>>();
+            Result::Ok(records)
+        })
+        .await?;
+
+    let template = CurrentUserMenu {
+        user: &user.0.into(),
+        users: &users,
+        switcher: config::get().user_switcher,
+    };
+    Ok(RawHtml(template.to_string()))
+}
+
+#[post("/switch", data = "<form>")]
+async fn switch(cookies: &CookieJar<'_>, form: Form<SwitchUserForm>) -> HtmxResponder {
+    cookies.add(Cookie::new(USER_COOKIE, form.into_inner().id));
+
+    HtmxResponder::Refresh {
+        content: String::new(),
+        refresh: Header::new("HX-Refresh", "true"),
+    }
+}
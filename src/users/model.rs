@@ -0,0 +1,88 @@
+use crate::{Db, config, schema::users};
+use diesel::prelude::*;
+use rocket::{
+    http::Status,
+    outcome::try_outcome,
+    request::{FromRequest, Outcome, Request},
+};
+
+pub(crate) const USER_COOKIE: &str = "user_id";
+
+pub(crate) const ROLE_ACCOUNTANT: &str = "accountant";
+pub(crate) const ROLE_MANAGER: &str = "manager";
+
+#[derive(Clone, Debug, Queryable, Selectable)]
+#[diesel(table_name = users)]
+pub(crate) struct User {
+    pub(crate) id: String,
+    pub(crate) username: String,
+    pub(crate) name: String,
+    pub(crate) role: String,
+}
+
+impl User {
+    pub(crate) fn can_view_margins(&self) -> bool {
+        self.role == ROLE_ACCOUNTANT || self.role == ROLE_MANAGER
+    }
+}
+
+// The user selected with the `user_id` cookie when the user switcher is enabled, otherwise the first
+// user. There is no authentication yet (see README), this only identifies who is working.
+pub(crate) struct CurrentUser(pub(crate) User);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for CurrentUser {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let db = try_outcome!(request.guard::<Db>().await);
+        let user_id = request
+            .cookies()
+            .get(USER_COOKIE)
+            .filter(|_| config::get().user_switcher)
+            .map(|cookie| cookie.value().to_string());
+
+        let user = db
+            .run(move |conn| crate::users::query::current(conn, user_id))
+            .await;
+
+        match user {
+            Ok(user) => Outcome::Success(CurrentUser(user)),
+            Err(_) => Outcome::Error((Status::InternalServerError, ())),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct UserPresenter {
+    pub id: String,
+    pub username: String,
+    pub name: String,
+    pub role: String,
+}
+
+impl UserPresenter {
+    pub fn role_name(&self) -> String {
+        let mut chars = self.role.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        }
+    }
+}
+
+impl From<User> for UserPresenter {
+    fn from(value: User) -> Self {
+        UserPresenter {
+            id: value.id,
+            username: value.username,
+            name: value.name,
+            role: value.role,
+        }
+    }
+}
+
+#[derive(Debug, FromForm)]
+pub(crate) struct SwitchUserForm {
+    pub(crate) id: String,
+}
@@ -0,0 +1,40 @@
+use crate::{Result, schema::users, users::model::User};
+use diesel::prelude::*;
+
+pub(crate) fn all(connection: &mut SqliteConnection) -> Result<Vec<User>> {
+    let records = users::table
+        .select(User::as_select())
+        .order_by(users::username)
+        .get_results(connection)?;
+    Ok(records)
+}
+
+pub(crate) fn current(connection: &mut SqliteConnection, id: Option<String>) -> Result<User> {
+    if let Some(id) = id {
+        let record = users::table
+            .select(User::as_select())
+            .filter(users::id.eq(&id))
+            .get_result(connection)
+            .optional()?;
+        if let Some(record) = record {
+            return Ok(record);
+        }
+    }
+
+    let record = users::table
+        .select(User::as_select())
+        .order_by(users::username)
+        .first(connection)?;
+    Ok(record)
+}
+
+pub(crate) fn ids_for_usernames(
+    connection: &mut SqliteConnection,
+    usernames: &[String],
+) -> Result<Vec<String>> {
+    let records = users::table
+        .select(users::id)
+        .filter(users::username.eq_any(usernames))
+        .get_results(connection)?;
+    Ok(records)
+}
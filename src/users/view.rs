@@ -0,0 +1,28 @@
+use crate::users::model::UserPresenter;
+
+markup::define! {
+    CurrentUserMenu<'a>(user: &'a UserPresenter, users: &'a Vec<UserPresenter>, switcher: bool) {
+        @if !*switcher {
+            div[id = "current_user", class = "ml-auto mr-3 font-bold text-header"] {
+                @user.name " (" @user.role_name() ")"
+            }
+        } else {
+            form[id = "current_user",
+                class = "ml-auto mr-3",
+                "hx-post" = "/users/switch",
+                "hx-trigger" = "change",
+                "hx-swap" = "none"] {
+                label[class = "visually-hidden", "for" = "current_user_id"] { "Current user" }
+                select[id = "current_user_id",
+                    name = "id",
+                    class = "font-bold text-header bg-transparent cursor-pointer"] {
+                    @for other in *users {
+                        option[value = &other.id, selected = other.id == user.id] {
+                            @other.name " (" @other.role_name() ")"
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
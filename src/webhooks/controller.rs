@@ -0,0 +1,112 @@
+use crate::{
+    Db, Result,
+    layout::Layout,
+    rocket_ext::HtmxResponder,
+    webhooks::{
+        self,
+        model::{
+            DeleteForm, DeliveryPresenter, SubscriptionForm, SubscriptionPresenter,
+            WebhookSubscription,
+        },
+        view::*,
+        worker,
+    },
+};
+use rocket::{
+    fairing::AdHoc,
+    form::{Contextual, Form},
+    response::{Redirect, content::RawHtml},
+};
+
+// Number of deliveries shown in the log
+const LOG_SIZE: i64 = 200;
+
+pub(crate) fn stage() -> AdHoc {
+    AdHoc::on_ignite("Webhook Stage", |rocket| async {
+        rocket
+            .mount("/webhooks", routes![index, create, delete, deliveries])
+            .attach(AdHoc::on_liftoff("Webhook Delivery", |rocket| {
+                Box::pin(worker::start(rocket))
+            }))
+    })
+}
+
+#[get("/")]
+async fn index(db: Db) -> Result<RawHtml<String>> {
+    let content = render(&db, Vec::new()).await?;
+    Ok(RawHtml(content))
+}
+
+#[post("/create", data = "<form>")]
+async fn create(db: Db, form: Form<Contextual<'_, SubscriptionForm>>) -> Result<HtmxResponder> {
+    let Some(subscription_form) = &form.value else {
+        let errors = form
+            .context
+            .errors()
+            .map(|error| error.to_string())
+            .collect::<Vec<String>>();
+        let content = render(&db, errors).await?;
+        return Ok(HtmxResponder::Ok(content));
+    };
+
+    let record: WebhookSubscription = subscription_form.into();
+    db.run(move |conn| webhooks::query::insert_subscription(conn, &record))
+        .await?;
+
+    Ok(HtmxResponder::Redirect(Redirect::to(uri!("/webhooks"))))
+}
+
+#[post("/delete", data = "<form>")]
+async fn delete(db: Db, form: Form<DeleteForm>) -> Result<Redirect> {
+    let id = form.into_inner().id;
+    db.run(move |conn| webhooks::query::delete_subscription(conn, &id))
+        .await?;
+
+    Ok(Redirect::to(uri!("/webhooks")))
+}
+
+#[get("/deliveries")]
+async fn deliveries(db: Db) -> Result<RawHtml<String>> {
+    let deliveries = db
+        .run(move |conn| {
+            let records = webhooks::query::recent_deliveries(conn, LOG_SIZE)?
+                .into_iter()
+                .map(|record| record.into())
+                .collect::<Vec<DeliveryPresenter>>();
+            Result::Ok(records)
+        })
+        .await?;
+
+    let template = Layout {
+        head: markup::new! {
+            title { "Webhook deliveries" }
+        },
+        body: Deliveries {
+            deliveries: &deliveries,
+        },
+    };
+    Ok(RawHtml(template.to_string()))
+}
+
+async fn render(db: &Db, errors: Vec<String>) -> Result<String> {
+    let subscriptions = db
+        .run(move |conn| {
+            let records = webhooks::query::all_subscriptions(conn)?
+                .into_iter()
+                .map(|record| record.into())
+                .collect::<Vec<SubscriptionPresenter>>();
+            Result::Ok(records)
+        })
+        .await?;
+
+    let template = Layout {
+        head: markup::new! {
+            title { "Webhooks" }
+        },
+        body: Index {
+            subscriptions: &subscriptions,
+            errors: &errors,
+        },
+    };
+    Ok(template.to_string())
+}
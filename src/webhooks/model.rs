@@ -0,0 +1,300 @@
+use crate::{
+    forms::{validate_events, validate_webhook_url},
+    schema::{webhook_deliveries, webhook_subscriptions},
+    time::timestamp,
+};
+use diesel::prelude::*;
+use diesel::sql_types::*;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::net::IpAddr;
+use time::{Duration, OffsetDateTime};
+use ulid::Ulid;
+
+pub(crate) const QUOTE_CREATED: &str = "quote.created";
+pub(crate) const QUOTE_UPDATED: &str = "quote.updated";
+pub(crate) const QUOTE_DELETED: &str = "quote.deleted";
+pub(crate) const QUOTE_ACCEPTED: &str = "quote.accepted";
+pub(crate) const QUOTE_REJECTED: &str = "quote.rejected";
+pub(crate) const LINE_ITEM_DATE_CREATED: &str = "line_item_date.created";
+pub(crate) const LINE_ITEM_DATE_UPDATED: &str = "line_item_date.updated";
+pub(crate) const LINE_ITEM_DATE_DELETED: &str = "line_item_date.deleted";
+pub(crate) const LINE_ITEM_CREATED: &str = "line_item.created";
+pub(crate) const LINE_ITEM_UPDATED: &str = "line_item.updated";
+pub(crate) const LINE_ITEM_DELETED: &str = "line_item.deleted";
+pub(crate) const LINE_ITEM_REORDERED: &str = "line_item.reordered";
+
+pub(crate) const EVENTS: [&str; 12] = [
+    QUOTE_CREATED,
+    QUOTE_UPDATED,
+    QUOTE_DELETED,
+    QUOTE_ACCEPTED,
+    QUOTE_REJECTED,
+    LINE_ITEM_DATE_CREATED,
+    LINE_ITEM_DATE_UPDATED,
+    LINE_ITEM_DATE_DELETED,
+    LINE_ITEM_CREATED,
+    LINE_ITEM_UPDATED,
+    LINE_ITEM_DELETED,
+    LINE_ITEM_REORDERED,
+];
+
+pub(crate) const STATUS_PENDING: &str = "pending";
+pub(crate) const STATUS_DELIVERED: &str = "delivered";
+pub(crate) const STATUS_FAILED: &str = "failed";
+
+// Attempts before a delivery is given up, spread over about eight hours by `backoff`
+pub(crate) const MAX_ATTEMPTS: i32 = 10;
+
+// Wait after a failed attempt: 30 seconds doubling with every attempt made so far
+pub(crate) fn backoff(attempts: i32) -> Duration {
+    Duration::seconds(30i64 << (attempts.clamp(1, MAX_ATTEMPTS) - 1))
+}
+
+// Hex encoded HMAC-SHA256 of the payload, sent as `X-Webhook-Signature: sha256=<signature>`
+pub(crate) fn signature(secret: &str, payload: &str) -> String {
+    let mut mac =
+        Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any size");
+    mac.update(payload.as_bytes());
+    mac.finalize()
+        .into_bytes()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect()
+}
+
+// Loopback, private, link-local and unspecified addresses, only reachable from inside the network
+pub(crate) fn internal_address(address: IpAddr) -> bool {
+    match address {
+        IpAddr::V4(address) => {
+            let [first, second, ..] = address.octets();
+            address.is_loopback()
+                || address.is_private()
+                || address.is_link_local()
+                || address.is_unspecified()
+                || address.is_broadcast()
+                // Shared address space of carrier-grade NAT, 100.64.0.0/10
+                || (first == 100 && second & 0xc0 == 64)
+        }
+        IpAddr::V6(address) => match address.to_ipv4_mapped() {
+            Some(address) => internal_address(IpAddr::V4(address)),
+            None => {
+                let first = address.segments()[0];
+                address.is_loopback()
+                    || address.is_unspecified()
+                    // Unique local fc00::/7 and link-local fe80::/10
+                    || first & 0xfe00 == 0xfc00
+                    || first & 0xffc0 == 0xfe80
+            }
+        },
+    }
+}
+
+// Hosts naming the server itself or its network rather than a public receiver
+pub(crate) fn internal_host(host: &str) -> bool {
+    let host = host.trim_end_matches('.').to_lowercase();
+    match host.trim_start_matches('[').trim_end_matches(']').parse() {
+        Ok(address) => internal_address(address),
+        Err(_) => {
+            host == "localhost"
+                || [".localhost", ".local", ".internal"]
+                    .iter()
+                    .any(|suffix| host.ends_with(suffix))
+        }
+    }
+}
+
+#[derive(Debug, Insertable, Queryable)]
+#[diesel(table_name = webhook_subscriptions)]
+pub(crate) struct WebhookSubscription {
+    pub(crate) id: String,
+    pub(crate) url: String,
+    pub(crate) secret: String,
+    pub(crate) events: String,
+    pub(crate) active: bool,
+    pub(crate) created_at: OffsetDateTime,
+    pub(crate) updated_at: OffsetDateTime,
+}
+
+impl WebhookSubscription {
+    pub(crate) fn subscribes_to(&self, event: &str) -> bool {
+        self.active && self.events.split(',').any(|subscribed| subscribed == event)
+    }
+}
+
+impl From<&SubscriptionForm> for WebhookSubscription {
+    fn from(value: &SubscriptionForm) -> Self {
+        let now = OffsetDateTime::now_utc();
+        WebhookSubscription {
+            id: Ulid::new().to_string(),
+            url: value.url.trim().to_string(),
+            secret: value
+                .secret
+                .clone()
+                .filter(|secret| !secret.trim().is_empty())
+                .unwrap_or_else(crate::share_links::model::token),
+            events: value
+                .events
+                .iter()
+                .filter(|event| EVENTS.contains(&event.as_str()))
+                .cloned()
+                .collect::<Vec<String>>()
+                .join(","),
+            active: true,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
+#[derive(Debug, Insertable, Queryable, Selectable)]
+#[diesel(table_name = webhook_deliveries)]
+pub(crate) struct WebhookDelivery {
+    pub(crate) id: String,
+    pub(crate) subscription_id: String,
+    pub(crate) event: String,
+    pub(crate) payload: String,
+    pub(crate) status: String,
+    pub(crate) attempts: i32,
+    pub(crate) next_attempt_at: OffsetDateTime,
+    pub(crate) response_status: Option<i32>,
+    pub(crate) last_error: Option<String>,
+    pub(crate) created_at: OffsetDateTime,
+    pub(crate) delivered_at: Option<OffsetDateTime>,
+}
+
+impl WebhookDelivery {
+    pub(crate) fn new(subscription_id: &str, event: &str, payload: &str) -> WebhookDelivery {
+        let now = OffsetDateTime::now_utc();
+        WebhookDelivery {
+            id: Ulid::new().to_string(),
+            subscription_id: subscription_id.to_string(),
+            event: event.to_string(),
+            payload: payload.to_string(),
+            status: String::from(STATUS_PENDING),
+            attempts: 0,
+            next_attempt_at: now,
+            response_status: None,
+            last_error: None,
+            created_at: now,
+            delivered_at: None,
+        }
+    }
+}
+
+#[derive(Debug, QueryableByName)]
+pub(crate) struct DeliveryWithUrl {
+    #[diesel(sql_type = Text)]
+    pub(crate) url: String,
+    #[diesel(sql_type = Text)]
+    pub(crate) event: String,
+    #[diesel(sql_type = Text)]
+    pub(crate) status: String,
+    #[diesel(sql_type = Integer)]
+    pub(crate) attempts: i32,
+    #[diesel(sql_type = Nullable<Integer>)]
+    pub(crate) response_status: Option<i32>,
+    #[diesel(sql_type = Nullable<Text>)]
+    pub(crate) last_error: Option<String>,
+    #[diesel(sql_type = TimestamptzSqlite)]
+    pub(crate) created_at: OffsetDateTime,
+}
+
+#[derive(Debug, FromForm)]
+pub struct SubscriptionForm {
+    #[field(validate = validate_webhook_url())]
+    pub(crate) url: String,
+    pub(crate) secret: Option<String>,
+    #[field(validate = validate_events())]
+    pub(crate) events: Vec<String>,
+}
+
+#[derive(Debug, FromForm)]
+pub(crate) struct DeleteForm {
+    pub(crate) id: String,
+}
+
+#[derive(Debug)]
+pub struct SubscriptionPresenter {
+    pub id: String,
+    pub url: String,
+    pub secret: String,
+    pub events: Vec<String>,
+}
+
+impl From<WebhookSubscription> for SubscriptionPresenter {
+    fn from(value: WebhookSubscription) -> Self {
+        SubscriptionPresenter {
+            id: value.id,
+            url: value.url,
+            secret: value.secret,
+            events: value.events.split(',').map(String::from).collect(),
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct DeliveryPresenter {
+    pub url: String,
+    pub event: String,
+    pub status: String,
+    pub attempts: i32,
+    pub response_status: String,
+    pub last_error: String,
+    pub created_at: String,
+}
+
+impl From<DeliveryWithUrl> for DeliveryPresenter {
+    fn from(value: DeliveryWithUrl) -> Self {
+        DeliveryPresenter {
+            url: value.url,
+            event: value.event,
+            status: value.status,
+            attempts: value.attempts,
+            response_status: value
+                .response_status
+                .map(|status| status.to_string())
+                .unwrap_or_default(),
+            last_error: value.last_error.unwrap_or_default(),
+            created_at: timestamp(value.created_at),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use time::Duration;
+
+    #[test]
+    fn backoff() {
+        assert_eq!(Duration::seconds(30), super::backoff(1));
+        assert_eq!(Duration::seconds(120), super::backoff(3));
+        assert_eq!(Duration::seconds(15_360), super::backoff(10));
+    }
+
+    #[test]
+    fn internal_host() {
+        for host in [
+            "localhost",
+            "api.localhost",
+            "printer.local",
+            "metadata.google.internal",
+            "127.0.0.1",
+            "10.1.2.3",
+            "172.16.0.1",
+            "192.168.1.1",
+            "169.254.169.254",
+            "100.64.0.1",
+            "0.0.0.0",
+            "[::1]",
+            "[fd00::1]",
+            "[fe80::1]",
+            "[::ffff:127.0.0.1]",
+        ] {
+            assert!(super::internal_host(host), "{host}");
+        }
+        for host in ["example.com", "93.184.216.34", "[2606:4700::1111]"] {
+            assert!(!super::internal_host(host), "{host}");
+        }
+    }
+}
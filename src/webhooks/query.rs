@@ -0,0 +1,175 @@
+use crate::{
+    Result,
+    schema::{webhook_deliveries, webhook_subscriptions},
+    webhooks::model::{
+        DeliveryWithUrl, MAX_ATTEMPTS, STATUS_DELIVERED, STATUS_FAILED, STATUS_PENDING,
+        WebhookDelivery, WebhookSubscription, backoff,
+    },
+};
+use diesel::prelude::*;
+use rocket::serde::{
+    Serialize,
+    json::{self, json},
+};
+use time::{OffsetDateTime, format_description::well_known::Rfc3339};
+use ulid::Ulid;
+
+pub(crate) fn all_subscriptions(
+    connection: &mut SqliteConnection,
+) -> Result<Vec<WebhookSubscription>> {
+    let records = webhook_subscriptions::table
+        .order_by(webhook_subscriptions::created_at)
+        .get_results(connection)?;
+    Ok(records)
+}
+
+pub(crate) fn insert_subscription(
+    connection: &mut SqliteConnection,
+    record: &WebhookSubscription,
+) -> Result {
+    diesel::dsl::insert_into(webhook_subscriptions::table)
+        .values(record)
+        .execute(connection)?;
+    Ok(())
+}
+
+pub(crate) fn delete_subscription<S: AsRef<str>>(
+    connection: &mut SqliteConnection,
+    id: S,
+) -> Result {
+    connection.transaction::<_, crate::error::AppError, _>(|tx| {
+        _ = diesel::dsl::delete(webhook_deliveries::table)
+            .filter(webhook_deliveries::subscription_id.eq(id.as_ref()))
+            .execute(tx)?;
+
+        _ = diesel::dsl::delete(webhook_subscriptions::table)
+            .filter(webhook_subscriptions::id.eq(id.as_ref()))
+            .execute(tx)?;
+
+        Ok(())
+    })
+}
+
+// Queues the event for every subscriber. Called by the `query` modules inside the transaction
+// of the change, so an event is delivered if and only if the change is committed.
+pub(crate) fn enqueue<T: Serialize>(
+    tx: &mut SqliteConnection,
+    event: &str,
+    quote_id: &str,
+    data: &T,
+) -> Result {
+    let subscriptions = all_subscriptions(tx)?
+        .into_iter()
+        .filter(|subscription| subscription.subscribes_to(event))
+        .collect::<Vec<WebhookSubscription>>();
+    if subscriptions.is_empty() {
+        return Ok(());
+    }
+
+    let payload = json::to_string(&json!({
+        "id": Ulid::new().to_string(),
+        "event": event,
+        "quote_id": quote_id,
+        "occurred_at": OffsetDateTime::now_utc().format(&Rfc3339)?,
+        "data": data,
+    }))?;
+    let records = subscriptions
+        .iter()
+        .map(|subscription| WebhookDelivery::new(&subscription.id, event, &payload))
+        .collect::<Vec<WebhookDelivery>>();
+
+    diesel::dsl::insert_into(webhook_deliveries::table)
+        .values(&records)
+        .execute(tx)?;
+    Ok(())
+}
+
+// Pending deliveries whose next attempt is due, with the URL and secret of their subscription
+pub(crate) fn due(
+    connection: &mut SqliteConnection,
+    limit: i64,
+) -> Result<Vec<(WebhookDelivery, String, String)>> {
+    let records = webhook_deliveries::table
+        .inner_join(webhook_subscriptions::table)
+        .filter(webhook_deliveries::status.eq(STATUS_PENDING))
+        .filter(webhook_deliveries::next_attempt_at.le(OffsetDateTime::now_utc()))
+        .order_by(webhook_deliveries::next_attempt_at)
+        .limit(limit)
+        .select((
+            WebhookDelivery::as_select(),
+            webhook_subscriptions::url,
+            webhook_subscriptions::secret,
+        ))
+        .get_results(connection)?;
+    Ok(records)
+}
+
+pub(crate) fn delivered(
+    connection: &mut SqliteConnection,
+    record: &WebhookDelivery,
+    response_status: i32,
+) -> Result {
+    _ = diesel::dsl::update(webhook_deliveries::table)
+        .filter(webhook_deliveries::id.eq(&record.id))
+        .set((
+            webhook_deliveries::status.eq(STATUS_DELIVERED),
+            webhook_deliveries::attempts.eq(record.attempts + 1),
+            webhook_deliveries::response_status.eq(Some(response_status)),
+            webhook_deliveries::last_error.eq(None::<String>),
+            webhook_deliveries::delivered_at.eq(Some(OffsetDateTime::now_utc())),
+        ))
+        .execute(connection)?;
+    Ok(())
+}
+
+// Schedules the next attempt, or gives up after `MAX_ATTEMPTS`
+pub(crate) fn failed(
+    connection: &mut SqliteConnection,
+    record: &WebhookDelivery,
+    response_status: Option<i32>,
+    error: &str,
+) -> Result {
+    let attempts = record.attempts + 1;
+    let status = if attempts >= MAX_ATTEMPTS {
+        STATUS_FAILED
+    } else {
+        STATUS_PENDING
+    };
+
+    _ = diesel::dsl::update(webhook_deliveries::table)
+        .filter(webhook_deliveries::id.eq(&record.id))
+        .set((
+            webhook_deliveries::status.eq(status),
+            webhook_deliveries::attempts.eq(attempts),
+            webhook_deliveries::next_attempt_at.eq(OffsetDateTime::now_utc() + backoff(attempts)),
+            webhook_deliveries::response_status.eq(response_status),
+            webhook_deliveries::last_error.eq(Some(error)),
+        ))
+        .execute(connection)?;
+    Ok(())
+}
+
+pub(crate) fn recent_deliveries(
+    connection: &mut SqliteConnection,
+    limit: i64,
+) -> Result<Vec<DeliveryWithUrl>> {
+    // language=SQL
+    let sql = r#"
+      select
+        s.url,
+        d.event,
+        d.status,
+        d.attempts,
+        d.response_status,
+        d.last_error,
+        d.created_at
+      from webhook_deliveries d
+        inner join webhook_subscriptions s on d.subscription_id = s.id
+      order by d.created_at desc, d.id desc
+      limit ?
+    "#;
+    let records = diesel::dsl::sql_query(sql)
+        .bind::<diesel::sql_types::BigInt, _>(limit)
+        .load(connection)?;
+    Ok(records)
+}
@@ -0,0 +1,103 @@
+use crate::webhooks::model::{DeliveryPresenter, EVENTS, SubscriptionPresenter};
+
+markup::define! {
+    Index<'a>(subscriptions: &'a Vec<SubscriptionPresenter>, errors: &'a Vec<String>) {
+        main[id = "container", class = "w-full px-4 py-0 mx-auto my-0 max-w-[60rem]"] {
+            div[id = "header", class = "flex flex-wrap gap-3 justify-between mt-4 mb-8"] {
+                h1[class = "text-header text-[2rem]/[1.1] box-border m-0 p-0 font-bold"] {"Webhooks"}
+                a[class = "button button-light", href = "/webhooks/deliveries"] { "Delivery log" }
+            }
+
+            @for subscription in *subscriptions {
+                div[class = "flex flex-wrap justify-between items-start gap-3 bg-white rounded-md mb-4 px-4 py-2 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]"] {
+                    div[class = "flex-1"] {
+                        div[class = "font-bold break-all"] { @subscription.url }
+                        div[class = "text-[0.875rem] text-muted"] { @subscription.events.join(", ") }
+                        div[class = "text-[0.75rem] text-muted break-all"] { "Secret: " @subscription.secret }
+                    }
+                    form[action = "/webhooks/delete", method = "post"] {
+                        input[name = "id", "type" = "hidden", value = &subscription.id] {}
+                        button[class = "button button-light", "type" = "submit"] { "Delete" }
+                    }
+                }
+            }
+
+            form[action = "/webhooks/create",
+                method = "post",
+                class = "flex flex-col gap-3 bg-white rounded-md mb-4 p-4 shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]",
+                autocomplete = "off",
+                novalidate,
+                "accept-charset" = "UTF-8"] {
+
+                h2[class = "text-[1.5rem] font-bold"] { "New subscription" }
+
+                @if !errors.is_empty() {
+                    div[class = "w-full text-primary bg-primary-bg p-2 rounded-md"] {
+                        @for message in *errors {
+                            p { @message }
+                        }
+                    }
+                }
+
+                label["for" = "webhook_url"] { "URL" }
+                input[id = "webhook_url",
+                    name = "url",
+                    class = "form-input",
+                    placeholder = "https://erp.example.com/hooks/quotes",
+                    "type" = "url"] {}
+                label["for" = "webhook_secret"] { "Secret (generated when left empty)" }
+                input[id = "webhook_secret",
+                    name = "secret",
+                    class = "form-input",
+                    "type" = "text"] {}
+                fieldset[class = "flex flex-wrap gap-x-4 gap-y-1"] {
+                    legend[class = "mb-1"] { "Events" }
+                    @for event in EVENTS {
+                        label[class = "flex items-center gap-1 text-[0.875rem]"] {
+                            input[name = "events", "type" = "checkbox", value = event] {}
+                            @event
+                        }
+                    }
+                }
+                div[class = "flex justify-end"] {
+                    input[name = "commit",
+                        "type" = "submit",
+                        value = "Add subscription",
+                        class = "button button-secondary"] {}
+                }
+            }
+        }
+    }
+
+    Deliveries<'a>(deliveries: &'a Vec<DeliveryPresenter>) {
+        main[id = "container", class = "w-full px-4 py-0 mx-auto my-0 max-w-[60rem]"] {
+            a[href = "/webhooks"] { "← Back to webhooks" }
+            h1[class = "text-header text-[2rem]/[1.1] mt-4 mb-8 p-0 font-bold"] { "Delivery log" }
+
+            @if deliveries.is_empty() {
+                div[class = "p-4 border-2 border-[hsl(0,6%,93%)] border-dashed text-center"] {
+                    p[class = "[font-size:1.125rem] text-header font-bold"] { "Nothing has been delivered yet" }
+                }
+            }
+            @for delivery in *deliveries {
+                div[class = "bg-white rounded-md mb-2 px-4 py-2 text-[0.875rem] shadow-[1px_3px_6px_hsl(0,0%,0%,0.1)]"] {
+                    div[class = "flex flex-wrap items-center gap-2"] {
+                        span[class = "font-bold"] { @delivery.event }
+                        span[class = "break-all"] { "to " @delivery.url }
+                        span[class = "text-[0.75rem] opacity-70"] { @delivery.created_at }
+                        span[class = "ml-auto font-bold"] { @delivery.status }
+                    }
+                    div[class = "text-[0.75rem] opacity-70"] {
+                        {format!("{} attempt(s)", delivery.attempts)}
+                        @if !delivery.response_status.is_empty() {
+                            " · HTTP " @delivery.response_status
+                        }
+                        @if !delivery.last_error.is_empty() {
+                            " · " @delivery.last_error
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use crate::{
+    Db, Result,
+    webhooks::{
+        self,
+        model::{WebhookDelivery, internal_address, internal_host, signature},
+    },
+};
+use reqwest::{
+    dns::{Addrs, Name, Resolve, Resolving},
+    redirect,
+};
+use rocket::{
+    Orbit, Rocket,
+    tokio::{self, net::lookup_host, time::sleep},
+};
+use std::{net::SocketAddr, sync::Arc, time::Duration};
+
+// How often the outbox is checked for due deliveries, and how many are sent at a time
+const INTERVAL: Duration = Duration::from_secs(5);
+const BATCH_SIZE: i64 = 20;
+
+// Starts delivering the outbox in the background until Rocket shuts down
+pub(crate) async fn start(rocket: &Rocket<Orbit>) {
+    let db = Db::get_one(rocket)
+        .await
+        .expect("failure obtaining database connection");
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .redirect(redirect::Policy::none())
+        .dns_resolver(Arc::new(PublicResolver))
+        .build()
+        .expect("failure building webhook client");
+    let shutdown = rocket.shutdown();
+
+    tokio::spawn(async move {
+        loop {
+            if let Err(error) = deliver_due(&db, &client).await {
+                log::error!("webhook delivery failed: {error:?}");
+            }
+
+            tokio::select! {
+                _ = shutdown.clone() => break,
+                _ = sleep(INTERVAL) => {}
+            }
+        }
+    });
+}
+
+async fn deliver_due(db: &Db, client: &reqwest::Client) -> Result {
+    let deliveries = db
+        .run(|conn| webhooks::query::due(conn, BATCH_SIZE))
+        .await?;
+
+    for (delivery, url, secret) in deliveries {
+        let outcome = post(client, &delivery, &url, &secret).await;
+        db.run(move |conn| match outcome {
+            Ok(status) => webhooks::query::delivered(conn, &delivery, status),
+            Err((status, error)) => webhooks::query::failed(conn, &delivery, status, &error),
+        })
+        .await?;
+    }
+
+    Ok(())
+}
+
+// Resolves host names to their public addresses only, so a name pointing inside the network (or
+// changed to after the subscription was registered) is never connected to
+struct PublicResolver;
+
+impl Resolve for PublicResolver {
+    fn resolve(&self, name: Name) -> Resolving {
+        Box::pin(public_addresses(name))
+    }
+}
+
+async fn public_addresses(
+    name: Name,
+) -> std::result::Result<Addrs, Box<dyn std::error::Error + Send + Sync>> {
+    let addresses = lookup_host((name.as_str(), 0))
+        .await?
+        .filter(|address| !internal_address(address.ip()))
+        .collect::<Vec<SocketAddr>>();
+    if addresses.is_empty() {
+        Err(format!("{} has no public address", name.as_str()))?;
+    }
+    Ok(Box::new(addresses.into_iter()))
+}
+
+// The response status on success, or the status if any and a description of the failure
+async fn post(
+    client: &reqwest::Client,
+    delivery: &WebhookDelivery,
+    url: &str,
+    secret: &str,
+) -> std::result::Result<i32, (Option<i32>, String)> {
+    // Addresses in the URL skip the resolver, subscriptions may predate the check on registration
+    let parsed = reqwest::Url::parse(url).map_err(|error| (None, error.to_string()))?;
+    if parsed.host_str().is_none_or(internal_host) {
+        return Err((None, String::from("Refusing to deliver to a local address")));
+    }
+
+    let response = client
+        .post(url)
+        .header("Content-Type", "application/json")
+        .header("X-Webhook-Id", &delivery.id)
+        .header("X-Webhook-Event", &delivery.event)
+        .header(
+            "X-Webhook-Signature",
+            format!("sha256={}", signature(secret, &delivery.payload)),
+        )
+        .body(delivery.payload.clone())
+        .send()
+        .await
+        .map_err(|error| (None, error.to_string()))?;
+
+    let status = response.status();
+    if status.is_success() {
+        Ok(i32::from(status.as_u16()))
+    } else {
+        Err((
+            Some(i32::from(status.as_u16())),
+            format!("Unexpected response {status}"),
+        ))
+    }
+}
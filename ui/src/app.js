@@ -1,3 +1,137 @@
-import "htmx.org";
+import htmx from "htmx.org";
 import * as hyperscript from "hyperscript.org";
 hyperscript.browserInit();
+
+// Drag-and-drop ordering: children with `.sortable-item` can be dragged by their `.sortable-handle`
+// within a `.sortable` container, which then triggers an `end` event for htmx to post the new order.
+htmx.onLoad((content) => {
+    const containers = content.matches(".sortable") ? [content] : [];
+    containers.push(...content.querySelectorAll(".sortable"));
+    containers.forEach(initSortable);
+});
+
+function initSortable(container) {
+    if (container.dataset.sortable === "ready") {
+        return;
+    }
+    container.dataset.sortable = "ready";
+
+    let dragged = null;
+
+    container.addEventListener("pointerdown", (event) => {
+        const item = event.target.closest(".sortable-handle")?.closest(".sortable-item");
+        if (item && item.parentElement === container) {
+            item.draggable = true;
+        }
+    });
+
+    container.addEventListener("dragstart", (event) => {
+        dragged = event.target.closest(".sortable-item");
+        if (!dragged) {
+            return;
+        }
+        event.dataTransfer.effectAllowed = "move";
+        dragged.style.opacity = "0.5";
+    });
+
+    container.addEventListener("dragover", (event) => {
+        if (!dragged) {
+            return;
+        }
+        event.preventDefault();
+        const target = event.target.closest(".sortable-item");
+        if (!target || target === dragged || target.parentElement !== container) {
+            return;
+        }
+        const rect = target.getBoundingClientRect();
+        const after = event.clientY > rect.top + rect.height / 2;
+        container.insertBefore(dragged, after ? target.nextSibling : target);
+    });
+
+    container.addEventListener("drop", (event) => event.preventDefault());
+
+    container.addEventListener("dragend", () => {
+        if (!dragged) {
+            return;
+        }
+        dragged.style.opacity = "";
+        dragged.draggable = false;
+        dragged = null;
+        htmx.trigger(container, "end");
+    });
+}
+
+// Catalog autocomplete: picking a suggestion fills the line item form, every field stays editable.
+document.addEventListener("click", (event) => {
+    const suggestion = event.target.closest("[data-catalog-item]");
+    if (suggestion) {
+        const form = suggestion.closest("form");
+        form.elements.namedItem("name").value = suggestion.dataset.name;
+        form.elements.namedItem("description").value = suggestion.dataset.description;
+        form.elements.namedItem("unit").value = suggestion.dataset.unit;
+        form.elements.namedItem("unit_price").value = suggestion.dataset.unitPrice;
+    }
+    document.querySelectorAll(".catalog-suggestions").forEach((suggestions) => {
+        suggestions.replaceChildren();
+    });
+});
+
+// Signature pads: drawing on the canvas of a `.signature-pad` keeps its hidden input filled with
+// the drawing as a PNG data URL, the button clears both.
+htmx.onLoad((content) => {
+    content.querySelectorAll(".signature-pad").forEach(initSignaturePad);
+});
+
+function initSignaturePad(pad) {
+    const canvas = pad.querySelector("canvas");
+    const input = pad.querySelector("input[type='hidden']");
+    const context = canvas.getContext("2d");
+    context.lineWidth = 2;
+    context.lineCap = "round";
+    let drawing = false;
+
+    const point = (event) => {
+        const rect = canvas.getBoundingClientRect();
+        return [
+            (event.clientX - rect.left) * canvas.width / rect.width,
+            (event.clientY - rect.top) * canvas.height / rect.height,
+        ];
+    };
+
+    canvas.addEventListener("pointerdown", (event) => {
+        drawing = true;
+        canvas.setPointerCapture(event.pointerId);
+        context.beginPath();
+        context.moveTo(...point(event));
+    });
+
+    canvas.addEventListener("pointermove", (event) => {
+        if (!drawing) {
+            return;
+        }
+        context.lineTo(...point(event));
+        context.stroke();
+    });
+
+    canvas.addEventListener("pointerup", () => {
+        drawing = false;
+        input.value = canvas.toDataURL("image/png");
+    });
+
+    pad.querySelector("button").addEventListener("click", () => {
+        context.clearRect(0, 0, canvas.width, canvas.height);
+        input.value = "";
+    });
+}
+
+// Changes refused because the quote is locked (409) are explained in the flash area.
+document.addEventListener("htmx:responseError", (event) => {
+    if (event.detail.xhr.status !== 409) {
+        return;
+    }
+    const message = document.createElement("div");
+    message.className = "text-[0.875rem] text-white px-4 py-2 bg-dark rounded-full animate-appear-then-fade";
+    message.textContent = event.detail.xhr.responseText;
+    message.addEventListener("animationend", () => message.remove());
+    document.getElementById("flash")?.replaceChildren(message);
+});